@@ -1,148 +1,1009 @@
-use notify_rust::Notification;
-use std::time::{Instant, Duration};
-use sysinfo::System;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
 
-#[derive(Clone, Debug)]
+use crate::collector::{ProcessSample, Sample};
+use crate::config::{deserialize_duration, AlertsConfig, NotificationsConfig};
+use crate::disk;
+use crate::network::glob_match;
+use crate::notify::{self, Notifier};
+use crate::process::ProcessMatcher;
+use crate::rule::Rule;
+use crate::sensors::TemperatureSample;
+use crate::watchdog::{self, ProcessEvent, ProcessEventKind, WatchConfig, Watchdog};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Metric {
+    Cpu,
+    Memory,
+    Network,
+    Disk,
+    // Porcentaje de la swap en uso
+    Swap,
+    // Porcentaje de la temperatura crítica de cada sensor
+    Temperature,
+}
+
+impl Metric {
+    // Las series de `expr` que corresponden a cada métrica. Las de E/S de
+    // disco no cuentan: el responsable de `Disk` es el disco más lleno.
+    fn of_series(name: &str) -> Option<Metric> {
+        match name {
+            "cpu_total" | "cpu_core" | "cgroup_cpu_pct" | "cgroup_cpu_cores" | "cgroup_throttled_pct"
+            | "cgroup_unit_cpu" | "proc_cpu" | "proc_cpu_total" => Some(Metric::Cpu),
+            "mem_pct" | "mem_used_mb" | "mem_available_mb" | "cgroup_mem_pct" | "cgroup_unit_mem_mb"
+            | "proc_mem_mb" | "proc_mem_total_mb" => Some(Metric::Memory),
+            "disk_free_pct" | "disk_used_pct" | "disk_free_mb" | "disk_inodes_free_pct" | "disk_hours_to_full" => {
+                Some(Metric::Disk)
+            }
+            "swap_pct" | "swap_used_mb" | "swap_in_mbs" | "swap_out_mbs" => Some(Metric::Swap),
+            "temp_c" | "temp_crit_pct" | "temp_crit_margin_c" => Some(Metric::Temperature),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+            Metric::Network => "network",
+            Metric::Disk => "disk",
+            Metric::Swap => "swap",
+            Metric::Temperature => "temperature",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Metric::Cpu => "CPU",
+            Metric::Memory => "Memoria",
+            Metric::Network => "Red",
+            Metric::Disk => "Disco",
+            Metric::Swap => "Swap",
+            Metric::Temperature => "Temperatura",
+        }
+    }
+
+    pub fn headline(self) -> &'static str {
+        match self {
+            Metric::Cpu => "¡Alto uso de CPU detectado!",
+            Metric::Memory => "¡Alto uso de memoria detectado!",
+            Metric::Network => "¡Alto tráfico de red detectado!",
+            Metric::Disk => "¡Poco espacio en disco!",
+            Metric::Swap => "¡Uso alto de la swap!",
+            Metric::Temperature => "¡Temperatura cerca de la crítica!",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Metric::Cpu | Metric::Memory | Metric::Disk | Metric::Swap => "%",
+            Metric::Temperature => "% de la crítica",
+            Metric::Network => " Mbps",
+        }
+    }
+
+    // Serie de las expresiones equivalente a la métrica
+    pub fn series(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu_total",
+            Metric::Memory => "mem_pct",
+            Metric::Network => "net_rx_mbps",
+            Metric::Disk => "disk_used_pct",
+            Metric::Swap => "swap_pct",
+            Metric::Temperature => "temp_crit_pct",
+        }
+    }
+}
+
+// Umbral numérico de resolución para `metric`, o expresión para `expr`.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Clear {
+    Value(f32),
+    Expr(String),
+}
+
+// Se dispara cuando `metric` supera `threshold` (o cuando se cumple `expr`)
+// durante al menos `for` y `for_samples` muestras seguidas, y se resuelve
+// cuando baja de `clear` (o se cumple la expresión `clear`).
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AlertCondition {
-    pub cpu_threshold: f32,
-    pub memory_threshold: f32,
-    pub network_threshold: f32,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub metric: Option<Metric>,
+    #[serde(default)]
+    pub threshold: Option<f32>,
+    // Ej. `avg(cpu_total, 1m) > 85 and mem_pct > 70`
+    #[serde(default)]
+    pub expr: Option<String>,
+    #[serde(default)]
+    pub clear: Option<Clear>,
+    // Procesos sobre los que se evalúan las series `proc_*` de `expr`
+    #[serde(default)]
+    pub process: Option<ProcessMatcher>,
+    #[serde(default, rename = "for", deserialize_with = "deserialize_duration")]
+    pub for_duration: Duration,
+    #[serde(default = "default_for_samples")]
+    pub for_samples: u32,
+    // Destinos de `[notifications.sinks]` (o "desktop"); sin valor se usan los por defecto
+    #[serde(default)]
+    pub notify: Option<Vec<String>>,
+}
+
+fn default_for_samples() -> u32 {
+    1
+}
+
+impl AlertCondition {
+    pub fn new(metric: Metric, threshold: f32) -> Self {
+        AlertCondition {
+            id: None,
+            metric: Some(metric),
+            threshold: Some(threshold),
+            expr: None,
+            clear: None,
+            process: None,
+            for_duration: Duration::ZERO,
+            for_samples: default_for_samples(),
+            notify: None,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.id
+            .clone()
+            .or_else(|| self.metric.map(|m| m.as_str().to_string()))
+            .unwrap_or_else(|| "regla".to_string())
+    }
+
+    // Umbral numérico de resolución; las reglas con `expr` no tienen.
+    pub fn clear_threshold(&self) -> Option<f32> {
+        match (&self.clear, self.threshold) {
+            (Some(Clear::Value(clear)), _) => Some(*clear),
+            (None, Some(threshold)) if self.metric.is_some() => Some(threshold),
+            _ => None,
+        }
+    }
+
+    // Las condiciones por métrica se traducen a la misma forma que `expr`.
+    pub fn trigger_expr(&self) -> String {
+        match (&self.expr, self.metric) {
+            (Some(expr), _) => expr.clone(),
+            (None, Some(metric)) => format!("{} > {}", metric.series(), self.threshold.unwrap_or_default()),
+            (None, None) => String::new(),
+        }
+    }
+
+    pub fn clear_expr(&self) -> Option<String> {
+        if let Some(Clear::Expr(expr)) = &self.clear {
+            return Some(expr.clone());
+        }
+        let metric = self.metric?;
+        Some(format!("{} < {}", metric.series(), self.clear_threshold()?))
+    }
+
+    pub fn rule(&self) -> Result<Rule, String> {
+        let process = self
+            .process
+            .as_ref()
+            .map(|p| p.compile())
+            .transpose()
+            .map_err(|e| format!("process.cmdline: {}", e))?;
+        Rule::new(&self.trigger_expr(), self.clear_expr().as_deref(), process).map_err(|e| e.to_string())
+    }
+
+    // Métrica de la que se informa el responsable (proceso o disco). Para
+    // `expr` se toma la de la primera serie de la regla que tenga una.
+    fn offender_metric(&self) -> Option<Metric> {
+        if self.metric.is_some() {
+            return self.metric;
+        }
+        self.rule().ok()?.series().into_iter().find_map(|s| Metric::of_series(s.name))
+    }
+
+    pub fn describe(&self) -> String {
+        let mut text = match (&self.expr, self.metric) {
+            (Some(expr), _) => match &self.clear {
+                Some(Clear::Expr(clear)) => format!("{} (se resuelve con {})", expr, clear),
+                _ => expr.clone(),
+            },
+            (None, Some(metric)) => {
+                let unit = metric.unit();
+                format!(
+                    "{} > {:.2}{} (se resuelve < {:.2}{})",
+                    metric.label(),
+                    self.threshold.unwrap_or_default(),
+                    unit,
+                    self.clear_threshold().unwrap_or_default(),
+                    unit
+                )
+            }
+            (None, None) => String::new(),
+        };
+        if let Some(process) = &self.process {
+            text.push_str(&format!(" en procesos {}", process.describe()));
+        }
+        if !self.for_duration.is_zero() {
+            text.push_str(&format!(" durante {:?}", self.for_duration));
+        }
+        if self.for_samples > 1 {
+            text.push_str(&format!(" en {} muestras seguidas", self.for_samples));
+        }
+        text
+    }
+}
+
+pub fn default_conditions() -> Vec<AlertCondition> {
+    vec![
+        AlertCondition::new(Metric::Cpu, 80.0),
+        AlertCondition::new(Metric::Memory, 70.0),
+        AlertCondition::new(Metric::Network, 10.0),
+    ]
+}
+
+// Ciclo de vida de una alerta guardada. `Interrupted` marca las que seguían
+// activas cuando se detuvo el monitoreo o se quitó su condición.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlertState {
+    Firing,
+    Resolved,
+    Interrupted,
+}
+
+impl AlertState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertState::Firing => "firing",
+            AlertState::Resolved => "resolved",
+            AlertState::Interrupted => "interrupted",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "firing" => Some(AlertState::Firing),
+            "resolved" => Some(AlertState::Resolved),
+            "interrupted" => Some(AlertState::Interrupted),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AlertState::Firing => "activa",
+            AlertState::Resolved => "resuelta",
+            AlertState::Interrupted => "interrumpida",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AlertRecord {
+    pub rule_id: String,
+    pub metric: String,
+    // Valor que disparó la alerta
+    pub value: f32,
+    pub threshold: f32,
+    pub started_at: DateTime<Local>,
+    pub resolved_at: Option<DateTime<Local>>,
+    pub top_offender: Option<String>,
+    pub state: AlertState,
+}
+
+#[derive(Clone, Debug)]
+pub enum AlertEvent {
+    Firing {
+        condition: AlertCondition,
+        record: AlertRecord,
+    },
+    Resolved {
+        condition: AlertCondition,
+        record: AlertRecord,
+        // Valor que la llevó por debajo de `clear`
+        value: f32,
+    },
+    // Un proceso vigilado terminó, empezó o se reinició. La terminación
+    // activa una alerta con el id de la vigilancia y el siguiente inicio la
+    // resuelve; `record` es la fila de `alerts` a guardar, si hay.
+    Process {
+        watch: WatchConfig,
+        event: ProcessEvent,
+        record: Option<AlertRecord>,
+    },
+}
+
+// Cuántas alertas recientes se conservan en memoria
+pub const HISTORY_LEN: usize = 100;
+
+// inactiva -> pendiente (umbral superado, aún no sostenido) -> activa -> inactiva
+#[derive(Default)]
+enum Phase {
+    #[default]
+    Idle,
+    Pending {
+        since: DateTime<Local>,
+        samples: u32,
+    },
+    Firing(AlertRecord),
+}
+
+#[derive(Default)]
+struct ConditionState {
+    phase: Phase,
+    last_fired: Option<DateTime<Local>>,
+}
+
+// Los tiempos se miden con la hora de cada muestra, así la evaluación sobre
+// el historial de `metrics.db` se comporta igual que en vivo.
+fn elapsed(since: DateTime<Local>, now: DateTime<Local>) -> Duration {
+    (now - since).to_std().unwrap_or_default()
+}
+
+fn compile(conditions: &[AlertCondition], previous: Vec<(AlertCondition, Rule)>) -> Vec<(AlertCondition, Rule)> {
+    let mut previous = previous;
+    let mut rules = Vec::new();
+    for condition in conditions {
+        // Una condición sin cambios conserva el historial de sus ventanas
+        if let Some(i) = previous.iter().position(|(c, _)| c == condition) {
+            rules.push(previous.swap_remove(i));
+            continue;
+        }
+        match condition.rule() {
+            Ok(rule) => rules.push((condition.clone(), rule)),
+            Err(e) => eprintln!("Condición `{}` ignorada: {}", condition.id(), e),
+        }
+    }
+    rules
 }
 
 pub struct AlertManager {
-    pub alerts: Vec<AlertCondition>,
-    pub triggered_alerts: Vec<String>,
-    last_cpu_alert: Option<Instant>,
-    last_memory_alert: Option<Instant>,
-    last_network_alert: Option<Instant>,
+    rules: Vec<(AlertCondition, Rule)>,
+    // Alertas recientes, de la más vieja a la más nueva
+    pub history: VecDeque<AlertRecord>,
+    cooldown: Duration,
+    notifier: Notifier,
+    // Con `false` no se imprimen los avisos (evaluación sobre el historial)
+    echo: bool,
+    watchdog: Watchdog,
+    states: HashMap<String, ConditionState>,
 }
 
 impl AlertManager {
-    pub fn new() -> Self {
+    pub fn new(config: &AlertsConfig, notifications: &NotificationsConfig) -> Self {
+        AlertManager {
+            rules: compile(&config.conditions, Vec::new()),
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            cooldown: config.cooldown,
+            notifier: Notifier::new(notifications),
+            echo: true,
+            watchdog: Watchdog::new(&config.watch),
+            states: HashMap::new(),
+        }
+    }
+
+    // Sin avisos ni salida: solo devuelve los eventos. Las vigilancias de
+    // procesos no se evalúan, ya que el historial no guarda todos los procesos.
+    pub fn silent(config: &AlertsConfig) -> Self {
+        let notifications = NotificationsConfig {
+            enabled: false,
+            ..NotificationsConfig::default()
+        };
+        let config = AlertsConfig {
+            watch: Vec::new(),
+            ..config.clone()
+        };
         AlertManager {
-            alerts: vec![
-                AlertCondition {
-                    cpu_threshold: 80.0,
-                    memory_threshold: 70.0,
-                    network_threshold: 10.0,
-                },
-            ],
-            triggered_alerts: Vec::new(),
-            last_cpu_alert: None,
-            last_memory_alert: None,
-            last_network_alert: None,
-        }
-    }
-
-    pub fn check_alerts(&mut self, cpu: f32, net: f32, mem: f32, system: &System, num_cores: f32) {
-        let alerts = self.alerts.clone();
-
-        for alert in alerts {
-            if cpu > alert.cpu_threshold {
-                let mut processes = system.processes().values().collect::<Vec<_>>();
-                processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap());
-                if let Some(top_process) = processes.first() {
-                    let top_cpu_percent = top_process.cpu_usage() / num_cores;
-                    self.trigger_cpu_alert(&alert, cpu, &top_process.name().to_string_lossy(), top_cpu_percent);
+            echo: false,
+            ..AlertManager::new(&config, &notifications)
+        }
+    }
+
+    // Reemplaza las condiciones de una sola vez. Las que conservan su id
+    // mantienen su estado (alertas activas y enfriamiento); las activas de
+    // condiciones quitadas se devuelven como interrumpidas.
+    pub fn reconfigure(&mut self, config: &AlertsConfig, notifications: &NotificationsConfig) -> Vec<AlertRecord> {
+        self.rules = compile(&config.conditions, std::mem::take(&mut self.rules));
+        self.cooldown = config.cooldown;
+        self.notifier = Notifier::new(notifications);
+        self.watchdog.reconfigure(&config.watch);
+        let mut ids: Vec<String> = self.rules.iter().map(|(c, _)| c.id()).collect();
+        ids.extend(self.watchdog.ids().map(str::to_string));
+        self.interrupt(|id| !ids.iter().any(|i| i == id))
+    }
+
+    // Cierra como interrumpidas todas las alertas activas, al detener el monitoreo.
+    pub fn interrupt_all(&mut self) -> Vec<AlertRecord> {
+        self.interrupt(|_| true)
+    }
+
+    fn interrupt(&mut self, remove: impl Fn(&str) -> bool) -> Vec<AlertRecord> {
+        let now = Local::now();
+        let mut interrupted = Vec::new();
+        let ids: Vec<String> = self.states.keys().filter(|id| remove(id)).cloned().collect();
+        for id in ids {
+            if let Some(ConditionState { phase: Phase::Firing(mut record), .. }) = self.states.remove(&id) {
+                record.state = AlertState::Interrupted;
+                record.resolved_at = Some(now);
+                self.update_history(&record);
+                interrupted.push(record);
+            }
+        }
+        interrupted
+    }
+
+    pub fn check_alerts(&mut self, sample: &Sample) -> Vec<AlertEvent> {
+        let now = sample.timestamp;
+        let mut events = Vec::new();
+
+        for (alert, rule) in &mut self.rules {
+            let (trigger, clear) = rule.evaluate(sample);
+            let state = self.states.entry(alert.id()).or_default();
+
+            match &mut state.phase {
+                Phase::Firing(record) => {
+                    if clear.active {
+                        let mut record = record.clone();
+                        record.state = AlertState::Resolved;
+                        record.resolved_at = Some(now);
+                        state.phase = Phase::Idle;
+                        let value = clear.value.or(trigger.value).unwrap_or_default() as f32;
+                        events.push(AlertEvent::Resolved { condition: alert.clone(), record, value });
+                    }
+                }
+                _ if !trigger.active => state.phase = Phase::Idle,
+                phase => {
+                    let (since, samples) = match phase {
+                        Phase::Pending { since, samples } => (*since, *samples + 1),
+                        _ => (now, 1),
+                    };
+                    *phase = Phase::Pending { since, samples };
+
+                    let sustained = samples >= alert.for_samples && elapsed(since, now) >= alert.for_duration;
+                    let cooled = state.last_fired.is_none_or(|last| elapsed(last, now) >= self.cooldown);
+
+                    if sustained && cooled {
+                        let record = AlertRecord {
+                            rule_id: alert.id(),
+                            metric: match alert.metric {
+                                Some(metric) => metric.as_str().to_string(),
+                                None => alert.trigger_expr(),
+                            },
+                            value: trigger.value.unwrap_or_default() as f32,
+                            threshold: trigger.threshold.unwrap_or_default() as f32,
+                            started_at: now,
+                            resolved_at: None,
+                            top_offender: alert
+                                .offender_metric()
+                                .and_then(|m| top_offender(m, sample, rule, alert.process.is_some())),
+                            state: AlertState::Firing,
+                        };
+                        state.phase = Phase::Firing(record.clone());
+                        state.last_fired = Some(now);
+                        events.push(AlertEvent::Firing { condition: alert.clone(), record });
+                    }
                 }
             }
+        }
 
-            if mem > alert.memory_threshold {
-                let mut processes_mem = system.processes().values().collect::<Vec<_>>();
-                processes_mem.sort_by(|a, b| b.memory().cmp(&a.memory()));
-                if let Some(top_process_mem) = processes_mem.first() {
-                    let mem_mb = top_process_mem.memory() as f64 / (1024.0 * 1024.0);
-                    self.trigger_memory_alert(&alert, mem, &top_process_mem.name().to_string_lossy(), mem_mb);
+        for (watch, event) in self.watchdog.observe(sample) {
+            let state = self.states.entry(watch.id.clone()).or_default();
+            let record = match (event.kind, &state.phase) {
+                // Ya está activa, ej. si terminó otro proceso de la misma vigilancia
+                (ProcessEventKind::Exited, Phase::Firing(_)) => None,
+                (ProcessEventKind::Exited, _) => {
+                    let record = AlertRecord {
+                        rule_id: watch.id.clone(),
+                        metric: "process".to_string(),
+                        value: 0.0,
+                        threshold: 1.0,
+                        started_at: now,
+                        resolved_at: None,
+                        top_offender: Some(format!("{} [{}]", event.name, event.pid)),
+                        state: AlertState::Firing,
+                    };
+                    state.phase = Phase::Firing(record.clone());
+                    Some(record)
+                }
+                (_, Phase::Firing(record)) => {
+                    let mut record = record.clone();
+                    record.state = AlertState::Resolved;
+                    record.resolved_at = Some(now);
+                    state.phase = Phase::Idle;
+                    Some(record)
                 }
+                _ => None,
+            };
+            if event.kind == ProcessEventKind::Exited && self.echo {
+                watchdog::restart(&watch, &event);
             }
+            events.push(AlertEvent::Process { watch, event, record });
+        }
 
-            if net > alert.network_threshold {
-                self.trigger_network_alert(&alert, net);
+        for event in &events {
+            match event {
+                AlertEvent::Firing { record, .. } => self.push_history(record),
+                AlertEvent::Resolved { record, .. } => self.update_history(record),
+                AlertEvent::Process { record: Some(record), .. } if record.state == AlertState::Firing => {
+                    self.push_history(record)
+                }
+                AlertEvent::Process { record: Some(record), .. } => self.update_history(record),
+                AlertEvent::Process { record: None, .. } => {}
             }
+            self.notify(event);
         }
+        events
     }
 
-    fn trigger_cpu_alert(&mut self, alert: &AlertCondition, cpu: f32, process_name: &str, process_cpu: f32) {
-        let now = Instant::now();
+    fn push_history(&mut self, record: &AlertRecord) {
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(record.clone());
+    }
+
+    fn update_history(&mut self, record: &AlertRecord) {
+        let entry = self
+            .history
+            .iter_mut()
+            .rev()
+            .find(|r| r.rule_id == record.rule_id && r.started_at == record.started_at);
+        if let Some(entry) = entry {
+            *entry = record.clone();
+        }
+    }
+
+    fn notify(&self, event: &AlertEvent) {
+        let (condition, record, value) = match event {
+            AlertEvent::Firing { condition, record } => (condition, record, record.value),
+            AlertEvent::Resolved { condition, record, value } => (condition, record, *value),
+            AlertEvent::Process { watch, event, record } => return self.notify_process(watch, event, record.as_ref()),
+        };
+        let offender = match condition.offender_metric() {
+            Some(Metric::Disk) => "Disco",
+            Some(Metric::Temperature) => "Sensor",
+            _ => "Proceso",
+        };
+        let process = record
+            .top_offender
+            .as_deref()
+            .map(|p| format!("{}: {}\n", offender, p))
+            .unwrap_or_default();
+        let duration = record
+            .resolved_at
+            .and_then(|end| (end - record.started_at).to_std().ok())
+            .map(|d| Duration::from_secs(d.as_secs()))
+            .unwrap_or_default();
 
-        if let Some(last) = self.last_cpu_alert {
-            if now.duration_since(last) < Duration::from_secs(10) {
-                return;
+        let firing = record.state == AlertState::Firing;
+        let (alert_message, summary, body) = match (firing, condition.metric) {
+            (true, Some(metric)) => {
+                let (value, threshold, unit) = (record.value, record.threshold, metric.unit());
+                (
+                    format!(
+                        "⚠️ ALERTA: {}\n{}Uso de {}: {:.2}{} (Umbral: {:.2}{})",
+                        metric.headline(), process, metric.label(), value, unit, threshold, unit
+                    ),
+                    format!("⚠️ ¡Alerta de {}!", metric.label()),
+                    format!(
+                        "{}Uso de {}: {:.2}{} (>{:.2}{})",
+                        process, metric.label(), value, unit, threshold, unit
+                    ),
+                )
             }
+            (false, Some(metric)) => {
+                let (clear, unit) = (condition.clear_threshold().unwrap_or_default(), metric.unit());
+                (
+                    format!(
+                        "✅ RESUELTA: {} volvió a {:.2}{} (< {:.2}{}) tras {:?}",
+                        metric.label(), value, unit, clear, unit, duration
+                    ),
+                    format!("✅ {} normalizada", metric.label()),
+                    format!(
+                        "Uso de {}: {:.2}{} (<{:.2}{}) tras {:?}",
+                        metric.label(), value, unit, clear, unit, duration
+                    ),
+                )
+            }
+            (true, None) => {
+                let expr = condition.trigger_expr();
+                (
+                    format!(
+                        "⚠️ ALERTA: Regla {}: {}\n{}Valor: {:.2} (Umbral: {:.2})",
+                        record.rule_id, expr, process, record.value, record.threshold
+                    ),
+                    format!("⚠️ ¡Alerta {}!", record.rule_id),
+                    format!("{}{}\nValor: {:.2} (Umbral: {:.2})", process, expr, record.value, record.threshold),
+                )
+            }
+            (false, None) => (
+                format!("✅ RESUELTA: {} volvió a {:.2} tras {:?}", record.rule_id, value, duration),
+                format!("✅ {} normalizada", record.rule_id),
+                format!("Valor: {:.2} tras {:?}", value, duration),
+            ),
+        };
+
+        if !self.echo {
+            return;
         }
+        println!("{}", alert_message);
 
-        self.last_cpu_alert = Some(now);
-        let alert_message = format!(
-            "⚠️ ALERTA: ¡Alto uso de CPU detectado!\nProceso: {} ({:.2}%)\nUso total de CPU: {:.2}% (Umbral: {:.2}%)",
-            process_name, process_cpu, cpu, alert.cpu_threshold
+        self.notifier.send(
+            condition.notify.as_deref(),
+            notify::Message {
+                event: record.state.as_str(),
+                summary,
+                body,
+                record: record.clone(),
+                current_value: value,
+            },
         );
+    }
+
+    fn notify_process(&self, watch: &WatchConfig, event: &ProcessEvent, record: Option<&AlertRecord>) {
+        let process = format!("{} [{}]", event.name, event.pid);
+        let after = record
+            .and_then(|r| (r.resolved_at? - r.started_at).to_std().ok())
+            .map(|d| format!(" tras {:?}", Duration::from_secs(d.as_secs())))
+            .unwrap_or_default();
+
+        let (alert_message, summary, body) = match event.kind {
+            ProcessEventKind::Exited => (
+                format!("⚠️ PROCESO TERMINADO: {}: {}", watch.id, process),
+                format!("⚠️ Proceso {} terminado", watch.id),
+                format!("{} ya no está en ejecución", process),
+            ),
+            ProcessEventKind::Started => (
+                format!("✅ PROCESO INICIADO: {}: {}{}", watch.id, process, after),
+                format!("✅ Proceso {} iniciado", watch.id),
+                format!("{} en ejecución{}", process, after),
+            ),
+            ProcessEventKind::Restarted => (
+                format!(
+                    "🔄 PROCESO REINICIADO: {}: {} [{} → {}]",
+                    watch.id,
+                    event.name,
+                    event.old_pid.unwrap_or_default(),
+                    event.pid
+                ),
+                format!("🔄 Proceso {} reiniciado", watch.id),
+                format!("{}: pid {} → {}", event.name, event.old_pid.unwrap_or_default(), event.pid),
+            ),
+        };
+
+        if !self.echo {
+            return;
+        }
         println!("{}", alert_message);
-        self.triggered_alerts.push(alert_message);
 
-        let _ = Notification::new()
-            .appname("Sistema de Defensa")
-            .icon("warning")
-            .summary("⚠️ ¡Alerta de CPU!")
-            .body(&format!(
-                "Proceso: {} ({:.2}%)\nUso total de CPU: {:.2}% (>{:.2}%)",
-                process_name, process_cpu, cpu, alert.cpu_threshold
-            ))
-            .show();
+        // Sin alerta que guardar, los destinos reciben igual los datos del proceso
+        let running = event.kind != ProcessEventKind::Exited;
+        let record = record.cloned().unwrap_or_else(|| AlertRecord {
+            rule_id: watch.id.clone(),
+            metric: "process".to_string(),
+            value: 1.0,
+            threshold: 1.0,
+            started_at: event.timestamp,
+            resolved_at: Some(event.timestamp),
+            top_offender: Some(process),
+            state: AlertState::Resolved,
+        });
+        self.notifier.send(
+            watch.notify.as_deref(),
+            notify::Message {
+                event: event.kind.as_str(),
+                summary,
+                body,
+                record,
+                current_value: if running { 1.0 } else { 0.0 },
+            },
+        );
     }
+}
 
-    fn trigger_memory_alert(&mut self, alert: &AlertCondition, mem_percent: f32, process_name: &str, mem_mb: f64) {
-        let now = Instant::now();
+// Proceso que más aporta a la métrica, como "nombre (valor)". En las reglas
+// por proceso se agrega el pid, ya que suele haber varios con el mismo nombre.
+// Para el disco, el punto de montaje más lleno con su tiempo hasta llenarse,
+// y para la temperatura, el sensor más cerca de su crítica.
+fn top_offender(metric: Metric, sample: &Sample, rule: &Rule, with_pid: bool) -> Option<String> {
+    let processes = sample.processes.iter().filter(|p| rule.matches(p));
+    let name = |p: &ProcessSample| match with_pid {
+        true => format!("{} [{}]", p.name, p.pid),
+        false => p.name.clone(),
+    };
+    match metric {
+        Metric::Cpu => processes
+            .max_by(|a, b| a.cpu.total_cmp(&b.cpu))
+            .map(|p| format!("{} ({:.2}%)", name(p), p.cpu)),
+        // Sin el uso de swap de cada proceso, el que más memoria ocupa
+        Metric::Memory | Metric::Swap => processes
+            .max_by_key(|p| p.memory)
+            .map(|p| format!("{} ({:.2} MB)", name(p), p.memory as f64 / (1024.0 * 1024.0))),
+        Metric::Network => None,
+        Metric::Disk => {
+            // Si la regla nombra puntos de montaje, solo entre esos
+            let mounts = rule.patterns("mount");
+            sample
+                .disks
+                .iter()
+                .filter(|d| mounts.is_empty() || mounts.iter().any(|p| glob_match(p, &d.mount)))
+                .filter_map(|d| Some((d, 100.0 - d.free_percent()?)))
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(d, used)| match d.full_in {
+                    Some(eta) => format!("{} ({:.2}%, se llena en {})", d.mount, used, disk::format_eta(eta)),
+                    None => format!("{} ({:.2}%)", d.mount, used),
+                })
+        }
+        Metric::Temperature => {
+            let sensors = rule.patterns("sensor");
+            sample
+                .temperatures
+                .iter()
+                .filter(|t| sensors.is_empty() || sensors.iter().any(|p| glob_match(p, &t.label)))
+                .max_by(|a, b| {
+                    let key = |t: &TemperatureSample| t.critical_percent().unwrap_or(f64::MIN);
+                    key(a).total_cmp(&key(b)).then(a.current.total_cmp(&b.current))
+                })
+                .map(|t| match t.critical {
+                    Some(critical) => format!("{} ({:.1} °C, crítica {:.1} °C)", t.label, t.current, critical),
+                    None => format!("{} ({:.1} °C)", t.label, t.current),
+                })
+        }
+    }
+}
 
-        if let Some(last) = self.last_memory_alert {
-            if now.duration_since(last) < Duration::from_secs(10) {
-                return;
-            }
+#[cfg(test)]
+mod tests {
+    use chrono::Duration as TimeDelta;
+
+    use super::*;
+    use crate::collector::test_sample;
+
+    fn sensor(label: &str, current: f32, critical: Option<f32>) -> TemperatureSample {
+        TemperatureSample {
+            label: label.to_string(),
+            current,
+            max: None,
+            critical,
         }
+    }
 
-        self.last_memory_alert = Some(now);
-        let alert_message = format!(
-            "⚠️ ALERTA: ¡Alto uso de memoria detectado!\nProceso: {} ({:.2} MB)\nUso de memoria: {:.2}% (Umbral: {:.2}%)",
-            process_name, mem_mb, mem_percent, alert.memory_threshold
-        );
-        println!("{}", alert_message);
-        self.triggered_alerts.push(alert_message);
+    fn expr(text: &str) -> AlertCondition {
+        AlertCondition {
+            id: Some("expr".to_string()),
+            metric: None,
+            threshold: None,
+            expr: Some(text.to_string()),
+            ..AlertCondition::new(Metric::Cpu, 0.0)
+        }
+    }
 
-        let _ = Notification::new()
-            .appname("Sistema de Defensa")
-            .icon("warning")
-            .summary("⚠️ ¡Alerta de Memoria!")
-            .body(&format!(
-                "Proceso: {} ({:.2} MB)\nUso de memoria: {:.2}% (>{:.2}%)",
-                process_name, mem_mb, mem_percent, alert.memory_threshold
-            ))
-            .show();
+    fn manager(conditions: Vec<AlertCondition>) -> AlertManager {
+        AlertManager::silent(&AlertsConfig {
+            cooldown: Duration::ZERO,
+            conditions,
+            watch: Vec::new(),
+        })
     }
 
-    fn trigger_network_alert(&mut self, alert: &AlertCondition, net: f32) {
-        let now = Instant::now();
+    // Muestras separadas por un segundo
+    fn sample_at(second: i64, temperatures: Vec<TemperatureSample>) -> Sample {
+        let mut sample = test_sample();
+        sample.timestamp += TimeDelta::seconds(second);
+        sample.temperatures = temperatures;
+        sample
+    }
 
-        if let Some(last) = self.last_network_alert {
-            if now.duration_since(last) < Duration::from_secs(10) {
-                return;
-            }
+    #[test]
+    fn temperature_threshold_is_relative_to_critical() {
+        let mut alerts = manager(vec![AlertCondition::new(Metric::Temperature, 90.0)]);
+
+        // 85 °C es mucho, pero está al 85% de una crítica de 100 °C; el
+        // disco a 60 °C ya está al 92% de la suya, y el sensor sin crítica no cuenta
+        let calm = vec![sensor("cpu", 85.0, Some(100.0)), sensor("acpi", 99.0, None)];
+        assert!(alerts.check_alerts(&sample_at(0, calm.clone())).is_empty());
+
+        let hot = vec![sensor("cpu", 85.0, Some(100.0)), sensor("nvme", 60.0, Some(65.0)), sensor("acpi", 99.0, None)];
+        let events = alerts.check_alerts(&sample_at(1, hot));
+        let [AlertEvent::Firing { record, .. }] = events.as_slice() else {
+            panic!("se esperaba una alerta: {:?}", events);
+        };
+        assert_eq!(record.metric, "temperature");
+        assert!((record.value - 92.31).abs() < 0.01, "{}", record.value);
+        assert_eq!(record.threshold, 90.0);
+        assert_eq!(record.top_offender.as_deref(), Some("nvme (60.0 °C, crítica 65.0 °C)"));
+
+        let events = alerts.check_alerts(&sample_at(2, calm));
+        assert!(matches!(events.as_slice(), [AlertEvent::Resolved { .. }]), "{:?}", events);
+    }
+
+    #[test]
+    fn temperature_offender_respects_sensor_selector() {
+        let mut alerts = manager(vec![expr(r#"temp_crit_pct{sensor="coretemp*"} > 80"#)]);
+
+        let sensors = vec![
+            sensor("coretemp Core 0", 85.0, Some(100.0)),
+            sensor("coretemp Core 1", 88.0, Some(100.0)),
+            sensor("nvme Composite", 64.0, Some(65.0)),
+        ];
+        let events = alerts.check_alerts(&sample_at(0, sensors));
+        let [AlertEvent::Firing { record, .. }] = events.as_slice() else {
+            panic!("se esperaba una alerta: {:?}", events);
+        };
+        assert_eq!(record.value, 88.0);
+        assert_eq!(record.top_offender.as_deref(), Some("coretemp Core 1 (88.0 °C, crítica 100.0 °C)"));
+    }
+
+    #[test]
+    fn offender_metric_comes_from_the_rule_series() {
+        let metric = |text: &str| expr(text).offender_metric();
+
+        // Las etiquetas y los nombres de otras series no cuentan
+        assert_eq!(metric(r#"psi_some_avg10{resource="memory"} > 20 and cpu_total > 50"#), Some(Metric::Cpu));
+        assert_eq!(metric(r#"disk_free_pct{mount="/tmp"} < 10 or temp_c > 90"#), Some(Metric::Disk));
+        assert_eq!(metric("cgroup_throttled_pct > 10"), Some(Metric::Cpu));
+        assert_eq!(metric("avg(swap_in_mbs, 1m) > 5"), Some(Metric::Swap));
+        assert_eq!(metric("load1 > cores * 2"), None);
+        assert_eq!(metric("disk_read_mbs > 100"), None);
+
+        let with_clear = AlertCondition {
+            clear: Some(Clear::Expr("mem_pct < 50".to_string())),
+            ..expr("load1 > 4")
+        };
+        assert_eq!(with_clear.offender_metric(), Some(Metric::Memory));
+        assert_eq!(AlertCondition::new(Metric::Network, 10.0).offender_metric(), Some(Metric::Network));
+    }
+
+    fn cpu_at(second: i64, cpu: f32) -> Sample {
+        let mut sample = sample_at(second, Vec::new());
+        sample.cpu_total = cpu;
+        sample
+    }
+
+    fn phase(alerts: &AlertManager, id: &str) -> &'static str {
+        match alerts.states.get(id).map(|s| &s.phase) {
+            None | Some(Phase::Idle) => "idle",
+            Some(Phase::Pending { .. }) => "pending",
+            Some(Phase::Firing(_)) => "firing",
         }
+    }
+
+    // Pasa las muestras (segundo, CPU) y devuelve la fase y los eventos de cada una
+    fn run(alerts: &mut AlertManager, steps: &[(i64, f32)]) -> Vec<(&'static str, &'static str)> {
+        steps
+            .iter()
+            .map(|&(second, cpu)| {
+                let events = alerts.check_alerts(&cpu_at(second, cpu));
+                let event = match events.as_slice() {
+                    [] => "",
+                    [AlertEvent::Firing { .. }] => "firing",
+                    [AlertEvent::Resolved { .. }] => "resolved",
+                    other => panic!("eventos inesperados: {:?}", other),
+                };
+                (phase(alerts, "cpu"), event)
+            })
+            .collect()
+    }
 
-        self.last_network_alert = Some(now);
-        let alert_message = format!(
-            "⚠️ ALERTA: ¡Alto tráfico de red detectado!\nVelocidad de red: {:.2} MB/s (Umbral: {:.2} MB/s)",
-            net, alert.network_threshold
+    #[test]
+    fn for_samples_must_be_consecutive() {
+        let mut alerts = manager(vec![AlertCondition { for_samples: 3, ..AlertCondition::new(Metric::Cpu, 80.0) }]);
+        let steps = [(0, 90.0), (1, 90.0), (2, 50.0), (3, 90.0), (4, 90.0), (5, 90.0), (6, 90.0), (7, 10.0)];
+        assert_eq!(
+            run(&mut alerts, &steps),
+            vec![
+                ("pending", ""),
+                ("pending", ""),
+                ("idle", ""),
+                ("pending", ""),
+                ("pending", ""),
+                ("firing", "firing"),
+                ("firing", ""),
+                ("idle", "resolved"),
+            ]
         );
-        println!("{}", alert_message);
-        self.triggered_alerts.push(alert_message);
-
-        let _ = Notification::new()
-            .appname("Sistema de Defensa")
-            .icon("warning")
-            .summary("⚠️ ¡Alerta de Red!")
-            .body(&format!(
-                "Velocidad de red: {:.2} MB/s (>{:.2} MB/s)",
-                net, alert.network_threshold
-            ))
-            .show();
-    }
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn for_duration_uses_sample_time() {
+        let condition = AlertCondition {
+            for_duration: Duration::from_secs(10),
+            ..AlertCondition::new(Metric::Cpu, 80.0)
+        };
+        let mut alerts = manager(vec![condition]);
+        let steps = [(0, 90.0), (5, 90.0), (9, 90.0), (10, 90.0)];
+        assert_eq!(
+            run(&mut alerts, &steps),
+            vec![("pending", ""), ("pending", ""), ("pending", ""), ("firing", "firing")]
+        );
+
+        // La alerta empieza cuando se dispara, no cuando quedó pendiente
+        let record = alerts.history.back().unwrap();
+        assert_eq!(record.started_at, cpu_at(10, 0.0).timestamp);
+    }
+
+    #[test]
+    fn clear_level_gives_hysteresis() {
+        let by_metric = AlertCondition { clear: Some(Clear::Value(60.0)), ..AlertCondition::new(Metric::Cpu, 80.0) };
+        let by_expr = AlertCondition {
+            id: Some("cpu".to_string()),
+            clear: Some(Clear::Expr("cpu_total < 60".to_string())),
+            ..expr("cpu_total > 80")
+        };
+        for condition in [by_metric, by_expr] {
+            let mut alerts = manager(vec![condition]);
+            // Entre 60 y 80 no se dispara ni se resuelve
+            let steps = [(0, 70.0), (1, 90.0), (2, 70.0), (3, 79.0), (4, 60.0), (5, 59.0), (6, 70.0)];
+            assert_eq!(
+                run(&mut alerts, &steps),
+                vec![
+                    ("idle", ""),
+                    ("firing", "firing"),
+                    ("firing", ""),
+                    ("firing", ""),
+                    ("firing", ""),
+                    ("idle", "resolved"),
+                    ("idle", ""),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn resolved_event_carries_the_clearing_value() {
+        let condition = AlertCondition { clear: Some(Clear::Value(60.0)), ..AlertCondition::new(Metric::Cpu, 80.0) };
+        let mut alerts = manager(vec![condition]);
+        alerts.check_alerts(&cpu_at(0, 95.0));
+        let events = alerts.check_alerts(&cpu_at(7, 42.0));
+        let [AlertEvent::Resolved { record, value, .. }] = events.as_slice() else {
+            panic!("se esperaba la resolución: {:?}", events);
+        };
+        assert_eq!(*value, 42.0);
+        assert_eq!((record.value, record.threshold), (95.0, 80.0));
+        assert_eq!(record.state, AlertState::Resolved);
+        assert_eq!(record.started_at, cpu_at(0, 0.0).timestamp);
+        assert_eq!(record.resolved_at, Some(cpu_at(7, 0.0).timestamp));
+    }
+
+    #[test]
+    fn cooldown_delays_refiring() {
+        let mut alerts = AlertManager::silent(&AlertsConfig {
+            cooldown: Duration::from_secs(30),
+            conditions: vec![AlertCondition::new(Metric::Cpu, 80.0)],
+            watch: Vec::new(),
+        });
+        let steps = [(0, 90.0), (1, 10.0), (2, 90.0), (29, 90.0), (30, 90.0), (31, 10.0)];
+        assert_eq!(
+            run(&mut alerts, &steps),
+            vec![
+                ("firing", "firing"),
+                ("idle", "resolved"),
+                // Sostenida, pero todavía en enfriamiento
+                ("pending", ""),
+                ("pending", ""),
+                ("firing", "firing"),
+                ("idle", "resolved"),
+            ]
+        );
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut alerts = manager(vec![AlertCondition::new(Metric::Cpu, 80.0)]);
+        for i in 0..HISTORY_LEN as i64 + 20 {
+            alerts.check_alerts(&cpu_at(2 * i, 90.0));
+            alerts.check_alerts(&cpu_at(2 * i + 1, 10.0));
+            assert!(alerts.history.len() <= HISTORY_LEN);
+        }
+        assert_eq!(alerts.history.len(), HISTORY_LEN);
+        // Se descartan las más viejas, y las que quedan están resueltas
+        assert_eq!(alerts.history.front().unwrap().started_at, cpu_at(40, 0.0).timestamp);
+        let last = alerts.history.back().unwrap();
+        assert_eq!(last.started_at, cpu_at(2 * (HISTORY_LEN as i64 + 19), 0.0).timestamp);
+        assert!(alerts.history.iter().all(|r| r.state == AlertState::Resolved && r.resolved_at.is_some()));
+    }
+}
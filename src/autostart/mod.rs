@@ -0,0 +1,76 @@
+use std::io;
+use std::path::Path;
+
+#[cfg(windows)]
+mod registry;
+#[cfg(target_os = "linux")]
+mod systemd;
+#[cfg(target_os = "linux")]
+mod xdg;
+
+pub const APP_NAME: &str = "Metricas";
+
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    // El detalle indica dónde quedó registrado (ruta, clave o unidad).
+    Enabled(String),
+    Disabled,
+}
+
+pub trait Autostart {
+    fn name(&self) -> &'static str;
+    fn enable(&self, exe: &Path) -> io::Result<()>;
+    fn disable(&self) -> io::Result<()>;
+    fn status(&self) -> io::Result<Status>;
+}
+
+pub fn available() -> &'static [&'static str] {
+    if cfg!(windows) {
+        &["registry"]
+    } else if cfg!(target_os = "linux") {
+        &["xdg", "systemd"]
+    } else {
+        &[]
+    }
+}
+
+// Sin nombre se usa el primer backend disponible en la plataforma.
+pub fn backend(name: Option<&str>) -> io::Result<Box<dyn Autostart>> {
+    let name = match name.or_else(|| available().first().copied()) {
+        Some(name) => name,
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "inicio automático no soportado en esta plataforma",
+            ))
+        }
+    };
+
+    match name {
+        #[cfg(windows)]
+        "registry" => Ok(Box::new(registry::RunKey)),
+        #[cfg(target_os = "linux")]
+        "xdg" => Ok(Box::new(xdg::DesktopEntry)),
+        #[cfg(target_os = "linux")]
+        "systemd" => Ok(Box::new(systemd::UserUnit)),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "backend de inicio automático desconocido: {} (disponibles: {})",
+                other,
+                available().join(", ")
+            ),
+        )),
+    }
+}
+
+// Directorio de configuración del usuario según XDG (`~/.config` por defecto).
+#[cfg(target_os = "linux")]
+fn config_home() -> io::Result<std::path::PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Ok(dir.into());
+    }
+    std::env::var_os("HOME")
+        .map(|home| Path::new(&home).join(".config"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME no está definido"))
+}
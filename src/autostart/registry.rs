@@ -0,0 +1,46 @@
+use std::io;
+use std::path::Path;
+
+use winreg::enums::*;
+use winreg::RegKey;
+
+use super::{Autostart, Status, APP_NAME};
+
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+// Valor en HKCU\...\Run, lo que hacía `add_to_startup`.
+pub struct RunKey;
+
+impl RunKey {
+    fn open(flags: u32) -> io::Result<RegKey> {
+        RegKey::predef(HKEY_CURRENT_USER).open_subkey_with_flags(RUN_KEY, flags)
+    }
+}
+
+impl Autostart for RunKey {
+    fn name(&self) -> &'static str {
+        "registry"
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        let exe = exe.to_str().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "la ruta del ejecutable no es UTF-8")
+        })?;
+        Self::open(KEY_WRITE)?.set_value(APP_NAME, &format!("\"{}\"", exe))
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        match Self::open(KEY_WRITE)?.delete_value(APP_NAME) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    }
+
+    fn status(&self) -> io::Result<Status> {
+        match Self::open(KEY_READ)?.get_value::<String, _>(APP_NAME) {
+            Ok(command) => Ok(Status::Enabled(format!("HKCU\\{}\\{} = {}", RUN_KEY, APP_NAME, command))),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Status::Disabled),
+            Err(e) => Err(e),
+        }
+    }
+}
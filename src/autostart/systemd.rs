@@ -0,0 +1,137 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{config_home, Autostart, Status};
+
+const UNIT: &str = "metricas.service";
+
+// Unidad `systemctl --user` en modo daemon, útil en sesiones sin escritorio.
+pub struct UserUnit;
+
+impl UserUnit {
+    fn path() -> io::Result<PathBuf> {
+        Ok(config_home()?.join("systemd").join("user").join(UNIT))
+    }
+}
+
+fn systemctl(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("systemctl").arg("--user").args(args).output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() {
+        Ok(stdout)
+    } else {
+        Err(failed(args, &String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+fn failed(args: &[&str], detail: &str) -> io::Error {
+    io::Error::other(format!("systemctl --user {} falló: {}", args.join(" "), detail.trim()))
+}
+
+// `is-enabled` termina con código cero si la unidad está habilitada y con
+// otro código tanto si está deshabilitada como si falló (sin bus de sesión,
+// unidad enmascarada): solo `disabled` en la salida indica lo primero.
+fn is_enabled(success: bool, stdout: &str, stderr: &str) -> io::Result<bool> {
+    let args = ["is-enabled", UNIT];
+    match (success, stdout.trim()) {
+        (true, _) => Ok(true),
+        (false, "disabled") => Ok(false),
+        (false, "") => Err(failed(&args, stderr)),
+        (false, state) => Err(failed(&args, &format!("estado `{}`", state))),
+    }
+}
+
+// Comillas para ExecStart; systemd interpreta `%` como especificador.
+fn quote_exec(arg: &str) -> String {
+    let escaped = arg
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("\"{}\"", escaped)
+}
+
+impl Autostart for UserUnit {
+    fn name(&self) -> &'static str {
+        "systemd"
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let unit = format!(
+            "[Unit]\n\
+             Description=Monitor de sistema (metricas)\n\
+             \n\
+             [Service]\n\
+             ExecStart={} daemon\n\
+             Restart=on-failure\n\
+             \n\
+             [Install]\n\
+             WantedBy=default.target\n",
+            quote_exec(&exe.to_string_lossy())
+        );
+        fs::write(&path, unit)?;
+        systemctl(&["daemon-reload"])?;
+        systemctl(&["enable", UNIT])?;
+        Ok(())
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(());
+        }
+        systemctl(&["disable", UNIT])?;
+        fs::remove_file(&path)?;
+        systemctl(&["daemon-reload"])?;
+        Ok(())
+    }
+
+    fn status(&self) -> io::Result<Status> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Status::Disabled);
+        }
+        let output = Command::new("systemctl").arg("--user").args(["is-enabled", UNIT]).output()?;
+        let enabled = is_enabled(
+            output.status.success(),
+            &String::from_utf8_lossy(&output.stdout),
+            &String::from_utf8_lossy(&output.stderr),
+        )?;
+        Ok(match enabled {
+            true => Status::Enabled(path.display().to_string()),
+            false => Status::Disabled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_exec_start() {
+        assert_eq!(quote_exec("/usr/bin/metricas"), "\"/usr/bin/metricas\"");
+        assert_eq!(quote_exec("/opt/mis apps/metricas"), "\"/opt/mis apps/metricas\"");
+        assert_eq!(quote_exec("/tmp/100%/m"), "\"/tmp/100%%/m\"");
+        assert_eq!(quote_exec(r#"/a"b\c"#), r#""/a\"b\\c""#);
+    }
+
+    #[test]
+    fn is_enabled_states() {
+        assert!(is_enabled(true, "enabled\n", "").unwrap());
+        assert!(!is_enabled(false, "disabled\n", "").unwrap());
+
+        let error = is_enabled(false, "", "Failed to connect to bus: No medium found\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "systemctl --user is-enabled metricas.service falló: Failed to connect to bus: No medium found"
+        );
+        let error = is_enabled(false, "masked\n", "").unwrap_err();
+        assert!(error.to_string().ends_with("estado `masked`"), "{}", error);
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{config_home, Autostart, Status, APP_NAME};
+
+// Entrada `~/.config/autostart/metricas.desktop` que leen los entornos de escritorio.
+pub struct DesktopEntry;
+
+impl DesktopEntry {
+    fn path() -> io::Result<PathBuf> {
+        Ok(config_home()?.join("autostart").join("metricas.desktop"))
+    }
+}
+
+// Comillas según la especificación Desktop Entry para la clave Exec.
+fn quote_exec(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Autostart for DesktopEntry {
+    fn name(&self) -> &'static str {
+        "xdg"
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let entry = format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name={}\n\
+             Comment=Monitor de sistema\n\
+             Exec={}\n\
+             Terminal=false\n\
+             X-GNOME-Autostart-enabled=true\n",
+            APP_NAME,
+            quote_exec(&exe.to_string_lossy())
+        );
+        fs::write(path, entry)
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        match fs::remove_file(Self::path()?) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            other => other,
+        }
+    }
+
+    fn status(&self) -> io::Result<Status> {
+        let path = Self::path()?;
+        match fs::read_to_string(&path) {
+            Ok(entry) if entry.lines().any(|l| l.trim() == "Hidden=true") => Ok(Status::Disabled),
+            Ok(_) => Ok(Status::Enabled(path.display().to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Status::Disabled),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_exec() {
+        assert_eq!(quote_exec("/usr/bin/metricas"), "\"/usr/bin/metricas\"");
+        assert_eq!(quote_exec("/opt/mis apps/metricas"), "\"/opt/mis apps/metricas\"");
+        assert_eq!(quote_exec(r#"/a"b"#), r#""/a\"b""#);
+        assert_eq!(quote_exec("/a`b$c"), r#""/a\`b\$c""#);
+        assert_eq!(quote_exec(r"/a\b"), r#""/a\\b""#);
+    }
+}
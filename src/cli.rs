@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+#[derive(Parser, Debug)]
+#[command(name = "metricas-bueno", version, about = "Monitor de sistema con alertas y almacenamiento en SQLite")]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
+    // Equivalente a `run`; se conserva por compatibilidad.
+    #[arg(long, global = true, hide = true)]
+    pub headless: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Las opciones globales tienen prioridad sobre el archivo de configuración.
+#[derive(Args, Debug, Clone)]
+pub struct GlobalArgs {
+    /// Archivo de configuración TOML (por defecto se busca en el directorio de configuración del usuario)
+    #[arg(long, global = true, value_name = "RUTA")]
+    pub config: Option<PathBuf>,
+
+    /// Ruta de la base de datos SQLite [por defecto: metrics.db]
+    #[arg(long, global = true, value_name = "RUTA")]
+    pub db: Option<PathBuf>,
+
+    /// Ruta del archivo de log [por defecto: metrics.log]
+    #[arg(long, global = true, value_name = "RUTA")]
+    pub log: Option<PathBuf>,
+
+    /// Ventana de muestreo, ej. 5s, 1m [por defecto: 5s]
+    #[arg(long, global = true, value_name = "DURACIÓN", value_parser = parse_duration)]
+    pub interval: Option<Duration>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Recolección, alertas y almacenamiento sin ventana
+    #[command(alias = "daemon")]
+    Run,
+    /// Interfaz gráfica con recolección en segundo plano (por defecto)
+    Gui,
+    /// Muestra las muestras guardadas
+    Query {
+        /// Antigüedad máxima (ej. 30m, 1h, 2d)
+        #[arg(long, value_parser = parse_duration, default_value = "1h")]
+        since: Duration,
+        /// Cantidad máxima de filas
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Exporta las muestras guardadas
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Antigüedad máxima; sin valor exporta todo
+        #[arg(long, value_parser = parse_duration)]
+        since: Option<Duration>,
+        /// Archivo de salida; sin valor escribe en la salida estándar
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Consulta las alertas
+    Alerts {
+        #[command(subcommand)]
+        command: AlertsCommand,
+    },
+    /// Inicio automático con el sistema
+    Autostart {
+        #[command(subcommand)]
+        action: AutostartAction,
+        /// Backend a usar (registry, xdg, systemd)
+        #[arg(long, global = true)]
+        backend: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AlertsCommand {
+    /// Lista las condiciones de alerta configuradas
+    List,
+    /// Muestra las alertas guardadas, con su inicio y resolución
+    History {
+        /// Antigüedad máxima (ej. 1h, 7d)
+        #[arg(long, value_parser = parse_duration, default_value = "1d")]
+        since: Duration,
+        /// Cantidad máxima de filas
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Evalúa reglas sobre las muestras guardadas, sin notificar
+    DryRun {
+        /// Condición configurada a evaluar; sin --id ni --expr se evalúan todas
+        #[arg(long, conflicts_with = "expr")]
+        id: Option<String>,
+        /// Expresión a probar, ej. "avg(cpu_total, 1m) > 85 and mem_pct > 70"
+        #[arg(long)]
+        expr: Option<String>,
+        /// Expresión de resolución para --expr
+        #[arg(long, requires = "expr")]
+        clear: Option<String>,
+        /// Tiempo que debe sostenerse --expr
+        #[arg(long = "for", value_parser = parse_duration, requires = "expr", value_name = "DURACIÓN")]
+        for_duration: Option<Duration>,
+        /// Muestras seguidas que debe cumplirse --expr
+        #[arg(long, requires = "expr")]
+        for_samples: Option<u32>,
+        /// Nombre (admite * y ?) de los procesos para las series proc_* de --expr
+        #[arg(long, requires = "expr", value_name = "NOMBRE")]
+        process: Option<String>,
+        /// Antigüedad máxima de las muestras
+        #[arg(long, value_parser = parse_duration, default_value = "1d")]
+        since: Duration,
+    },
+    /// Lista las series que se pueden usar en las expresiones
+    Series,
+    /// Muestra los inicios, finales y reinicios de los procesos vigilados
+    Processes {
+        /// Antigüedad máxima (ej. 1h, 7d)
+        #[arg(long, value_parser = parse_duration, default_value = "1d")]
+        since: Duration,
+        /// Cantidad máxima de filas
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Muestra las señales enviadas a procesos desde la ventana y su resultado
+    Kills {
+        /// Antigüedad máxima (ej. 1h, 7d)
+        #[arg(long, value_parser = parse_duration, default_value = "1d")]
+        since: Duration,
+        /// Cantidad máxima de filas
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum AutostartAction {
+    Enable,
+    Disable,
+    Status,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// "90s", "5m", "1h30m", "2d", "500ms". Un número solo se toma como segundos.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("duración vacía".to_string());
+    }
+    if let Ok(secs) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        if digits == 0 {
+            return Err(format!("duración inválida: {}", text));
+        }
+        let value: u64 = rest[..digits].parse().map_err(|_| format!("duración inválida: {}", text))?;
+        rest = &rest[digits..];
+
+        let unit_len = rest.find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit = &rest[..unit_len];
+        rest = &rest[unit_len..];
+
+        let part = match unit {
+            "ms" => Some(Duration::from_millis(value)),
+            "s" => Some(Duration::from_secs(value)),
+            "m" => value.checked_mul(60).map(Duration::from_secs),
+            "h" => value.checked_mul(3600).map(Duration::from_secs),
+            "d" => value.checked_mul(86400).map(Duration::from_secs),
+            _ => return Err(format!("unidad desconocida en {} (use ms, s, m, h o d)", text)),
+        };
+        total = part
+            .and_then(|part| total.checked_add(part))
+            .ok_or_else(|| "duración demasiado grande".to_string())?;
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Ok(Duration::from_secs(172_800)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration(" 45 "), Ok(Duration::from_secs(45)));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert_eq!(parse_duration(""), Err("duración vacía".to_string()));
+        assert!(parse_duration("5w").unwrap_err().starts_with("unidad desconocida"));
+        assert_eq!(parse_duration("m5"), Err("duración inválida: m5".to_string()));
+        assert_eq!(parse_duration("999999999999999d"), Err("duración demasiado grande".to_string()));
+        assert_eq!(
+            parse_duration("18446744073709551615s1s"),
+            Err("duración demasiado grande".to_string())
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use std::cmp::Reverse;
+use std::sync::mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use sysinfo::{Disks, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System, ThreadKind, UpdateKind, Users};
+
+use crate::disk::{self, Counters, DiskIo, FillTracker, IoTracker};
+use crate::linux::cgroup::{self, CgroupSample, CgroupTracker, CgroupUsage};
+use crate::linux::psi::{self, Psi};
+use crate::linux::vmstat::{self, SwapTracker};
+use crate::linux::{self, diskstats};
+use crate::network::{self, InterfaceFilter, InterfaceRate, NetworkSampler};
+use crate::sensors::{Sensors, SensorsConfig, TemperatureSample};
+
+#[derive(Clone, Debug)]
+pub struct ProcessSample {
+    pub pid: u32,
+    pub parent: Option<u32>,
+    pub name: String,
+    // Porcentaje del total de la máquina (ya dividido por la cantidad de núcleos)
+    pub cpu: f32,
+    pub memory: u64,
+    pub virtual_memory: u64,
+    // Segundos desde 1970; junto con el pid identifica al proceso aunque el pid se reutilice
+    pub start_time: u64,
+    // Vacíos cuando no se pueden leer (ej. procesos de otro usuario) y en
+    // las muestras cargadas de la base
+    pub exe: String,
+    pub cmdline: String,
+    pub user: Option<String>,
+    // Ej. `Sleeping` o `Run`; vacío en las muestras cargadas de la base
+    pub status: String,
+    // Ruta del cgroup v2; solo Linux
+    pub cgroup: Option<String>,
+    // Bytes por segundo leídos y escritos desde la muestra anterior
+    pub read_rate: f64,
+    pub write_rate: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiskSample {
+    pub mount: String,
+    // Dispositivo de bloques, ej. `sda1`
+    pub device: String,
+    pub kind: String,
+    // Bytes desde la muestra anterior
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub io: DiskIo,
+    pub total_space: u64,
+    pub available_space: u64,
+    // Solo en Unix y en los sistemas de archivos con límite de inodos
+    pub inodes_total: Option<u64>,
+    pub inodes_free: Option<u64>,
+    // Tiempo hasta llenarse al ritmo reciente; `None` si no se está llenando
+    pub full_in: Option<Duration>,
+}
+
+impl DiskSample {
+    pub fn free_percent(&self) -> Option<f64> {
+        (self.total_space > 0).then(|| self.available_space as f64 / self.total_space as f64 * 100.0)
+    }
+
+    pub fn inodes_free_percent(&self) -> Option<f64> {
+        let (total, free) = (self.inodes_total?, self.inodes_free?);
+        (total > 0).then(|| free as f64 / total as f64 * 100.0)
+    }
+}
+
+// Foto del sistema en un instante. La producen `Collector` y la comparten
+// la GUI, el almacenamiento y las alertas.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub timestamp: DateTime<Local>,
+    pub cpu_total: f32,
+    pub cpu_cores: Vec<f32>,
+    // MHz de cada núcleo; 0 si el sistema no lo informa
+    pub cpu_frequencies: Vec<u64>,
+    // Carga promedio de 1, 5 y 15 minutos; siempre 0 en Windows
+    pub load_average: [f64; 3],
+    // Segundos desde el arranque del sistema
+    pub uptime: u64,
+    pub memory_total: u64,
+    pub memory_used: u64,
+    // Memoria que se puede usar sin llevar nada a la swap, incluida la caché
+    // que se puede liberar; 0 en las muestras anteriores a que se midiera
+    pub memory_available: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+    // Bytes por segundo llevados desde y hacia la swap; solo Linux
+    pub swap_in_rate: Option<f64>,
+    pub swap_out_rate: Option<f64>,
+    pub interfaces: Vec<InterfaceRate>,
+    pub processes: Vec<ProcessSample>,
+    pub disks: Vec<DiskSample>,
+    pub temperatures: Vec<TemperatureSample>,
+    // Solo en Linux con PSI; vacío en los demás sistemas
+    pub pressure: Psi,
+    // Solo si el monitor corre en un cgroup v2
+    pub cgroup: Option<CgroupSample>,
+    // Procesos agrupados por cgroup; solo Linux
+    pub cgroups: Vec<CgroupUsage>,
+}
+
+impl Sample {
+    // Dentro de un cgroup con límite de memoria, respecto de ese límite. Si
+    // no, con la memoria disponible cuando se conoce, porque en algunos
+    // sistemas la usada incluye la caché de disco.
+    pub fn memory_percent(&self) -> f32 {
+        if let Some(percent) = self.cgroup.as_ref().and_then(|c| c.memory_percent()) {
+            return percent as f32;
+        }
+        if self.memory_total == 0 {
+            return 0.0;
+        }
+        let used = match self.memory_available {
+            0 => self.memory_used,
+            available => self.memory_total.saturating_sub(available),
+        };
+        (used as f64 / self.memory_total as f64 * 100.0) as f32
+    }
+
+    // Uso de CPU respecto de los núcleos que el cgroup puede usar, o
+    // `cpu_total` si no tiene límite
+    pub fn cpu_percent(&self) -> f32 {
+        match self.cgroup.as_ref().and_then(|c| c.cpu_percent()) {
+            Some(percent) => percent as f32,
+            None => self.cpu_total,
+        }
+    }
+
+    pub fn swap_percent(&self) -> Option<f32> {
+        (self.swap_total > 0).then(|| (self.swap_used as f64 / self.swap_total as f64 * 100.0) as f32)
+    }
+
+    // (bajada, subida) en Mbps sumando todas las interfaces medidas
+    pub fn network_totals(&self) -> (f64, f64) {
+        network::totals(&self.interfaces)
+    }
+
+    pub fn top_cpu(&self, n: usize) -> Vec<&ProcessSample> {
+        let mut processes: Vec<_> = self.processes.iter().collect();
+        processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu));
+        processes.truncate(n);
+        processes
+    }
+
+    pub fn top_memory(&self, n: usize) -> Vec<&ProcessSample> {
+        let mut processes: Vec<_> = self.processes.iter().collect();
+        processes.sort_by_key(|p| Reverse(p.memory));
+        processes.truncate(n);
+        processes
+    }
+}
+
+pub struct Collector {
+    sys: System,
+    users: Users,
+    disks: Disks,
+    io: IoTracker,
+    fill: FillTracker,
+    network: NetworkSampler,
+    sensors: Sensors,
+    swap: SwapTracker,
+    cgroup: CgroupTracker,
+    // Cuándo se refrescaron los procesos, para convertir su E/S en tasas
+    processes_refreshed: Instant,
+}
+
+// El ejecutable, la línea de comandos y el usuario no cambian durante la
+// vida del proceso, así que se leen una sola vez.
+fn process_refresh() -> ProcessRefreshKind {
+    ProcessRefreshKind::nothing()
+        .with_memory()
+        .with_cpu()
+        .with_disk_usage()
+        .with_exe(UpdateKind::OnlyIfNotSet)
+        .with_cmd(UpdateKind::OnlyIfNotSet)
+        .with_user(UpdateKind::OnlyIfNotSet)
+}
+
+impl Collector {
+    pub fn new(filter: InterfaceFilter, sensors: SensorsConfig) -> Self {
+        let mut sys = System::new_with_specifics(RefreshKind::everything());
+        // Primera lectura para que el uso de CPU de la siguiente tenga referencia
+        sys.refresh_cpu_all();
+        sys.refresh_processes_specifics(ProcessesToUpdate::All, false, process_refresh());
+
+        Collector {
+            sys,
+            users: Users::new_with_refreshed_list(),
+            disks: Disks::new_with_refreshed_list(),
+            io: IoTracker::default(),
+            fill: FillTracker::default(),
+            network: NetworkSampler::new(filter),
+            sensors: Sensors::new(sensors),
+            swap: SwapTracker::default(),
+            cgroup: CgroupTracker::new(linux::ROOT),
+            processes_refreshed: Instant::now(),
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: InterfaceFilter) {
+        self.network.set_filter(filter);
+    }
+
+    pub fn set_sensors(&mut self, config: SensorsConfig) {
+        self.sensors.reconfigure(config);
+    }
+
+    // Los porcentajes de CPU y las tasas de red cubren el tiempo desde la
+    // llamada anterior.
+    pub fn collect(&mut self) -> Sample {
+        self.sys.refresh_cpu_all();
+        self.sys.refresh_processes_specifics(ProcessesToUpdate::All, true, process_refresh());
+        self.sys.refresh_memory();
+        let refreshed = Instant::now();
+        let seconds = (refreshed - std::mem::replace(&mut self.processes_refreshed, refreshed)).as_secs_f64();
+        let rate = |bytes: u64| if seconds > 0.0 { bytes as f64 / seconds } else { 0.0 };
+
+        let cpu_cores: Vec<f32> = self.sys.cpus().iter().map(|c| c.cpu_usage()).collect();
+        let cpu_frequencies = self.sys.cpus().iter().map(|c| c.frequency()).collect();
+        let load = System::load_average();
+        let num_cores = cpu_cores.len().max(1) as f32;
+        let cpu_total = cpu_cores.iter().sum::<f32>() / num_cores;
+
+        // Un usuario creado después del arranque obliga a releer la lista
+        let unknown_user = self
+            .sys
+            .processes()
+            .values()
+            .filter_map(|p| p.user_id())
+            .any(|uid| self.users.get_user_by_id(uid).is_none());
+        if unknown_user {
+            self.users.refresh();
+        }
+
+        // En Linux los hilos aparecen como procesos con la memoria del
+        // proceso entero; contarlos duplicaría las sumas por proceso
+        let processes: Vec<ProcessSample> = self
+            .sys
+            .processes()
+            .values()
+            .filter(|p| p.thread_kind() != Some(ThreadKind::Userland))
+            .map(|p| ProcessSample {
+                pid: p.pid().as_u32(),
+                parent: p.parent().map(|parent| parent.as_u32()),
+                name: p.name().to_string_lossy().into_owned(),
+                cpu: p.cpu_usage() / num_cores,
+                memory: p.memory(),
+                virtual_memory: p.virtual_memory(),
+                start_time: p.start_time(),
+                exe: p.exe().map(|e| e.to_string_lossy().into_owned()).unwrap_or_default(),
+                cmdline: p
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                user: p
+                    .user_id()
+                    .and_then(|uid| self.users.get_user_by_id(uid))
+                    .map(|u| u.name().to_string()),
+                status: p.status().to_string(),
+                cgroup: cgroup::of_process(Path::new(linux::ROOT), p.pid().as_u32()),
+                read_rate: rate(p.disk_usage().read_bytes),
+                write_rate: rate(p.disk_usage().written_bytes),
+            })
+            .collect();
+
+        // La lista de discos se conserva entre muestras para que los
+        // contadores de lectura y escritura se puedan convertir en tasas
+        let timestamp = Local::now();
+        let now = Instant::now();
+        self.disks.refresh(true);
+        let diskstats = diskstats::read(Path::new(linux::ROOT));
+        let mut io_keys = Vec::new();
+        let mut disks: Vec<DiskSample> = self
+            .disks
+            .iter()
+            .map(|disk| {
+                let mount = disk.mount_point().to_string_lossy().into_owned();
+                let device = disk::device_name(disk.name());
+                let usage = disk.usage();
+                let counters = Counters {
+                    read_bytes: usage.total_read_bytes,
+                    written_bytes: usage.total_written_bytes,
+                    stats: diskstats.get(&device).copied(),
+                };
+                // Los bind mounts comparten el dispositivo; los sistemas de
+                // archivos sin dispositivo (tmpfs, overlay) se separan por montaje
+                let io_key = match Path::new(disk.name()).starts_with("/dev") {
+                    true => device.clone(),
+                    false => mount.clone(),
+                };
+                let (read_bytes, written_bytes, io) = self.io.update(&io_key, now, counters);
+                io_keys.push(io_key);
+                let inodes = disk::inodes(&mount);
+                DiskSample {
+                    device,
+                    kind: format!("{:?}", disk.kind()),
+                    read_bytes,
+                    written_bytes,
+                    io,
+                    total_space: disk.total_space(),
+                    available_space: disk.available_space(),
+                    inodes_total: inodes.map(|(total, _)| total),
+                    inodes_free: inodes.map(|(_, free)| free),
+                    full_in: None,
+                    mount,
+                }
+            })
+            .collect();
+        self.io.retain(&io_keys.iter().map(String::as_str).collect::<Vec<_>>());
+        self.fill.update(timestamp, &mut disks);
+
+        let (swap_in_rate, swap_out_rate) = self.swap_rates(now);
+        let own_cgroup = self.cgroup.update(now, self.sys.total_memory(), cpu_cores.len());
+
+        Sample {
+            timestamp,
+            cpu_total,
+            cpu_cores,
+            cpu_frequencies,
+            load_average: [load.one, load.five, load.fifteen],
+            uptime: System::uptime(),
+            memory_total: self.sys.total_memory(),
+            memory_used: self.sys.used_memory(),
+            memory_available: self.sys.available_memory(),
+            swap_total: self.sys.total_swap(),
+            swap_used: self.sys.used_swap(),
+            swap_in_rate,
+            swap_out_rate,
+            interfaces: self.network.sample(),
+            disks,
+            temperatures: self.sensors.read(),
+            pressure: psi::read(Path::new(linux::ROOT)),
+            cgroup: own_cgroup,
+            cgroups: cgroup::group(&processes),
+            processes,
+        }
+    }
+
+    fn swap_rates(&mut self, now: Instant) -> (Option<f64>, Option<f64>) {
+        let Some(counters) = vmstat::read(Path::new(linux::ROOT)) else {
+            return (None, None);
+        };
+        self.swap.update(now, counters)
+    }
+}
+
+// Muestras que puede tener pendientes un suscriptor con pérdida
+const SUBSCRIBER_QUEUE: usize = 16;
+
+enum Subscriber {
+    // Recibe todas las muestras: el almacenamiento y las alertas
+    Lossless(Sender<Arc<Sample>>),
+    // Si se atrasa (la ventana minimizada) las muestras nuevas se descartan
+    // en lugar de acumularse en memoria; `dropped` cuenta las de la racha actual
+    Lossy {
+        name: &'static str,
+        tx: SyncSender<Arc<Sample>>,
+        dropped: u64,
+    },
+}
+
+impl Subscriber {
+    // `false` si el receptor ya no existe
+    fn send(&mut self, sample: &Arc<Sample>) -> bool {
+        match self {
+            Subscriber::Lossless(tx) => tx.send(Arc::clone(sample)).is_ok(),
+            Subscriber::Lossy { name, tx, dropped } => match tx.try_send(Arc::clone(sample)) {
+                Ok(()) => {
+                    if *dropped > 0 {
+                        eprintln!("{}: se descartaron {} muestras mientras estaba atrasado", name, dropped);
+                        *dropped = 0;
+                    }
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    if *dropped == 0 {
+                        eprintln!("{}: no procesa las muestras a tiempo; se descartan hasta que se ponga al día", name);
+                    }
+                    *dropped += 1;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            },
+        }
+    }
+}
+
+// Reparte cada muestra a todos los suscriptores. Los receptores que se
+// descartan se quitan en la siguiente publicación.
+#[derive(Default)]
+pub struct SampleBus {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl SampleBus {
+    pub fn subscribe(&self) -> Receiver<Arc<Sample>> {
+        let (tx, rx) = channel();
+        self.subscribers.lock().unwrap().push(Subscriber::Lossless(tx));
+        rx
+    }
+
+    // Para quien solo muestra los datos; `name` identifica los descartes en el log
+    pub fn subscribe_lossy(&self, name: &'static str) -> Receiver<Arc<Sample>> {
+        let (tx, rx) = sync_channel(SUBSCRIBER_QUEUE);
+        self.subscribers.lock().unwrap().push(Subscriber::Lossy { name, tx, dropped: 0 });
+        rx
+    }
+
+    pub fn publish(&self, sample: Sample) {
+        let sample = Arc::new(sample);
+        self.subscribers.lock().unwrap().retain_mut(|s| s.send(&sample));
+    }
+}
+
+// Muestra vacía a las 12:00 del 1/1/2024, para armar las de las pruebas.
+#[cfg(test)]
+pub fn test_sample() -> Sample {
+    use chrono::TimeZone;
+
+    Sample {
+        timestamp: Local.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap(),
+        cpu_total: 0.0,
+        cpu_cores: Vec::new(),
+        cpu_frequencies: Vec::new(),
+        load_average: [0.0; 3],
+        uptime: 0,
+        memory_total: 0,
+        memory_used: 0,
+        memory_available: 0,
+        swap_total: 0,
+        swap_used: 0,
+        swap_in_rate: None,
+        swap_out_rate: None,
+        interfaces: Vec::new(),
+        processes: Vec::new(),
+        disks: Vec::new(),
+        temperatures: Vec::new(),
+        pressure: Psi::default(),
+        cgroup: None,
+        cgroups: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+pub fn test_process(pid: u32, name: &str, cpu: f32, memory: u64) -> ProcessSample {
+    ProcessSample {
+        pid,
+        parent: None,
+        name: name.to_string(),
+        cpu,
+        memory,
+        virtual_memory: 0,
+        start_time: 0,
+        exe: String::new(),
+        cmdline: String::new(),
+        user: None,
+        status: String::new(),
+        cgroup: None,
+        read_rate: 0.0,
+        write_rate: 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dropped(bus: &SampleBus) -> Vec<u64> {
+        let subscribers = bus.subscribers.lock().unwrap();
+        subscribers
+            .iter()
+            .filter_map(|s| match s {
+                Subscriber::Lossy { dropped, .. } => Some(*dropped),
+                Subscriber::Lossless(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bus_drops_samples_for_slow_lossy_subscribers() {
+        let bus = SampleBus::default();
+        let slow = bus.subscribe_lossy("lenta");
+        let fast = bus.subscribe_lossy("rápida");
+        for _ in 0..SUBSCRIBER_QUEUE + 5 {
+            bus.publish(test_sample());
+            assert!(fast.try_recv().is_ok());
+        }
+        assert_eq!(dropped(&bus), vec![5, 0]);
+        assert_eq!(slow.try_iter().count(), SUBSCRIBER_QUEUE);
+
+        // Con espacio libre vuelve a recibir y la cuenta empieza de nuevo
+        bus.publish(test_sample());
+        assert_eq!(slow.try_iter().count(), 1);
+        assert_eq!(dropped(&bus), vec![0, 0]);
+    }
+
+    #[test]
+    fn lossless_subscribers_get_every_sample() {
+        let bus = SampleBus::default();
+        let storage = bus.subscribe();
+        let _window = bus.subscribe_lossy("ventana");
+        for _ in 0..SUBSCRIBER_QUEUE * 4 {
+            bus.publish(test_sample());
+        }
+        assert_eq!(storage.try_iter().count(), SUBSCRIBER_QUEUE * 4);
+        assert_eq!(dropped(&bus), vec![SUBSCRIBER_QUEUE as u64 * 3]);
+    }
+
+    #[test]
+    fn bus_forgets_dropped_subscribers() {
+        let bus = SampleBus::default();
+        let kept = bus.subscribe();
+        drop(bus.subscribe());
+        drop(bus.subscribe_lossy("cerrada"));
+        bus.publish(test_sample());
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+        assert!(kept.try_recv().is_ok());
+    }
+}
@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::alert::{default_conditions, AlertCondition, Clear, Metric};
+use crate::cli::parse_duration;
+use crate::network::InterfaceFilter;
+use crate::notify::{SinkConfig, DESKTOP};
+use crate::sensors::SensorsConfig;
+use crate::watchdog::WatchConfig;
+
+mod reload;
+
+pub use reload::{diff, spawn_watcher, SharedConfig};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub general: GeneralConfig,
+    pub alerts: AlertsConfig,
+    pub network: InterfaceFilter,
+    pub sensors: SensorsConfig,
+    pub notifications: NotificationsConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeneralConfig {
+    pub db_path: PathBuf,
+    pub log_path: PathBuf,
+    // Ventana durante la que se mide la red antes de guardar cada muestra
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub interval: Duration,
+    // Pausa entre el final de una muestra y el comienzo de la siguiente
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub pause: Duration,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        GeneralConfig {
+            db_path: PathBuf::from("metrics.db"),
+            log_path: PathBuf::from("metrics.log"),
+            interval: Duration::from_secs(5),
+            pause: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AlertsConfig {
+    // Tiempo mínimo entre dos notificaciones del mismo tipo
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub cooldown: Duration,
+    pub conditions: Vec<AlertCondition>,
+    // Procesos cuyo final, inicio o reinicio se avisa
+    pub watch: Vec<WatchConfig>,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        AlertsConfig {
+            cooldown: Duration::from_secs(10),
+            conditions: default_conditions(),
+            watch: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotificationsConfig {
+    pub enabled: bool,
+    pub appname: String,
+    // Destinos de las condiciones que no indican `notify`
+    pub default: Vec<String>,
+    pub sinks: BTreeMap<String, SinkConfig>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            enabled: true,
+            appname: "Sistema de Defensa".to_string(),
+            default: vec![DESKTOP.to_string()],
+            sinks: BTreeMap::new(),
+        }
+    }
+}
+
+// Valores de la línea de comandos que tienen prioridad sobre el archivo,
+// también después de cada recarga.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub db_path: Option<PathBuf>,
+    pub log_path: Option<PathBuf>,
+    pub interval: Option<Duration>,
+}
+
+impl Overrides {
+    pub fn apply(&self, config: &mut Config) {
+        if let Some(db_path) = &self.db_path {
+            config.general.db_path = db_path.clone();
+        }
+        if let Some(log_path) = &self.log_path {
+            config.general.log_path = log_path.clone();
+        }
+        if let Some(interval) = self.interval {
+            config.general.interval = interval;
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    // Sin ruta cuando no hay archivo y el valor inválido vino de la línea de comandos
+    Invalid { path: Option<PathBuf>, key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            // El error de toml ya incluye línea, columna y la clave afectada
+            ConfigError::Parse(path, e) => write!(f, "{}: {}", path.display(), e),
+            ConfigError::Invalid { path: Some(path), key, message } => {
+                write!(f, "{}: clave `{}`: {}", path.display(), key, message)
+            }
+            ConfigError::Invalid { path: None, key, message } => write!(f, "clave `{}`: {}", key, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Acepta "5s", "500ms", "1m" o un entero en segundos.
+pub(crate) fn deserialize_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Secs(u64),
+        Text(String),
+    }
+
+    match Raw::deserialize(deserializer)? {
+        Raw::Secs(secs) => Ok(Duration::from_secs(secs)),
+        Raw::Text(text) => parse_duration(&text).map_err(serde::de::Error::custom),
+    }
+}
+
+// `$XDG_CONFIG_HOME/metricas/config.toml` (o `~/.config/...`) en Unix y
+// `%APPDATA%\metricas\config.toml` en Windows.
+pub fn default_path() -> Option<PathBuf> {
+    let base = if cfg!(windows) {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+    };
+    base.map(|dir| dir.join("metricas").join("config.toml"))
+}
+
+impl Config {
+    // Con `explicit` el archivo debe existir; sin él se busca en la ruta por
+    // defecto y, si no hay archivo, se usan los valores por defecto. También
+    // devuelve la ruta a vigilar para recargar, exista o no todavía.
+    pub fn load(explicit: Option<&Path>, overrides: &Overrides) -> Result<(Config, Option<PathBuf>), ConfigError> {
+        let path = match explicit {
+            Some(path) => path.to_path_buf(),
+            None => match default_path() {
+                Some(path) if path.is_file() => path,
+                other => {
+                    let mut config = Config::default();
+                    overrides.apply(&mut config);
+                    config.validate().map_err(|(key, message)| ConfigError::Invalid { path: None, key, message })?;
+                    return Ok((config, other));
+                }
+            },
+        };
+
+        let config = Config::from_file(&path, overrides)?;
+        Ok((config, Some(path)))
+    }
+
+    // Los valores de la línea de comandos se aplican antes de validar, así
+    // tampoco pueden saltear las comprobaciones.
+    pub fn from_file(path: &Path, overrides: &Overrides) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+        let mut config: Config = toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))?;
+        overrides.apply(&mut config);
+        config.validate().map_err(|(key, message)| ConfigError::Invalid {
+            path: Some(path.to_path_buf()),
+            key,
+            message,
+        })?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), (String, String)> {
+        let general = &self.general;
+        if general.db_path.as_os_str().is_empty() {
+            return Err(("general.db_path".into(), "no puede estar vacía".into()));
+        }
+        if general.log_path.as_os_str().is_empty() {
+            return Err(("general.log_path".into(), "no puede estar vacía".into()));
+        }
+        if general.interval < Duration::from_millis(100) {
+            return Err(("general.interval".into(), "debe ser de al menos 100ms".into()));
+        }
+
+        let mut ids = Vec::new();
+        for (i, condition) in self.alerts.conditions.iter().enumerate() {
+            let key = |name: &str| format!("alerts.conditions[{}].{}", i, name);
+
+            let id = condition.id();
+            if ids.contains(&id) {
+                return Err((
+                    key("id"),
+                    format!("`{}` ya se usa en otra condición; asigne un `id` distinto", id),
+                ));
+            }
+            ids.push(id);
+
+            match (condition.metric, &condition.expr) {
+                (Some(_), Some(_)) => {
+                    return Err((key("expr"), "use `metric` con `threshold` o `expr`, no ambos".into()));
+                }
+                (None, None) => {
+                    return Err((key("metric"), "falta `metric` (con `threshold`) o `expr`".into()));
+                }
+                (Some(metric), None) => {
+                    if condition.process.is_some() {
+                        return Err((key("process"), "solo se usa con `expr` y las series `proc_*`".into()));
+                    }
+                    Self::validate_threshold(condition, metric).map_err(|(k, m)| (key(k), m))?
+                }
+                (None, Some(expr)) => {
+                    let scoped = condition.process.is_some();
+                    if let Some(process) = &condition.process {
+                        if process.is_empty() {
+                            return Err((key("process"), "indique al menos name, pid, exe, cmdline o user".into()));
+                        }
+                        process
+                            .validate()
+                            .map_err(|(field, message)| (key(&format!("process.{}", field)), message))?;
+                    }
+                    if condition.id.is_none() {
+                        return Err((key("id"), "las condiciones con `expr` necesitan un `id`".into()));
+                    }
+                    if condition.threshold.is_some() {
+                        return Err((key("threshold"), "solo se usa con `metric`; ponga el umbral en `expr`".into()));
+                    }
+                    crate::rule::check(expr, scoped).map_err(|e| (key("expr"), format!("{}\n{}", e, e.pointer(expr))))?;
+                    match &condition.clear {
+                        Some(Clear::Expr(clear)) => crate::rule::check(clear, scoped)
+                            .map_err(|e| (key("clear"), format!("{}\n{}", e, e.pointer(clear))))?,
+                        Some(Clear::Value(_)) => {
+                            return Err((key("clear"), "con `expr` debe ser una expresión entre comillas".into()));
+                        }
+                        None => {}
+                    }
+                }
+            }
+            if condition.for_samples == 0 {
+                return Err((key("for_samples"), "debe ser al menos 1".into()));
+            }
+            for (j, name) in condition.notify.iter().flatten().enumerate() {
+                self.check_sink(name).map_err(|message| (key(&format!("notify[{}]", j)), message))?;
+            }
+        }
+
+        for (i, watch) in self.alerts.watch.iter().enumerate() {
+            let key = |name: &str| format!("alerts.watch[{}].{}", i, name);
+
+            if ids.contains(&watch.id) {
+                return Err((
+                    key("id"),
+                    format!("`{}` ya se usa en otra condición o vigilancia", watch.id),
+                ));
+            }
+            ids.push(watch.id.clone());
+
+            if watch.id.trim().is_empty() {
+                return Err((key("id"), "no puede estar vacío".into()));
+            }
+            if watch.process.is_empty() {
+                return Err((key("process"), "indique al menos name, pid, exe, cmdline o user".into()));
+            }
+            watch
+                .process
+                .validate()
+                .map_err(|(field, message)| (key(&format!("process.{}", field)), message))?;
+            if watch.restart.as_ref().is_some_and(|c| c.first().is_none_or(|c| c.trim().is_empty())) {
+                return Err((key("restart"), "no puede estar vacío".into()));
+            }
+            for (j, name) in watch.notify.iter().flatten().enumerate() {
+                self.check_sink(name).map_err(|message| (key(&format!("notify[{}]", j)), message))?;
+            }
+        }
+
+        let notifications = &self.notifications;
+        if notifications.sinks.contains_key(DESKTOP) {
+            return Err((
+                format!("notifications.sinks.{}", DESKTOP),
+                format!("`{}` es el nombre reservado del aviso de escritorio", DESKTOP),
+            ));
+        }
+        for (name, sink) in &notifications.sinks {
+            sink.validate()
+                .map_err(|(field, message)| (format!("notifications.sinks.{}.{}", name, field), message))?;
+        }
+        for (i, name) in notifications.default.iter().enumerate() {
+            self.check_sink(name).map_err(|message| (format!("notifications.default[{}]", i), message))?;
+        }
+
+        if self.sensors.root.as_os_str().is_empty() {
+            return Err(("sensors.root".into(), "no puede estar vacía".into()));
+        }
+
+        for (list, patterns) in [("include", &self.network.include), ("exclude", &self.network.exclude)] {
+            if let Some(i) = patterns.iter().position(|p| p.trim().is_empty()) {
+                return Err((format!("network.{}[{}]", list, i), "el patrón está vacío".into()));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_threshold(condition: &AlertCondition, metric: Metric) -> Result<(), (&'static str, String)> {
+        let Some(threshold) = condition.threshold else {
+            return Err(("threshold", format!("falta el umbral de `{}`", metric.as_str())));
+        };
+        let range = match metric {
+            Metric::Cpu | Metric::Memory | Metric::Disk | Metric::Swap | Metric::Temperature => {
+                0.0..=100.0
+            }
+            Metric::Network => 0.0..=f32::MAX,
+        };
+        if !range.contains(&threshold) {
+            return Err(("threshold", format!("fuera de rango (valor: {})", threshold)));
+        }
+        match &condition.clear {
+            Some(Clear::Value(clear)) => {
+                if !range.contains(clear) {
+                    return Err(("clear", format!("fuera de rango (valor: {})", clear)));
+                }
+                if *clear > threshold {
+                    return Err((
+                        "clear",
+                        format!("debe ser menor o igual que threshold ({} > {})", clear, threshold),
+                    ));
+                }
+            }
+            Some(Clear::Expr(_)) => {
+                return Err(("clear", "con `metric` debe ser un número".into()));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    fn check_sink(&self, name: &str) -> Result<(), String> {
+        if name == DESKTOP || self.notifications.sinks.contains_key(name) {
+            return Ok(());
+        }
+        let mut known: Vec<&str> = vec![DESKTOP];
+        known.extend(self.notifications.sinks.keys().map(String::as_str));
+        Err(format!("destino desconocido `{}` (definidos: {})", name, known.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::Fixture;
+
+    // La clave que informa la validación de un archivo
+    fn invalid(text: &str) -> String {
+        let config: Config = toml::from_str(text).unwrap();
+        config.validate().unwrap_err().0
+    }
+
+    #[test]
+    fn example_is_valid() {
+        let config: Config = toml::from_str(include_str!("../../config.example.toml")).unwrap();
+        config.validate().unwrap();
+        Config::default().validate().unwrap();
+    }
+
+    #[test]
+    fn reports_the_bad_key() {
+        let cases = [
+            ("[general]\ninterval = \"50ms\"", "general.interval"),
+            ("[general]\ndb_path = \"\"", "general.db_path"),
+            (
+                "[[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 80.0\n\
+                 [[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 90.0",
+                "alerts.conditions[1].id",
+            ),
+            ("[[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 180.0", "alerts.conditions[0].threshold"),
+            (
+                "[[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 80.0\nclear = 90.0",
+                "alerts.conditions[0].clear",
+            ),
+            ("[[alerts.conditions]]\nthreshold = 80.0", "alerts.conditions[0].metric"),
+            ("[[alerts.conditions]]\nid = \"x\"\nexpr = \"cpu_total >\"", "alerts.conditions[0].expr"),
+            ("[[alerts.conditions]]\nexpr = \"cpu_total > 5\"", "alerts.conditions[0].id"),
+            (
+                "[[alerts.conditions]]\nid = \"x\"\nexpr = \"proc_count < 1\"\nprocess = { cmdline = \"(\" }",
+                "alerts.conditions[0].process.cmdline",
+            ),
+            (
+                "[[alerts.conditions]]\nid = \"x\"\nexpr = \"proc_count < 1\"\nprocess = {}",
+                "alerts.conditions[0].process",
+            ),
+            (
+                "[[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 80.0\nnotify = [\"desktop\", \"equipo\"]",
+                "alerts.conditions[0].notify[1]",
+            ),
+            ("[[alerts.watch]]\nid = \"cpu\"\nprocess = { name = \"x\" }", "alerts.watch[0].id"),
+            ("[[alerts.watch]]\nid = \"w\"\nprocess = {}", "alerts.watch[0].process"),
+            ("[[alerts.watch]]\nid = \"w\"\nprocess = { cmdline = \"[\" }", "alerts.watch[0].process.cmdline"),
+            ("[[alerts.watch]]\nid = \"w\"\nprocess = { name = \"x\" }\nrestart = []", "alerts.watch[0].restart"),
+            (
+                "[notifications.sinks.equipo]\ntype = \"webhook\"\nurl = \"ftp://example.com\"",
+                "notifications.sinks.equipo.url",
+            ),
+            ("[notifications.sinks.desktop]\ntype = \"exec\"\ncommand = [\"x\"]", "notifications.sinks.desktop"),
+            ("[notifications]\ndefault = [\"nadie\"]", "notifications.default[0]"),
+            ("[network]\nexclude = [\"lo\", \" \"]", "network.exclude[1]"),
+        ];
+        for (text, key) in cases {
+            assert_eq!(invalid(text), key, "{}", text);
+        }
+    }
+
+    #[test]
+    fn overrides_are_validated() {
+        let fixture = Fixture::new("config");
+        fixture.write("config.toml", "[general]\ninterval = \"5s\"\n");
+        let path = fixture.root.join("config.toml");
+        let overrides = Overrides { interval: Some(Duration::ZERO), ..Default::default() };
+
+        let Err(ConfigError::Invalid { path: Some(reported), key, .. }) = Config::from_file(&path, &overrides) else {
+            panic!("--interval 0 no debería pasar la validación");
+        };
+        assert_eq!((reported, key.as_str()), (path.clone(), "general.interval"));
+
+        let overrides = Overrides { interval: Some(Duration::from_secs(1)), ..Default::default() };
+        assert_eq!(Config::from_file(&path, &overrides).unwrap().general.interval, Duration::from_secs(1));
+        let (config, watched) = Config::load(Some(&path), &overrides).unwrap();
+        assert_eq!((config.general.interval, watched), (Duration::from_secs(1), Some(path)));
+    }
+}
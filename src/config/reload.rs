@@ -0,0 +1,209 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{spawn, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use super::{Config, Overrides};
+use crate::watchdog::WatchConfig;
+
+// Configuración vigente compartida entre hilos. Cada reemplazo incrementa la
+// generación para que los consumidores detecten el cambio sin comparar todo.
+pub struct SharedConfig {
+    current: RwLock<Arc<Config>>,
+    generation: AtomicU64,
+}
+
+impl SharedConfig {
+    pub fn new(config: Config) -> Self {
+        SharedConfig {
+            current: RwLock::new(Arc::new(config)),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self) -> Arc<Config> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn replace(&self, config: Config) {
+        *self.current.write().unwrap() = Arc::new(config);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Claves que cambiaron entre dos configuraciones, con el valor anterior y el nuevo.
+pub fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let mut changes = Vec::new();
+    let mut check = |key: &str, old: String, new: String| {
+        if old != new {
+            changes.push(format!("{}: {} -> {}", key, old, new));
+        }
+    };
+
+    check("general.db_path", format!("{:?}", old.general.db_path), format!("{:?}", new.general.db_path));
+    check("general.log_path", format!("{:?}", old.general.log_path), format!("{:?}", new.general.log_path));
+    check("general.interval", format!("{:?}", old.general.interval), format!("{:?}", new.general.interval));
+    check("general.pause", format!("{:?}", old.general.pause), format!("{:?}", new.general.pause));
+    check("alerts.cooldown", format!("{:?}", old.alerts.cooldown), format!("{:?}", new.alerts.cooldown));
+
+    let conditions = old.alerts.conditions.len().max(new.alerts.conditions.len());
+    for i in 0..conditions {
+        let key = format!("alerts.conditions[{}]", i);
+        match (old.alerts.conditions.get(i), new.alerts.conditions.get(i)) {
+            (Some(o), Some(n)) if o.id() == n.id() && o.metric == n.metric => {
+                check(&format!("{}.threshold", key), format!("{:?}", o.threshold), format!("{:?}", n.threshold));
+                check(&format!("{}.expr", key), format!("{:?}", o.expr), format!("{:?}", n.expr));
+                check(&format!("{}.clear", key), format!("{:?}", o.clear), format!("{:?}", n.clear));
+                check(
+                    &format!("{}.process", key),
+                    o.process.as_ref().map(|p| p.describe()).unwrap_or_else(|| "-".into()),
+                    n.process.as_ref().map(|p| p.describe()).unwrap_or_else(|| "-".into()),
+                );
+                check(&format!("{}.for", key), format!("{:?}", o.for_duration), format!("{:?}", n.for_duration));
+                check(&format!("{}.for_samples", key), o.for_samples.to_string(), n.for_samples.to_string());
+                check(&format!("{}.notify", key), format!("{:?}", o.notify), format!("{:?}", n.notify));
+            }
+            (o, n) => check(
+                &key,
+                o.map(|c| format!("{} [{}]", c.id(), c.describe())).unwrap_or_else(|| "-".into()),
+                n.map(|c| format!("{} [{}]", c.id(), c.describe())).unwrap_or_else(|| "-".into()),
+            ),
+        }
+    }
+
+    let watches = old.alerts.watch.len().max(new.alerts.watch.len());
+    for i in 0..watches {
+        let describe = |w: Option<&WatchConfig>| {
+            w.map(|w| format!("{} [{}]", w.id, w.describe())).unwrap_or_else(|| "-".into())
+        };
+        let (o, n) = (old.alerts.watch.get(i), new.alerts.watch.get(i));
+        check(&format!("alerts.watch[{}]", i), describe(o), describe(n));
+        if let (Some(o), Some(n)) = (o, n) {
+            check(&format!("alerts.watch[{}].notify", i), format!("{:?}", o.notify), format!("{:?}", n.notify));
+        }
+    }
+
+    check("network.include", format!("{:?}", old.network.include), format!("{:?}", new.network.include));
+    check("network.exclude", format!("{:?}", old.network.exclude), format!("{:?}", new.network.exclude));
+    check("sensors.source", format!("{:?}", old.sensors.source), format!("{:?}", new.sensors.source));
+    check("sensors.root", format!("{:?}", old.sensors.root), format!("{:?}", new.sensors.root));
+    check("notifications.enabled", old.notifications.enabled.to_string(), new.notifications.enabled.to_string());
+    check("notifications.appname", format!("{:?}", old.notifications.appname), format!("{:?}", new.notifications.appname));
+    check("notifications.default", format!("{:?}", old.notifications.default), format!("{:?}", new.notifications.default));
+
+    // Sin valores: los destinos pueden tener contraseñas o tokens
+    let (old_sinks, new_sinks) = (&old.notifications.sinks, &new.notifications.sinks);
+    for name in old_sinks.keys().chain(new_sinks.keys().filter(|k| !old_sinks.contains_key(*k))) {
+        let state = match (old_sinks.get(name), new_sinks.get(name)) {
+            (Some(o), Some(n)) if o == n => continue,
+            (Some(_), Some(_)) => "modificado",
+            (Some(_), None) => "eliminado",
+            _ => "agregado",
+        };
+        changes.push(format!("notifications.sinks.{}: {}", name, state));
+    }
+
+    changes
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+// Revisa el archivo una vez por segundo y, en Unix, recarga también con SIGHUP.
+// Una configuración inválida se informa y se descarta: sigue la anterior.
+pub fn spawn_watcher(
+    path: PathBuf,
+    overrides: Overrides,
+    shared: Arc<SharedConfig>,
+    shutdown: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    let hangup = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    if let Err(e) = signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&hangup)) {
+        eprintln!("No se pudo registrar SIGHUP: {}", e);
+    }
+
+    spawn(move || {
+        let mut last_modified = modified(&path);
+
+        while !crate::wait_or_shutdown(&shutdown, Duration::from_secs(1)) {
+            let current = modified(&path);
+            let signaled = hangup.swap(false, Ordering::SeqCst);
+            if !signaled && (current.is_none() || current == last_modified) {
+                continue;
+            }
+            last_modified = current;
+
+            match Config::from_file(&path, &overrides) {
+                Ok(config) => {
+                    if *shared.get() != config {
+                        shared.replace(config);
+                    }
+                }
+                Err(e) => eprintln!("Configuración no recargada, se mantiene la anterior: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "[[alerts.conditions]]\nmetric = \"cpu\"\nthreshold = 80.0\n\
+                        [[alerts.conditions]]\nmetric = \"memory\"\nthreshold = 90.0\n";
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        let old: Config = toml::from_str(old).unwrap();
+        let new: Config = toml::from_str(new).unwrap();
+        diff(&old, &new)
+    }
+
+    #[test]
+    fn same_config_has_no_changes() {
+        assert!(changes(BASE, BASE).is_empty());
+    }
+
+    #[test]
+    fn changed_threshold() {
+        let new = BASE.replace("threshold = 80.0", "threshold = 85.0");
+        assert_eq!(changes(BASE, &new), vec!["alerts.conditions[0].threshold: Some(80.0) -> Some(85.0)"]);
+    }
+
+    #[test]
+    fn added_and_removed_conditions() {
+        let added = format!("{}[[alerts.conditions]]\nmetric = \"swap\"\nthreshold = 50.0\n", BASE);
+        let change = changes(BASE, &added);
+        assert_eq!(change.len(), 1);
+        assert!(change[0].starts_with("alerts.conditions[2]: - -> swap ["), "{}", change[0]);
+
+        let change = changes(&added, BASE);
+        assert_eq!(change.len(), 1);
+        assert!(change[0].starts_with("alerts.conditions[2]: swap ["), "{}", change[0]);
+        assert!(change[0].ends_with("] -> -"), "{}", change[0]);
+    }
+
+    #[test]
+    fn replaced_condition_is_shown_whole() {
+        let new = BASE.replace("metric = \"memory\"", "metric = \"swap\"");
+        let change = changes(BASE, &new);
+        assert_eq!(change.len(), 1);
+        assert!(change[0].starts_with("alerts.conditions[1]: memory [") && change[0].contains("-> swap ["));
+    }
+
+    #[test]
+    fn sinks_are_listed_without_values() {
+        let old = "[notifications.sinks.equipo]\ntype = \"webhook\"\nurl = \"https://example.com/a?token=1\"\n";
+        let new = old.replace("token=1", "token=2");
+        assert_eq!(changes(old, &new), vec!["notifications.sinks.equipo: modificado"]);
+        assert_eq!(changes(old, ""), vec!["notifications.sinks.equipo: eliminado"]);
+        assert_eq!(changes("", old), vec!["notifications.sinks.equipo: agregado"]);
+    }
+}
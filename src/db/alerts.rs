@@ -0,0 +1,175 @@
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, Result};
+
+use super::{parse_timestamp, TIMESTAMP_FORMAT};
+use crate::alert::{AlertRecord, AlertState};
+
+fn format_time(time: &DateTime<Local>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+// Una alerta que empieza se inserta; al resolverse o interrumpirse se cierra
+// la fila activa de su regla.
+pub fn save_alert(conn: &Connection, record: &AlertRecord) -> Result<()> {
+    if record.state == AlertState::Firing {
+        conn.execute(
+            "INSERT INTO alerts (rule_id, metric, value, threshold, started_at, resolved_at, top_offender, state)
+             VALUES (?1, ?2, ?3, ?4, ?5, NULL, ?6, ?7)",
+            params![
+                record.rule_id,
+                record.metric,
+                record.value,
+                record.threshold,
+                format_time(&record.started_at),
+                record.top_offender,
+                record.state.as_str()
+            ],
+        )?;
+    } else {
+        conn.execute(
+            "UPDATE alerts SET state = ?1, resolved_at = ?2
+             WHERE rule_id = ?3 AND state = 'firing'",
+            params![
+                record.state.as_str(),
+                record.resolved_at.as_ref().map(format_time),
+                record.rule_id
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+// Alertas que quedaron activas porque el proceso terminó sin cerrarlas. No se
+// sabe cuándo se resolvieron, así que `resolved_at` queda vacío.
+pub fn interrupt_open_alerts(conn: &Connection) -> Result<usize> {
+    conn.execute("UPDATE alerts SET state = 'interrupted' WHERE state = 'firing'", [])
+}
+
+// Alertas iniciadas desde `since`, de la más vieja a la más nueva. Con
+// `limit` se devuelven solo las últimas.
+pub fn alerts_since(conn: &Connection, since: Option<&str>, limit: Option<usize>) -> Result<Vec<AlertRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT rule_id, metric, value, threshold, started_at, resolved_at, top_offender, state
+         FROM alerts
+         WHERE ?1 IS NULL OR started_at >= ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let limit = limit.map(|l| l as i64).unwrap_or(-1);
+    let mut records = stmt
+        .query_map(params![since, limit], |row| {
+            let started_at: String = row.get(4)?;
+            let resolved_at: Option<String> = row.get(5)?;
+            let state: String = row.get(7)?;
+            Ok(AlertRecord {
+                rule_id: row.get(0)?,
+                metric: row.get(1)?,
+                value: row.get(2)?,
+                threshold: row.get(3)?,
+                started_at: parse_timestamp(&started_at).unwrap_or_default(),
+                resolved_at: resolved_at.as_deref().and_then(parse_timestamp),
+                top_offender: row.get(6)?,
+                state: AlertState::parse(&state).unwrap_or(AlertState::Interrupted),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    records.reverse();
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Duration as TimeDelta;
+
+    use super::*;
+    use crate::alert::{AlertCondition, AlertEvent, AlertManager, Metric};
+    use crate::collector::{test_process, test_sample, Sample};
+    use crate::config::{AlertsConfig, NotificationsConfig};
+    use crate::db::migrate;
+
+    fn database() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn config(conditions: Vec<AlertCondition>) -> AlertsConfig {
+        AlertsConfig { cooldown: Duration::ZERO, conditions, watch: Vec::new() }
+    }
+
+    fn sample(second: i64, cpu: f32) -> Sample {
+        let mut sample = test_sample();
+        sample.timestamp += TimeDelta::seconds(second);
+        sample.cpu_total = cpu;
+        sample.processes = vec![test_process(42, "stress", cpu, 0), test_process(7, "bash", 1.0, 0)];
+        sample
+    }
+
+    // Guarda lo que el monitoreo guardaría de cada evento
+    fn check(conn: &Connection, alerts: &mut AlertManager, sample: &Sample) {
+        for event in alerts.check_alerts(sample) {
+            match event {
+                AlertEvent::Firing { record, .. } | AlertEvent::Resolved { record, .. } => {
+                    save_alert(conn, &record).unwrap()
+                }
+                AlertEvent::Process { .. } => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn firing_and_resolved_rows() {
+        let conn = database();
+        let mut alerts = AlertManager::silent(&config(vec![AlertCondition::new(Metric::Cpu, 80.0)]));
+        check(&conn, &mut alerts, &sample(0, 95.0));
+
+        let [open] = alerts_since(&conn, None, None).unwrap().try_into().unwrap();
+        assert_eq!((open.rule_id.as_str(), open.metric.as_str()), ("cpu", "cpu"));
+        assert_eq!((open.value, open.threshold, open.state), (95.0, 80.0, AlertState::Firing));
+        assert_eq!(open.started_at, sample(0, 0.0).timestamp);
+        assert_eq!(open.resolved_at, None);
+        assert_eq!(open.top_offender.as_deref(), Some("stress (95.00%)"));
+
+        check(&conn, &mut alerts, &sample(90, 10.0));
+        let [closed] = alerts_since(&conn, None, None).unwrap().try_into().unwrap();
+        assert_eq!(closed.state, AlertState::Resolved);
+        assert_eq!(closed.started_at, sample(0, 0.0).timestamp);
+        assert_eq!(closed.resolved_at, Some(sample(90, 0.0).timestamp));
+        assert_eq!((closed.value, closed.top_offender), (95.0, open.top_offender));
+
+        // `since` filtra por el inicio
+        assert!(alerts_since(&conn, Some("2024-01-01 12:00:01"), None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn open_alerts_are_interrupted() {
+        let conn = database();
+        let conditions = vec![AlertCondition::new(Metric::Cpu, 80.0), AlertCondition::new(Metric::Memory, 101.0)];
+        let mut alerts = AlertManager::silent(&config(conditions));
+        check(&conn, &mut alerts, &sample(0, 95.0));
+
+        // Al arrancar, las que quedaron activas de una ejecución anterior
+        assert_eq!(interrupt_open_alerts(&conn).unwrap(), 1);
+        let [stale] = alerts_since(&conn, None, None).unwrap().try_into().unwrap();
+        assert_eq!((stale.state, stale.resolved_at), (AlertState::Interrupted, None));
+
+        // Al quitar la condición de una alerta activa
+        check(&conn, &mut alerts, &sample(10, 10.0));
+        check(&conn, &mut alerts, &sample(20, 95.0));
+        let notifications = NotificationsConfig { enabled: false, ..Default::default() };
+        let interrupted = alerts.reconfigure(&config(vec![AlertCondition::new(Metric::Memory, 101.0)]), &notifications);
+        for record in &interrupted {
+            save_alert(&conn, record).unwrap();
+        }
+        let records = alerts_since(&conn, None, None).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].started_at, sample(20, 0.0).timestamp);
+        assert_eq!(records[1].state, AlertState::Interrupted);
+        assert!(records[1].resolved_at.is_some());
+        assert_eq!(interrupt_open_alerts(&conn).unwrap(), 0);
+
+        assert_eq!(alerts_since(&conn, None, Some(1)).unwrap()[0].started_at, records[1].started_at);
+    }
+}
@@ -0,0 +1,583 @@
+use rusqlite::{params, Connection, Result, Transaction};
+
+type Migration = fn(&Transaction) -> Result<()>;
+
+// Cada entrada sube `PRAGMA user_version` en uno. Nunca reordenar ni editar
+// migraciones ya publicadas: solo agregar nuevas al final.
+const MIGRATIONS: &[Migration] = &[
+    create_legacy_metrics,
+    normalize_metrics,
+    add_network_interfaces,
+    create_alerts,
+    add_disk_space,
+    create_process_events,
+    add_disk_inodes,
+    add_disk_io,
+    add_load_and_frequency,
+    create_temperatures,
+    add_swap,
+    create_pressure,
+    create_cgroup,
+    create_cgroup_usage,
+    create_process_kills,
+];
+
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    let current: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(current) {
+        let tx = conn.transaction()?;
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", (version + 1) as i64)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
+// v1: la tabla original de texto. Las bases existentes ya la tienen.
+fn create_legacy_metrics(tx: &Transaction) -> Result<()> {
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS metrics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            Hora TEXT NOT NULL,
+            Uso_CPU TEXT,
+            Uso_CPU_Total TEXT,
+            Memoria_total TEXT,
+            Memoria_usada TEXT,
+            Procesos TEXT,
+            Discos TEXT,
+            Internet TEXT,
+            Procesos_CPU TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// v2: columnas numéricas y tablas hijas por muestra. Las filas viejas se
+// convierten conservando su id.
+fn normalize_metrics(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics RENAME TO metrics_v1;
+
+        CREATE TABLE metrics (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            cpu_total REAL NOT NULL,
+            memory_total INTEGER NOT NULL,
+            memory_used INTEGER NOT NULL,
+            net_rx_mbps REAL NOT NULL,
+            net_tx_mbps REAL NOT NULL
+        );
+        CREATE INDEX metrics_timestamp ON metrics (timestamp);
+
+        CREATE TABLE metrics_cpu (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            core INTEGER NOT NULL,
+            usage REAL NOT NULL,
+            PRIMARY KEY (sample_id, core)
+        );
+
+        CREATE TABLE metrics_processes (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            ranking TEXT NOT NULL CHECK (ranking IN ('cpu', 'memory')),
+            position INTEGER NOT NULL,
+            pid INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            cpu REAL,
+            memory INTEGER,
+            PRIMARY KEY (sample_id, ranking, position)
+        );
+
+        CREATE TABLE metrics_disks (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            mount TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            read_bytes INTEGER NOT NULL,
+            written_bytes INTEGER NOT NULL
+        );
+        CREATE INDEX metrics_disks_sample ON metrics_disks (sample_id);",
+    )?;
+
+    let mut select = tx.prepare(
+        "SELECT id, Hora, Uso_CPU, Uso_CPU_Total, Memoria_total, Memoria_usada,
+                Procesos, Discos, Internet, Procesos_CPU
+         FROM metrics_v1 ORDER BY id",
+    )?;
+    let mut rows = select.query([])?;
+
+    while let Some(row) = rows.next()? {
+        let id: i64 = row.get(0)?;
+        let timestamp: String = row.get(1)?;
+        let cores: Option<String> = row.get(2)?;
+        let cpu_total: Option<String> = row.get(3)?;
+        let memory_total: Option<String> = row.get(4)?;
+        let memory_used: Option<String> = row.get(5)?;
+        let top_memory: Option<String> = row.get(6)?;
+        let disks: Option<String> = row.get(7)?;
+        let network: Option<String> = row.get(8)?;
+        let top_cpu: Option<String> = row.get(9)?;
+
+        let (rx, tx_mbps) = network.as_deref().and_then(parse_network).unwrap_or((0.0, 0.0));
+
+        tx.execute(
+            "INSERT INTO metrics (id, timestamp, cpu_total, memory_total, memory_used, net_rx_mbps, net_tx_mbps)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                timestamp,
+                cpu_total.as_deref().and_then(parse_number).unwrap_or(0.0),
+                memory_total.as_deref().and_then(parse_megabytes).unwrap_or(0),
+                memory_used.as_deref().and_then(parse_megabytes).unwrap_or(0),
+                rx,
+                tx_mbps
+            ],
+        )?;
+
+        for (core, usage) in cores.as_deref().map(parse_cores).unwrap_or_default() {
+            tx.execute(
+                "INSERT OR IGNORE INTO metrics_cpu (sample_id, core, usage) VALUES (?1, ?2, ?3)",
+                params![id, core, usage],
+            )?;
+        }
+
+        for (ranking, list) in [("cpu", top_cpu), ("memory", top_memory)] {
+            let processes = list.as_deref().map(parse_processes).unwrap_or_default();
+            for (position, (pid, name, cpu, memory)) in processes.into_iter().enumerate() {
+                tx.execute(
+                    "INSERT INTO metrics_processes (sample_id, ranking, position, pid, name, cpu, memory)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![id, ranking, position as i64, pid, name, cpu, memory],
+                )?;
+            }
+        }
+
+        for (mount, kind, read, written) in disks.as_deref().map(parse_disks).unwrap_or_default() {
+            tx.execute(
+                "INSERT INTO metrics_disks (sample_id, mount, kind, read_bytes, written_bytes)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![id, mount, kind, read, written],
+            )?;
+        }
+    }
+    drop(rows);
+    drop(select);
+
+    tx.execute("DROP TABLE metrics_v1", [])?;
+    Ok(())
+}
+
+// v3: tasas por interfaz. Las filas convertidas de v1 solo medían "Wi-Fi".
+fn add_network_interfaces(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE metrics_network (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            interface TEXT NOT NULL,
+            rx_mbps REAL NOT NULL,
+            tx_mbps REAL NOT NULL,
+            PRIMARY KEY (sample_id, interface)
+        );
+
+        INSERT INTO metrics_network (sample_id, interface, rx_mbps, tx_mbps)
+        SELECT id, 'Wi-Fi', net_rx_mbps, net_tx_mbps FROM metrics;",
+    )?;
+    Ok(())
+}
+
+// v4: historial de alertas. A lo sumo una fila `firing` por regla.
+fn create_alerts(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            rule_id TEXT NOT NULL,
+            metric TEXT NOT NULL,
+            value REAL NOT NULL,
+            threshold REAL NOT NULL,
+            started_at TEXT NOT NULL,
+            resolved_at TEXT,
+            top_offender TEXT,
+            state TEXT NOT NULL CHECK (state IN ('firing', 'resolved', 'interrupted'))
+        );
+        CREATE INDEX alerts_started_at ON alerts (started_at);
+        CREATE UNIQUE INDEX alerts_firing ON alerts (rule_id) WHERE state = 'firing';",
+    )?;
+    Ok(())
+}
+
+// v5: espacio de cada disco. Las filas anteriores quedan en NULL.
+fn add_disk_space(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics_disks ADD COLUMN total_bytes INTEGER;
+        ALTER TABLE metrics_disks ADD COLUMN available_bytes INTEGER;",
+    )?;
+    Ok(())
+}
+
+// v6: inicios, finales y reinicios de los procesos vigilados.
+fn create_process_events(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE process_events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            watch_id TEXT NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('started', 'exited', 'restarted')),
+            pid INTEGER NOT NULL,
+            old_pid INTEGER,
+            name TEXT NOT NULL,
+            timestamp TEXT NOT NULL
+        );
+        CREATE INDEX process_events_timestamp ON process_events (timestamp);",
+    )?;
+    Ok(())
+}
+
+// v7: inodos de cada disco (solo Unix). Las filas anteriores quedan en NULL.
+fn add_disk_inodes(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics_disks ADD COLUMN inodes_total INTEGER;
+        ALTER TABLE metrics_disks ADD COLUMN inodes_free INTEGER;",
+    )?;
+    Ok(())
+}
+
+// v8: dispositivo y actividad de cada disco. Antes read_bytes y
+// written_bytes no eran comparables entre muestras; se dejan como estaban.
+fn add_disk_io(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics_disks ADD COLUMN device TEXT;
+        ALTER TABLE metrics_disks ADD COLUMN read_rate REAL;
+        ALTER TABLE metrics_disks ADD COLUMN write_rate REAL;
+        ALTER TABLE metrics_disks ADD COLUMN read_iops REAL;
+        ALTER TABLE metrics_disks ADD COLUMN write_iops REAL;
+        ALTER TABLE metrics_disks ADD COLUMN read_latency_ms REAL;
+        ALTER TABLE metrics_disks ADD COLUMN write_latency_ms REAL;
+        ALTER TABLE metrics_disks ADD COLUMN queue_depth REAL;",
+    )?;
+    Ok(())
+}
+
+// v9: carga promedio, tiempo encendido y frecuencia de cada núcleo.
+fn add_load_and_frequency(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics ADD COLUMN load1 REAL;
+        ALTER TABLE metrics ADD COLUMN load5 REAL;
+        ALTER TABLE metrics ADD COLUMN load15 REAL;
+        ALTER TABLE metrics ADD COLUMN uptime INTEGER;
+        ALTER TABLE metrics_cpu ADD COLUMN frequency INTEGER;",
+    )?;
+    Ok(())
+}
+
+// v10: temperatura de cada sensor por muestra.
+fn create_temperatures(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE temperatures (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            label TEXT NOT NULL,
+            current REAL NOT NULL,
+            max REAL,
+            critical REAL
+        );
+        CREATE INDEX temperatures_sample ON temperatures (sample_id);",
+    )?;
+    Ok(())
+}
+
+// v11: memoria disponible, swap y tasas de swap (solo Linux).
+fn add_swap(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE metrics ADD COLUMN memory_available INTEGER;
+        ALTER TABLE metrics ADD COLUMN swap_total INTEGER;
+        ALTER TABLE metrics ADD COLUMN swap_used INTEGER;
+        ALTER TABLE metrics ADD COLUMN swap_in_rate REAL;
+        ALTER TABLE metrics ADD COLUMN swap_out_rate REAL;",
+    )?;
+    Ok(())
+}
+
+// v12: Pressure Stall Information de Linux, una fila por recurso y tipo.
+fn create_pressure(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE pressure (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            resource TEXT NOT NULL CHECK (resource IN ('cpu', 'memory', 'io')),
+            kind TEXT NOT NULL CHECK (kind IN ('some', 'full')),
+            avg10 REAL NOT NULL,
+            avg60 REAL NOT NULL,
+            avg300 REAL NOT NULL
+        );
+        CREATE INDEX pressure_sample ON pressure (sample_id);",
+    )?;
+    Ok(())
+}
+
+// v13: cgroup v2 del monitor, una fila por muestra cuando corre en uno.
+fn create_cgroup(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE metrics_cgroup (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            path TEXT NOT NULL,
+            memory_current INTEGER NOT NULL,
+            memory_limit INTEGER,
+            cpu_limit REAL,
+            cpu_usage REAL,
+            throttled_percent REAL
+        );
+        CREATE INDEX metrics_cgroup_sample ON metrics_cgroup (sample_id);",
+    )?;
+    Ok(())
+}
+
+// v14: uso de los procesos agrupado por cgroup (unidad o contenedor).
+fn create_cgroup_usage(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE cgroup_usage (
+            sample_id INTEGER NOT NULL REFERENCES metrics (id) ON DELETE CASCADE,
+            path TEXT NOT NULL,
+            name TEXT NOT NULL,
+            processes INTEGER NOT NULL,
+            cpu REAL NOT NULL,
+            memory INTEGER NOT NULL,
+            read_rate REAL NOT NULL,
+            write_rate REAL NOT NULL
+        );
+        CREATE INDEX cgroup_usage_sample ON cgroup_usage (sample_id);",
+    )?;
+    Ok(())
+}
+
+// v15: señales enviadas desde la ventana y su resultado.
+fn create_process_kills(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE process_kills (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            pid INTEGER NOT NULL,
+            start_time INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            user TEXT,
+            cmdline TEXT NOT NULL,
+            signal TEXT NOT NULL CHECK (signal IN ('TERM', 'INT', 'HUP', 'STOP', 'CONT', 'KILL')),
+            result TEXT NOT NULL CHECK (result IN (
+                'sent', 'exited', 'escalated', 'survived', 'not_found', 'pid_reused', 'failed', 'unsupported'
+            )),
+            elapsed_ms INTEGER NOT NULL
+        );
+        CREATE INDEX process_kills_timestamp ON process_kills (timestamp);",
+    )?;
+    Ok(())
+}
+
+// --- Lectura del formato de texto de v1 ---
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().trim_end_matches('%').trim().parse().ok()
+}
+
+// "1234.56 MB" -> bytes
+fn parse_megabytes(text: &str) -> Option<i64> {
+    let mb = parse_number(text.trim().trim_end_matches("MB"))?;
+    Some((mb * 1024.0 * 1024.0).round() as i64)
+}
+
+// "CPU 1: 12.00% | CPU 2: 3.50%" -> [(0, 12.0), (1, 3.5)]
+fn parse_cores(text: &str) -> Vec<(i64, f64)> {
+    text.split(" | ")
+        .filter_map(|part| {
+            let (label, usage) = part.split_once(':')?;
+            let index: i64 = label.trim().strip_prefix("CPU")?.trim().parse().ok()?;
+            Some((index - 1, parse_number(usage)?))
+        })
+        .collect()
+}
+
+// "[123] nombre (CPU: 1.00%)" o "[123] nombre (Mem: 10.00 MB)"
+fn parse_processes(text: &str) -> Vec<(i64, String, Option<f64>, Option<i64>)> {
+    text.split(" | ")
+        .filter_map(|part| {
+            let rest = part.trim().strip_prefix('[')?;
+            let (pid, rest) = rest.split_once(']')?;
+            let pid: i64 = pid.trim().parse().ok()?;
+            let open = rest.rfind(" (")?;
+            let name = rest[..open].trim().to_string();
+            let value = rest[open + 2..].trim_end_matches(')');
+
+            if let Some(cpu) = value.strip_prefix("CPU:") {
+                Some((pid, name, parse_number(cpu), None))
+            } else if let Some(mem) = value.strip_prefix("Mem:") {
+                Some((pid, name, None, parse_megabytes(mem)))
+            } else {
+                Some((pid, name, None, None))
+            }
+        })
+        .collect()
+}
+
+// "[C:\][Tipo: SSD] Lectura: 1.00 MB | Escritura: 2.00 MB | [D:\]..." — el
+// separador entre discos también aparece dentro de cada disco.
+fn parse_disks(text: &str) -> Vec<(String, String, i64, i64)> {
+    text.split(" | [")
+        .filter_map(|part| {
+            let part = part.strip_prefix('[').unwrap_or(part);
+            let (mount, rest) = part.split_once("][Tipo: ")?;
+            let (kind, rest) = rest.split_once(']')?;
+            let (read, written) = rest.split_once(" | ")?;
+            let read = parse_megabytes(read.trim().strip_prefix("Lectura:")?)?;
+            let written = parse_megabytes(written.trim().strip_prefix("Escritura:")?)?;
+            Some((mount.to_string(), kind.to_string(), read, written))
+        })
+        .collect()
+}
+
+// "Wi-Fi: 1.00 Mbps (down) / 2.00 Mbps (up)"
+fn parse_network(text: &str) -> Option<(f64, f64)> {
+    let (_, rates) = text.split_once(':')?;
+    let (down, up) = rates.split_once(" / ")?;
+    let down = parse_number(down.trim().strip_suffix("Mbps (down)")?)?;
+    let up = parse_number(up.trim().strip_suffix("Mbps (up)")?)?;
+    Some((down, up))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: i64 = 1024 * 1024;
+
+    // Base tal como la dejaba la versión de texto, sin `user_version`.
+    fn legacy_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE metrics (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                Hora TEXT NOT NULL,
+                Uso_CPU TEXT,
+                Uso_CPU_Total TEXT,
+                Memoria_total TEXT,
+                Memoria_usada TEXT,
+                Procesos TEXT,
+                Discos TEXT,
+                Internet TEXT,
+                Procesos_CPU TEXT
+            );
+            INSERT INTO metrics VALUES (
+                1, '2024-01-01 12:00:00',
+                'CPU 1: 12.00% | CPU 2: 3.50%', '7.75%', '16000.00 MB', '8000.50 MB',
+                '[10] firefox (Mem: 512.00 MB) | [20] code (Mem: 256.00 MB)',
+                '[C:\][Tipo: SSD] Lectura: 1.00 MB | Escritura: 2.00 MB | [D:\][Tipo: HDD] Lectura: 0.50 MB | Escritura: 0.00 MB',
+                'Wi-Fi: 1.50 Mbps (down) / 0.25 Mbps (up)',
+                '[10] firefox (CPU: 25.00%) | [30] mi proceso (x) (CPU: 5.00%)'
+            );
+            INSERT INTO metrics VALUES (
+                7, '2024-01-01 12:00:05',
+                'basura | CPU x: 5% | CPU 3: 9.00%', 'n/a', NULL, 'mucho',
+                'sin formato | [abc] x (Mem: 1.00 MB) | [5] ok (Mem: 1.00 MB)',
+                'roto', 'Wi-Fi: ? / ?', NULL
+            );"#,
+        )
+        .unwrap();
+        conn
+    }
+
+    fn version(conn: &Connection) -> usize {
+        conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+    }
+
+    fn rows<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str) -> Vec<T> {
+        let mut stmt = conn.prepare(sql).unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn legacy_rows_are_normalized() {
+        let mut conn = legacy_db();
+        migrate(&mut conn).unwrap();
+        assert_eq!(version(&conn), MIGRATIONS.len());
+
+        let metrics: Vec<(i64, String, f64, i64, i64, f64, f64)> = conn
+            .prepare("SELECT id, timestamp, cpu_total, memory_total, memory_used, net_rx_mbps, net_tx_mbps FROM metrics ORDER BY id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?, r.get(6)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            metrics,
+            [
+                (1, "2024-01-01 12:00:00".to_string(), 7.75, 16000 * MB, (8000.5 * MB as f64) as i64, 1.5, 0.25),
+                // Lo ilegible queda en 0 pero la muestra se conserva con su id
+                (7, "2024-01-01 12:00:05".to_string(), 0.0, 0, 0, 0.0, 0.0),
+            ]
+        );
+
+        let cores: Vec<String> = rows(&conn, "SELECT sample_id || ':' || core || '=' || usage FROM metrics_cpu ORDER BY sample_id, core");
+        assert_eq!(cores, ["1:0=12.0", "1:1=3.5", "7:2=9.0"]);
+
+        let processes: Vec<String> = rows(
+            &conn,
+            "SELECT sample_id || ' ' || ranking || ' ' || position || ' ' || pid || ' ' || name || ' '
+                    || IFNULL(cpu, '-') || ' ' || IFNULL(memory, '-')
+             FROM metrics_processes ORDER BY sample_id, ranking, position",
+        );
+        assert_eq!(
+            processes,
+            [
+                "1 cpu 0 10 firefox 25.0 -".to_string(),
+                "1 cpu 1 30 mi proceso (x) 5.0 -".to_string(),
+                format!("1 memory 0 10 firefox - {}", 512 * MB),
+                format!("1 memory 1 20 code - {}", 256 * MB),
+                format!("7 memory 0 5 ok - {}", MB),
+            ]
+        );
+
+        let disks: Vec<String> = rows(
+            &conn,
+            "SELECT sample_id || ' ' || mount || ' ' || kind || ' ' || read_bytes || ' ' || written_bytes
+             FROM metrics_disks ORDER BY sample_id, mount",
+        );
+        assert_eq!(disks, [format!(r"1 C:\ SSD {} {}", MB, 2 * MB), format!(r"1 D:\ HDD {} 0", MB / 2)]);
+
+        // v3 copia la red de v1 como la interfaz "Wi-Fi"
+        let network: Vec<String> = rows(&conn, "SELECT sample_id || ' ' || interface || ' ' || rx_mbps FROM metrics_network ORDER BY sample_id");
+        assert_eq!(network, ["1 Wi-Fi 1.5", "7 Wi-Fi 0.0"]);
+
+        let legacy: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'metrics_v1'", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(legacy, 0);
+    }
+
+    #[test]
+    fn migrate_is_idempotent() {
+        let mut conn = legacy_db();
+        migrate(&mut conn).unwrap();
+        let schema = |conn: &Connection| -> Vec<String> {
+            rows(conn, "SELECT type || ' ' || name || ' ' || IFNULL(sql, '') FROM sqlite_master ORDER BY name")
+        };
+        let before = (schema(&conn), rows::<i64>(&conn, "SELECT COUNT(*) FROM metrics_processes"));
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(version(&conn), MIGRATIONS.len());
+        assert_eq!((schema(&conn), rows::<i64>(&conn, "SELECT COUNT(*) FROM metrics_processes")), before);
+    }
+
+    #[test]
+    fn new_database_gets_latest_schema() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        assert_eq!(version(&conn), MIGRATIONS.len());
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM metrics", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn parses_legacy_numbers() {
+        assert_eq!(parse_number(" 12.50 % "), Some(12.5));
+        assert_eq!(parse_number("n/a"), None);
+        assert_eq!(parse_megabytes("1.50 MB"), Some(MB + MB / 2));
+        assert_eq!(parse_megabytes(""), None);
+        assert_eq!(parse_network("eth0: 3.00 Mbps (down) / 4.00 Mbps (up)"), Some((3.0, 4.0)));
+        assert_eq!(parse_network("Wi-Fi: 3.00 Mbps"), None);
+    }
+}
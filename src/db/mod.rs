@@ -0,0 +1,474 @@
+use std::path::Path;
+
+use chrono::{DateTime, Local, NaiveDateTime};
+use rusqlite::{params, Connection, Result};
+
+use crate::collector::{DiskSample, ProcessSample, Sample};
+use crate::disk::{DiskIo, FillTracker};
+use crate::linux::cgroup::{CgroupSample, CgroupUsage};
+use crate::linux::psi::{self, Pressure, PressureAvg, Psi};
+use crate::network::InterfaceRate;
+use crate::sensors::TemperatureSample;
+
+mod alerts;
+mod migrations;
+mod process_events;
+mod process_kills;
+
+pub use alerts::{alerts_since, interrupt_open_alerts, save_alert};
+pub use process_events::{process_events_since, save_process_event};
+pub use process_kills::{process_kills_since, save_process_kill};
+pub use migrations::migrate;
+
+// Fila de `metrics` sin las tablas hijas, para consultas y exportación.
+pub struct SampleSummary {
+    pub id: i64,
+    pub timestamp: String,
+    pub cpu_total: f64,
+    pub memory_total: i64,
+    pub memory_used: i64,
+    pub net_rx_mbps: f64,
+    pub net_tx_mbps: f64,
+    // Sin dato en las muestras anteriores a que se midieran
+    pub load1: Option<f64>,
+    pub load5: Option<f64>,
+    pub load15: Option<f64>,
+    pub uptime: Option<i64>,
+    pub memory_available: Option<i64>,
+    pub swap_total: Option<i64>,
+    pub swap_used: Option<i64>,
+    pub swap_in_rate: Option<f64>,
+    pub swap_out_rate: Option<f64>,
+}
+
+impl SampleSummary {
+    // Igual que `Sample::memory_percent`
+    pub fn memory_percent(&self) -> f64 {
+        if self.memory_total <= 0 {
+            return 0.0;
+        }
+        let used = match self.memory_available {
+            Some(available) if available > 0 => self.memory_total - available,
+            _ => self.memory_used,
+        };
+        used as f64 / self.memory_total as f64 * 100.0
+    }
+
+    pub fn swap_percent(&self) -> Option<f64> {
+        let (total, used) = (self.swap_total?, self.swap_used?);
+        (total > 0).then(|| used as f64 / total as f64 * 100.0)
+    }
+}
+
+pub fn open(path: impl AsRef<Path>) -> Result<Connection> {
+    let mut conn = Connection::open(path)?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    migrate(&mut conn)?;
+    Ok(conn)
+}
+
+pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Local>> {
+    NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_local_timezone(Local)
+        .earliest()
+}
+
+// Cuántos procesos de cada ranking se guardan por muestra
+const TOP_PROCESSES: usize = 5;
+
+pub fn insert_sample(conn: &mut Connection, sample: &Sample) -> Result<i64> {
+    let (net_rx_mbps, net_tx_mbps) = sample.network_totals();
+    let tx = conn.transaction()?;
+
+    tx.execute(
+        "INSERT INTO metrics (
+            timestamp,
+            cpu_total,
+            memory_total,
+            memory_used,
+            net_rx_mbps,
+            net_tx_mbps,
+            load1,
+            load5,
+            load15,
+            uptime,
+            memory_available,
+            swap_total,
+            swap_used,
+            swap_in_rate,
+            swap_out_rate
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            sample.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+            sample.cpu_total,
+            sample.memory_total as i64,
+            sample.memory_used as i64,
+            net_rx_mbps,
+            net_tx_mbps,
+            sample.load_average[0],
+            sample.load_average[1],
+            sample.load_average[2],
+            sample.uptime as i64,
+            sample.memory_available as i64,
+            sample.swap_total as i64,
+            sample.swap_used as i64,
+            sample.swap_in_rate,
+            sample.swap_out_rate
+        ],
+    )?;
+    let sample_id = tx.last_insert_rowid();
+
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO metrics_cpu (sample_id, core, usage, frequency) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for (core, usage) in sample.cpu_cores.iter().enumerate() {
+            let frequency = sample.cpu_frequencies.get(core).map(|f| *f as i64);
+            stmt.execute(params![sample_id, core as i64, usage, frequency])?;
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO metrics_network (sample_id, interface, rx_mbps, tx_mbps)
+             VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        for iface in &sample.interfaces {
+            stmt.execute(params![sample_id, iface.name, iface.rx_mbps, iface.tx_mbps])?;
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO metrics_processes (sample_id, ranking, position, pid, name, cpu, memory)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        )?;
+        let rankings = [
+            ("cpu", sample.top_cpu(TOP_PROCESSES)),
+            ("memory", sample.top_memory(TOP_PROCESSES)),
+        ];
+        for (ranking, processes) in rankings {
+            for (position, p) in processes.iter().enumerate() {
+                stmt.execute(params![
+                    sample_id,
+                    ranking,
+                    position as i64,
+                    p.pid,
+                    p.name,
+                    p.cpu,
+                    p.memory as i64
+                ])?;
+            }
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO metrics_disks (
+                sample_id, mount, kind, read_bytes, written_bytes, total_bytes, available_bytes, inodes_total, inodes_free,
+                device, read_rate, write_rate, read_iops, write_iops, read_latency_ms, write_latency_ms, queue_depth
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+        )?;
+        for disk in &sample.disks {
+            stmt.execute(params![
+                sample_id,
+                disk.mount,
+                disk.kind,
+                disk.read_bytes as i64,
+                disk.written_bytes as i64,
+                disk.total_space as i64,
+                disk.available_space as i64,
+                disk.inodes_total.map(|i| i as i64),
+                disk.inodes_free.map(|i| i as i64),
+                disk.device,
+                disk.io.read_rate,
+                disk.io.write_rate,
+                disk.io.read_iops,
+                disk.io.write_iops,
+                disk.io.read_latency_ms,
+                disk.io.write_latency_ms,
+                disk.io.queue_depth
+            ])?;
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO temperatures (sample_id, label, current, max, critical) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?;
+        for sensor in &sample.temperatures {
+            stmt.execute(params![sample_id, sensor.label, sensor.current, sensor.max, sensor.critical])?;
+        }
+
+        if let Some(cgroup) = &sample.cgroup {
+            tx.execute(
+                "INSERT INTO metrics_cgroup (
+                    sample_id, path, memory_current, memory_limit, cpu_limit, cpu_usage, throttled_percent
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    sample_id,
+                    cgroup.path,
+                    cgroup.memory_current as i64,
+                    cgroup.memory_limit.map(|l| l as i64),
+                    cgroup.cpu_limit,
+                    cgroup.cpu_usage,
+                    cgroup.throttled_percent
+                ],
+            )?;
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO cgroup_usage (sample_id, path, name, processes, cpu, memory, read_rate, write_rate)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        )?;
+        for group in &sample.cgroups {
+            stmt.execute(params![
+                sample_id,
+                group.path,
+                group.name,
+                group.processes as i64,
+                group.cpu,
+                group.memory as i64,
+                group.read_rate,
+                group.write_rate
+            ])?;
+        }
+
+        let mut stmt = tx.prepare(
+            "INSERT INTO pressure (sample_id, resource, kind, avg10, avg60, avg300) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+        for resource in psi::RESOURCES {
+            let Some(pressure) = sample.pressure.get(resource) else {
+                continue;
+            };
+            let kinds = [("some", Some(pressure.some)), ("full", pressure.full)];
+            for (kind, avg) in kinds.into_iter().filter_map(|(kind, avg)| Some((kind, avg?))) {
+                stmt.execute(params![sample_id, resource, kind, avg.avg10, avg.avg60, avg.avg300])?;
+            }
+        }
+    }
+
+    tx.commit()?;
+    Ok(sample_id)
+}
+
+// Muestras desde `since` (mismo formato que `timestamp`), de la más vieja a la
+// más nueva. Con `limit` se devuelven solo las últimas.
+pub fn samples_since(conn: &Connection, since: Option<&str>, limit: Option<usize>) -> Result<Vec<SampleSummary>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, timestamp, cpu_total, memory_total, memory_used, net_rx_mbps, net_tx_mbps,
+                load1, load5, load15, uptime,
+                memory_available, swap_total, swap_used, swap_in_rate, swap_out_rate
+         FROM metrics
+         WHERE ?1 IS NULL OR timestamp >= ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let limit = limit.map(|l| l as i64).unwrap_or(-1);
+    let mut samples = stmt
+        .query_map(params![since, limit], |row| {
+            Ok(SampleSummary {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                cpu_total: row.get(2)?,
+                memory_total: row.get(3)?,
+                memory_used: row.get(4)?,
+                net_rx_mbps: row.get(5)?,
+                net_tx_mbps: row.get(6)?,
+                load1: row.get(7)?,
+                load5: row.get(8)?,
+                load15: row.get(9)?,
+                uptime: row.get(10)?,
+                memory_available: row.get(11)?,
+                swap_total: row.get(12)?,
+                swap_used: row.get(13)?,
+                swap_in_rate: row.get(14)?,
+                swap_out_rate: row.get(15)?,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    samples.reverse();
+    Ok(samples)
+}
+
+// Rearma las muestras guardadas desde `since` para volver a evaluarlas. Solo
+// se guardan los procesos de los rankings, así que `processes` es parcial.
+pub fn load_samples(conn: &Connection, since: Option<&str>) -> Result<Vec<Sample>> {
+    let mut cpu = conn.prepare("SELECT usage, frequency FROM metrics_cpu WHERE sample_id = ?1 ORDER BY core")?;
+    let mut network = conn.prepare(
+        "SELECT interface, rx_mbps, tx_mbps FROM metrics_network WHERE sample_id = ?1 ORDER BY interface",
+    )?;
+    let mut processes = conn.prepare(
+        "SELECT pid, name, MAX(cpu), MAX(memory) FROM metrics_processes
+         WHERE sample_id = ?1 GROUP BY pid, name",
+    )?;
+    let mut disks = conn.prepare(
+        "SELECT mount, kind, read_bytes, written_bytes, total_bytes, available_bytes, inodes_total, inodes_free,
+                device, read_rate, write_rate, read_iops, write_iops, read_latency_ms, write_latency_ms, queue_depth
+         FROM metrics_disks WHERE sample_id = ?1",
+    )?;
+    let mut cgroup = conn.prepare(
+        "SELECT path, memory_current, memory_limit, cpu_limit, cpu_usage, throttled_percent
+         FROM metrics_cgroup WHERE sample_id = ?1",
+    )?;
+    let mut cgroups = conn.prepare(
+        "SELECT path, name, processes, cpu, memory, read_rate, write_rate
+         FROM cgroup_usage WHERE sample_id = ?1 ORDER BY cpu DESC, memory DESC",
+    )?;
+    let mut pressure =
+        conn.prepare("SELECT resource, kind, avg10, avg60, avg300 FROM pressure WHERE sample_id = ?1")?;
+    let mut temperatures =
+        conn.prepare("SELECT label, current, max, critical FROM temperatures WHERE sample_id = ?1 ORDER BY label")?;
+
+    let mut samples = Vec::new();
+    let mut fill = FillTracker::default();
+    for summary in samples_since(conn, since, None)? {
+        let id = summary.id;
+        let (cpu_cores, cpu_frequencies): (Vec<f32>, Vec<u64>) = cpu
+            .query_map([id], |row| {
+                Ok((row.get::<_, f64>(0)? as f32, row.get::<_, Option<i64>>(1)?.unwrap_or_default() as u64))
+            })?
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .unzip();
+        let interfaces = network
+            .query_map([id], |row| {
+                Ok(InterfaceRate {
+                    name: row.get(0)?,
+                    rx_mbps: row.get(1)?,
+                    tx_mbps: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        let processes = processes
+            .query_map([id], |row| {
+                Ok(ProcessSample {
+                    pid: row.get(0)?,
+                    parent: None,
+                    name: row.get(1)?,
+                    cpu: row.get::<_, Option<f64>>(2)?.unwrap_or_default() as f32,
+                    memory: row.get::<_, Option<i64>>(3)?.unwrap_or_default() as u64,
+                    virtual_memory: 0,
+                    start_time: 0,
+                    exe: String::new(),
+                    cmdline: String::new(),
+                    user: None,
+                    status: String::new(),
+                    cgroup: None,
+                    read_rate: 0.0,
+                    write_rate: 0.0,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        let mut disks = disks
+            .query_map([id], |row| {
+                Ok(DiskSample {
+                    mount: row.get(0)?,
+                    device: row.get::<_, Option<String>>(8)?.unwrap_or_default(),
+                    kind: row.get(1)?,
+                    read_bytes: row.get::<_, i64>(2)? as u64,
+                    written_bytes: row.get::<_, i64>(3)? as u64,
+                    io: DiskIo {
+                        read_rate: row.get::<_, Option<f64>>(9)?.unwrap_or_default(),
+                        write_rate: row.get::<_, Option<f64>>(10)?.unwrap_or_default(),
+                        read_iops: row.get(11)?,
+                        write_iops: row.get(12)?,
+                        read_latency_ms: row.get(13)?,
+                        write_latency_ms: row.get(14)?,
+                        queue_depth: row.get(15)?,
+                    },
+                    total_space: row.get::<_, Option<i64>>(4)?.unwrap_or_default() as u64,
+                    available_space: row.get::<_, Option<i64>>(5)?.unwrap_or_default() as u64,
+                    inodes_total: row.get::<_, Option<i64>>(6)?.map(|i| i as u64),
+                    inodes_free: row.get::<_, Option<i64>>(7)?.map(|i| i as u64),
+                    full_in: None,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        let temperatures = temperatures
+            .query_map([id], |row| {
+                Ok(TemperatureSample {
+                    label: row.get(0)?,
+                    current: row.get::<_, f64>(1)? as f32,
+                    max: row.get::<_, Option<f64>>(2)?.map(|t| t as f32),
+                    critical: row.get::<_, Option<f64>>(3)?.map(|t| t as f32),
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+
+        let cgroup = cgroup
+            .query_map([id], |row| {
+                Ok(CgroupSample {
+                    path: row.get(0)?,
+                    memory_current: row.get::<_, i64>(1)? as u64,
+                    memory_limit: row.get::<_, Option<i64>>(2)?.map(|l| l as u64),
+                    cpu_limit: row.get(3)?,
+                    cpu_usage: row.get(4)?,
+                    throttled_percent: row.get(5)?,
+                })
+            })?
+            .next()
+            .transpose()?;
+        let cgroups = cgroups
+            .query_map([id], |row| {
+                Ok(CgroupUsage {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    processes: row.get::<_, i64>(2)? as usize,
+                    cpu: row.get::<_, f64>(3)? as f32,
+                    memory: row.get::<_, i64>(4)? as u64,
+                    read_rate: row.get(5)?,
+                    write_rate: row.get(6)?,
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        let mut psi = Psi::default();
+        let rows = pressure.query_map([id], |row| {
+            let avg = PressureAvg {
+                avg10: row.get(2)?,
+                avg60: row.get(3)?,
+                avg300: row.get(4)?,
+            };
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, avg))
+        })?;
+        for row in rows {
+            let (resource, kind, avg) = row?;
+            let Some(slot) = psi.get_mut(&resource) else {
+                continue;
+            };
+            let entry = slot.get_or_insert_with(Pressure::default);
+            match kind.as_str() {
+                "full" => entry.full = Some(avg),
+                _ => entry.some = avg,
+            }
+        }
+
+        let Some(timestamp) = parse_timestamp(&summary.timestamp) else {
+            continue;
+        };
+        // El tiempo hasta llenarse no se guarda; se recalcula igual que en vivo
+        fill.update(timestamp, &mut disks);
+        samples.push(Sample {
+            timestamp,
+            cpu_total: summary.cpu_total as f32,
+            cpu_cores,
+            cpu_frequencies,
+            load_average: [
+                summary.load1.unwrap_or_default(),
+                summary.load5.unwrap_or_default(),
+                summary.load15.unwrap_or_default(),
+            ],
+            uptime: summary.uptime.unwrap_or_default() as u64,
+            memory_total: summary.memory_total as u64,
+            memory_used: summary.memory_used as u64,
+            memory_available: summary.memory_available.unwrap_or_default() as u64,
+            swap_total: summary.swap_total.unwrap_or_default() as u64,
+            swap_used: summary.swap_used.unwrap_or_default() as u64,
+            swap_in_rate: summary.swap_in_rate,
+            swap_out_rate: summary.swap_out_rate,
+            interfaces,
+            processes,
+            disks,
+            temperatures,
+            pressure: psi,
+            cgroup,
+            cgroups,
+        });
+    }
+    Ok(samples)
+}
@@ -0,0 +1,49 @@
+use rusqlite::{params, Connection, Result};
+
+use super::{parse_timestamp, TIMESTAMP_FORMAT};
+use crate::watchdog::{ProcessEvent, ProcessEventKind};
+
+pub fn save_process_event(conn: &Connection, event: &ProcessEvent) -> Result<()> {
+    conn.execute(
+        "INSERT INTO process_events (watch_id, kind, pid, old_pid, name, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            event.watch_id,
+            event.kind.as_str(),
+            event.pid,
+            event.old_pid,
+            event.name,
+            event.timestamp.format(TIMESTAMP_FORMAT).to_string()
+        ],
+    )?;
+    Ok(())
+}
+
+// Eventos desde `since`, del más viejo al más nuevo. Con `limit` se
+// devuelven solo los últimos.
+pub fn process_events_since(conn: &Connection, since: Option<&str>, limit: Option<usize>) -> Result<Vec<ProcessEvent>> {
+    let mut stmt = conn.prepare(
+        "SELECT watch_id, kind, pid, old_pid, name, timestamp
+         FROM process_events
+         WHERE ?1 IS NULL OR timestamp >= ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let limit = limit.map(|l| l as i64).unwrap_or(-1);
+    let mut events = stmt
+        .query_map(params![since, limit], |row| {
+            let kind: String = row.get(1)?;
+            let timestamp: String = row.get(5)?;
+            Ok(ProcessEvent {
+                watch_id: row.get(0)?,
+                kind: ProcessEventKind::parse(&kind).unwrap_or(ProcessEventKind::Exited),
+                pid: row.get(2)?,
+                old_pid: row.get(3)?,
+                name: row.get(4)?,
+                timestamp: parse_timestamp(&timestamp).unwrap_or_default(),
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    events.reverse();
+    Ok(events)
+}
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use rusqlite::{params, Connection, Result};
+
+use super::{parse_timestamp, TIMESTAMP_FORMAT};
+use crate::kill::{KillRecord, KillResult, KillSignal};
+
+pub fn save_process_kill(conn: &Connection, record: &KillRecord) -> Result<()> {
+    conn.execute(
+        "INSERT INTO process_kills (timestamp, pid, start_time, name, user, cmdline, signal, result, elapsed_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            record.timestamp.format(TIMESTAMP_FORMAT).to_string(),
+            record.pid,
+            record.start_time as i64,
+            record.name,
+            record.user,
+            record.cmdline,
+            record.signal.as_str(),
+            record.result.as_str(),
+            record.elapsed.as_millis() as i64
+        ],
+    )?;
+    Ok(())
+}
+
+// Señales desde `since`, de la más vieja a la más nueva. Con `limit` se
+// devuelven solo las últimas.
+pub fn process_kills_since(conn: &Connection, since: Option<&str>, limit: Option<usize>) -> Result<Vec<KillRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT timestamp, pid, start_time, name, user, cmdline, signal, result, elapsed_ms
+         FROM process_kills
+         WHERE ?1 IS NULL OR timestamp >= ?1
+         ORDER BY id DESC
+         LIMIT ?2",
+    )?;
+    let limit = limit.map(|l| l as i64).unwrap_or(-1);
+    let mut records = stmt
+        .query_map(params![since, limit], |row| {
+            let timestamp: String = row.get(0)?;
+            let signal: String = row.get(6)?;
+            let result: String = row.get(7)?;
+            Ok(KillRecord {
+                timestamp: parse_timestamp(&timestamp).unwrap_or_default(),
+                pid: row.get(1)?,
+                start_time: row.get::<_, i64>(2)? as u64,
+                name: row.get(3)?,
+                user: row.get(4)?,
+                cmdline: row.get(5)?,
+                signal: KillSignal::parse(&signal).unwrap_or(KillSignal::Kill),
+                result: KillResult::parse(&result).unwrap_or(KillResult::Failed),
+                elapsed: Duration::from_millis(row.get::<_, i64>(8)? as u64),
+                audited: true,
+            })
+        })?
+        .collect::<Result<Vec<_>>>()?;
+    records.reverse();
+    Ok(records)
+}
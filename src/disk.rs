@@ -0,0 +1,340 @@
+use std::collections::{HashMap, VecDeque};
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+use crate::collector::DiskSample;
+use crate::linux::diskstats::DiskStats;
+
+// Historial con el que se estima cuánto falta para que se llene cada disco
+const FILL_WINDOW: Duration = Duration::from_secs(30 * 60);
+// Con menos historial la estimación depende demasiado de un solo archivo
+const FILL_MIN_SPAN: Duration = Duration::from_secs(60);
+
+// (inodos totales, inodos libres). `None` si el sistema de archivos no tiene
+// un límite fijo de inodos (btrfs, vfat) o no se pudo consultar.
+#[cfg(unix)]
+pub fn inodes(mount: &str) -> Option<(u64, u64)> {
+    let path = std::ffi::CString::new(mount).ok()?;
+    let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` termina en NUL y `stat` tiene el tamaño que espera statvfs
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: statvfs devolvió 0, así que completó la estructura
+    let stat = unsafe { stat.assume_init() };
+    if stat.f_files == 0 {
+        return None;
+    }
+    // El tipo de los campos cambia según la plataforma
+    #[allow(clippy::unnecessary_cast)]
+    Some((stat.f_files as u64, stat.f_favail as u64))
+}
+
+#[cfg(not(unix))]
+pub fn inodes(_mount: &str) -> Option<(u64, u64)> {
+    None
+}
+
+// Nombre del dispositivo en /proc/diskstats: `/dev/mapper/raiz` -> `dm-0`.
+pub fn device_name(name: &OsStr) -> String {
+    let path = Path::new(name);
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.strip_prefix("/dev").unwrap_or(&path).to_string_lossy().into_owned()
+}
+
+// Actividad de un disco entre dos muestras. Las operaciones, la latencia y
+// la cola solo están en Linux.
+#[derive(Clone, Debug, Default)]
+pub struct DiskIo {
+    // Bytes por segundo
+    pub read_rate: f64,
+    pub write_rate: f64,
+    pub read_iops: Option<f64>,
+    pub write_iops: Option<f64>,
+    // Tiempo medio de cada operación; sin dato si no hubo operaciones
+    pub read_latency_ms: Option<f64>,
+    pub write_latency_ms: Option<f64>,
+    // Operaciones en cola en promedio
+    pub queue_depth: Option<f64>,
+}
+
+// Contadores acumulados de un disco en una muestra.
+#[derive(Clone, Copy)]
+pub struct Counters {
+    pub read_bytes: u64,
+    pub written_bytes: u64,
+    pub stats: Option<DiskStats>,
+}
+
+// Últimos contadores de cada dispositivo (o punto de montaje, si no tiene),
+// para convertirlos en tasas. Un dispositivo montado en varios lugares (bind
+// mounts) se cuenta una sola vez por muestra, en el primer punto de montaje,
+// así no se suma dos veces.
+#[derive(Default)]
+pub struct IoTracker {
+    previous: HashMap<String, (Instant, Counters)>,
+}
+
+impl IoTracker {
+    // Devuelve (bytes leídos, bytes escritos, tasas) desde la muestra anterior.
+    // La primera vez que aparece un disco no hay con qué comparar.
+    pub fn update(&mut self, device: &str, now: Instant, counters: Counters) -> (u64, u64, DiskIo) {
+        let Some((then, old)) = self.previous.insert(device.to_string(), (now, counters)) else {
+            return (0, 0, DiskIo::default());
+        };
+        // Otro punto de montaje del mismo dispositivo en esta muestra
+        if then == now {
+            return (0, 0, DiskIo::default());
+        }
+        let seconds = (now - then).as_secs_f64();
+        // Un contador que baja es un dispositivo que se volvió a conectar
+        let read = counters.read_bytes.saturating_sub(old.read_bytes);
+        let written = counters.written_bytes.saturating_sub(old.written_bytes);
+
+        let mut io = DiskIo {
+            read_rate: read as f64 / seconds,
+            write_rate: written as f64 / seconds,
+            ..DiskIo::default()
+        };
+        if let (Some(new), Some(old)) = (counters.stats, old.stats) {
+            let delta = |new: u64, old: u64| new.checked_sub(old).map(|d| d as f64);
+            let latency = |ms: Option<f64>, ops: Option<f64>| match (ms, ops) {
+                (Some(ms), Some(ops)) if ops > 0.0 => Some(ms / ops),
+                _ => None,
+            };
+            let (reads, writes) = (delta(new.reads, old.reads), delta(new.writes, old.writes));
+            io.read_iops = reads.map(|r| r / seconds);
+            io.write_iops = writes.map(|w| w / seconds);
+            io.read_latency_ms = latency(delta(new.read_ms, old.read_ms), reads);
+            io.write_latency_ms = latency(delta(new.write_ms, old.write_ms), writes);
+            io.queue_depth = delta(new.weighted_ms, old.weighted_ms).map(|ms| ms / (seconds * 1000.0));
+        }
+        (read, written, io)
+    }
+
+    pub fn retain(&mut self, devices: &[&str]) {
+        self.previous.retain(|device, _| devices.contains(&device.as_str()));
+    }
+}
+
+// Espacio libre reciente de cada punto de montaje.
+#[derive(Default)]
+pub struct FillTracker {
+    history: HashMap<String, VecDeque<(DateTime<Local>, u64)>>,
+}
+
+impl FillTracker {
+    // Completa `full_in` de cada disco con la tendencia de la última media hora.
+    pub fn update(&mut self, now: DateTime<Local>, disks: &mut [DiskSample]) {
+        self.history.retain(|mount, _| disks.iter().any(|d| d.mount == *mount));
+        for disk in disks.iter_mut().filter(|d| d.total_space > 0) {
+            let history = self.history.entry(disk.mount.clone()).or_default();
+            history.push_back((now, disk.available_space));
+            while history.front().is_some_and(|(t, _)| span(*t, now) > FILL_WINDOW) {
+                history.pop_front();
+            }
+            disk.full_in = full_in(history);
+        }
+    }
+}
+
+fn span(from: DateTime<Local>, to: DateTime<Local>) -> Duration {
+    (to - from).to_std().unwrap_or_default()
+}
+
+// Pendiente por mínimos cuadrados del espacio libre; `None` si no baja.
+fn full_in(history: &VecDeque<(DateTime<Local>, u64)>) -> Option<Duration> {
+    let (start, _) = *history.front()?;
+    let (end, available) = *history.back()?;
+    if span(start, end) < FILL_MIN_SPAN {
+        return None;
+    }
+
+    let points: Vec<(f64, f64)> = history
+        .iter()
+        .map(|(t, free)| (span(start, *t).as_secs_f64(), *free as f64))
+        .collect();
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_free = points.iter().map(|(_, f)| f).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|(t, f)| (t - mean_t) * (f - mean_free)).sum();
+    let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+    // Bytes por segundo; negativa si el disco se está llenando
+    let slope = covariance / variance;
+    if !slope.is_finite() || slope >= 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(available as f64 / -slope).ok()
+}
+
+// "3d 4h", "5h 20m" o "12m"
+pub fn format_eta(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration as TimeDelta, TimeZone};
+
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn counters(read_mb: u64, written_mb: u64, stats: Option<DiskStats>) -> Counters {
+        Counters { read_bytes: read_mb * MB, written_bytes: written_mb * MB, stats }
+    }
+
+    fn stats(reads: u64, read_ms: u64, writes: u64, write_ms: u64, weighted_ms: u64) -> Option<DiskStats> {
+        Some(DiskStats { reads, read_ms, writes, write_ms, weighted_ms })
+    }
+
+    #[test]
+    fn first_sample_has_no_rates() {
+        let mut tracker = IoTracker::default();
+        let (read, written, io) = tracker.update("sda1", Instant::now(), counters(100, 50, stats(10, 5, 10, 5, 5)));
+        assert_eq!((read, written), (0, 0));
+        assert_eq!((io.read_rate, io.write_rate, io.read_iops, io.queue_depth), (0.0, 0.0, None, None));
+    }
+
+    #[test]
+    fn rates_from_counter_deltas() {
+        let mut tracker = IoTracker::default();
+        let start = Instant::now();
+        tracker.update("sda1", start, counters(100, 50, stats(1000, 2000, 500, 5000, 10_000)));
+        let later = start + Duration::from_secs(2);
+        let (read, written, io) = tracker.update("sda1", later, counters(120, 54, stats(1200, 2600, 500, 5000, 13_000)));
+
+        assert_eq!((read, written), (20 * MB, 4 * MB));
+        assert_eq!((io.read_rate, io.write_rate), (10.0 * MB as f64, 2.0 * MB as f64));
+        assert_eq!((io.read_iops, io.write_iops), (Some(100.0), Some(0.0)));
+        // 600 ms en 200 lecturas; sin escrituras no hay latencia
+        assert_eq!((io.read_latency_ms, io.write_latency_ms), (Some(3.0), None));
+        // 3000 ms de espera acumulada en 2 s
+        assert_eq!(io.queue_depth, Some(1.5));
+    }
+
+    #[test]
+    fn counters_that_go_back_are_a_reconnected_device() {
+        let mut tracker = IoTracker::default();
+        let start = Instant::now();
+        tracker.update("sdb1", start, counters(100, 100, stats(1000, 1000, 1000, 1000, 1000)));
+        let (read, written, io) =
+            tracker.update("sdb1", start + Duration::from_secs(1), counters(5, 1, stats(10, 10, 10, 10, 10)));
+        assert_eq!((read, written), (0, 0));
+        assert_eq!((io.read_iops, io.write_latency_ms, io.queue_depth), (None, None, None));
+
+        // Sin /proc/diskstats solo hay bytes
+        let (_, _, io) = tracker.update("sdb1", start + Duration::from_secs(2), counters(7, 1, None));
+        assert_eq!((io.read_rate, io.read_iops), (2.0 * MB as f64, None));
+    }
+
+    #[test]
+    fn bind_mounts_count_once() {
+        let mut tracker = IoTracker::default();
+        let start = Instant::now();
+        let later = start + Duration::from_secs(1);
+        for now in [start, later] {
+            let total = if now == start { 0 } else { 10 };
+            let first = tracker.update("sda1", now, counters(total, 0, None));
+            let second = tracker.update("sda1", now, counters(total, 0, None));
+            assert_eq!((second.0, second.2.read_rate), (0, 0.0));
+            if now == later {
+                assert_eq!((first.0, first.2.read_rate), (10 * MB, 10.0 * MB as f64));
+            }
+        }
+
+        // Los dispositivos que ya no están se olvidan
+        tracker.retain(&["sdb1"]);
+        assert!(tracker.previous.is_empty());
+    }
+
+    fn disk(mount: &str, available_gb: u64) -> DiskSample {
+        DiskSample {
+            mount: mount.to_string(),
+            device: String::new(),
+            kind: String::new(),
+            read_bytes: 0,
+            written_bytes: 0,
+            io: DiskIo::default(),
+            total_space: 1000 << 30,
+            available_space: available_gb << 30,
+            inodes_total: None,
+            inodes_free: None,
+            full_in: None,
+        }
+    }
+
+    // Una muestra por minuto con el espacio libre de `/` en GB
+    fn fill(tracker: &mut FillTracker, minute: i64, available_gb: u64) -> Option<Duration> {
+        let start = Local.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut disks = vec![disk("/", available_gb)];
+        tracker.update(start + TimeDelta::minutes(minute), &mut disks);
+        disks[0].full_in
+    }
+
+    #[test]
+    fn flat_or_shrinking_usage_never_fills() {
+        let mut flat = FillTracker::default();
+        let mut freeing = FillTracker::default();
+        for minute in 0..10 {
+            assert_eq!(fill(&mut flat, minute, 500), None);
+            assert_eq!(fill(&mut freeing, minute, 500 + minute as u64), None);
+        }
+    }
+
+    #[test]
+    fn growing_usage_estimates_time_to_full() {
+        let mut tracker = FillTracker::default();
+        // Sin un minuto de historial no hay estimación
+        assert_eq!(fill(&mut tracker, 0, 100), None);
+        // Se pierde 1 GB por minuto: con 99 GB libres faltan 99 minutos
+        assert_eq!(fill(&mut tracker, 1, 99), Some(Duration::from_secs(99 * 60)));
+        assert_eq!(fill(&mut tracker, 2, 98), Some(Duration::from_secs(98 * 60)));
+
+        // Con ruido la pendiente sale de todos los puntos
+        let mut noisy = FillTracker::default();
+        for (minute, free) in [(0, 100), (1, 100), (2, 96), (3, 97)] {
+            fill(&mut noisy, minute, free);
+        }
+        let eta = fill(&mut noisy, 4, 92).unwrap().as_secs_f64() / 60.0;
+        // Pendiente de -1.9 GB por minuto
+        assert!((eta - 92.0 / 1.9).abs() < 1e-6, "{}", eta);
+    }
+
+    #[test]
+    fn old_history_is_dropped() {
+        let mut tracker = FillTracker::default();
+        // Media hora llenándose rápido y después estable
+        for minute in 0..=30 {
+            fill(&mut tracker, minute, 400 - 10 * minute as u64);
+        }
+        // La bajada de los minutos 15 a 30 todavía está en la ventana
+        assert!(fill(&mut tracker, 45, 100).is_some());
+        assert_eq!(tracker.history["/"].len(), 17);
+        // Media hora después del último cambio ya no
+        assert_eq!(fill(&mut tracker, 61, 100), None);
+        assert_eq!(tracker.history["/"].len(), 2);
+
+        // Un disco que desaparece pierde su historial
+        tracker.update(Local::now(), &mut []);
+        assert!(tracker.history.is_empty());
+    }
+
+    #[test]
+    fn eta_format() {
+        assert_eq!(format_eta(Duration::from_secs(12 * 60 + 59)), "12m");
+        assert_eq!(format_eta(Duration::from_secs(5 * 3600 + 20 * 60)), "5h 20m");
+        assert_eq!(format_eta(Duration::from_secs(3 * 86400 + 4 * 3600 + 59)), "3d 4h");
+    }
+}
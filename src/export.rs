@@ -0,0 +1,175 @@
+use std::io::{self, Write};
+
+use serde_json::{json, Number, Value};
+
+use crate::cli::ExportFormat;
+use crate::db::SampleSummary;
+
+pub fn write(out: &mut impl Write, format: ExportFormat, samples: &[SampleSummary]) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => write_csv(out, samples),
+        ExportFormat::Json => write_json(out, samples),
+    }
+}
+
+// Columnas que las muestras viejas no tienen: vacías en CSV y `null` en JSON.
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn decimal(value: Option<f64>) -> String {
+    optional(value.map(|v| format!("{:.2}", v)))
+}
+
+fn write_csv(out: &mut impl Write, samples: &[SampleSummary]) -> io::Result<()> {
+    writeln!(
+        out,
+        "id,timestamp,cpu_total,memory_total,memory_used,net_rx_mbps,net_tx_mbps,load1,load5,load15,uptime,\
+         memory_available,swap_total,swap_used,swap_in_rate,swap_out_rate"
+    )?;
+    for s in samples {
+        writeln!(
+            out,
+            "{},{},{:.2},{},{},{:.4},{:.4},{},{},{},{},{},{},{},{},{}",
+            s.id,
+            s.timestamp,
+            s.cpu_total,
+            s.memory_total,
+            s.memory_used,
+            s.net_rx_mbps,
+            s.net_tx_mbps,
+            decimal(s.load1),
+            decimal(s.load5),
+            decimal(s.load15),
+            optional(s.uptime),
+            optional(s.memory_available),
+            optional(s.swap_total),
+            optional(s.swap_used),
+            decimal(s.swap_in_rate),
+            decimal(s.swap_out_rate)
+        )?;
+    }
+    Ok(())
+}
+
+// Un objeto por línea, como `to_json(orient='records', lines=True)` en el script de Python.
+// Los valores que no son números finitos salen como `null`.
+fn write_json(out: &mut impl Write, samples: &[SampleSummary]) -> io::Result<()> {
+    for s in samples {
+        let row = json!({
+            "id": s.id,
+            "timestamp": s.timestamp,
+            "cpu_total": rounded(Some(s.cpu_total), 2),
+            "memory_total": s.memory_total,
+            "memory_used": s.memory_used,
+            "net_rx_mbps": rounded(Some(s.net_rx_mbps), 4),
+            "net_tx_mbps": rounded(Some(s.net_tx_mbps), 4),
+            "load1": rounded(s.load1, 2),
+            "load5": rounded(s.load5, 2),
+            "load15": rounded(s.load15, 2),
+            "uptime": s.uptime,
+            "memory_available": s.memory_available,
+            "swap_total": s.swap_total,
+            "swap_used": s.swap_used,
+            "swap_in_rate": rounded(s.swap_in_rate, 2),
+            "swap_out_rate": rounded(s.swap_out_rate, 2),
+        });
+        serde_json::to_writer(&mut *out, &row)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+// Con los mismos decimales que el CSV
+fn rounded(value: Option<f64>, decimals: i32) -> Value {
+    let scale = 10f64.powi(decimals);
+    value
+        .and_then(|v| Number::from_f64((v * scale).round() / scale))
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Una muestra con todas las columnas y otra de antes de que existieran
+    fn samples() -> Vec<SampleSummary> {
+        vec![
+            SampleSummary {
+                id: 1,
+                timestamp: "2024-01-01 12:00:00".to_string(),
+                cpu_total: 12.345,
+                memory_total: 16_000,
+                memory_used: 8_000,
+                net_rx_mbps: 1.23456,
+                net_tx_mbps: 0.5,
+                load1: Some(0.5),
+                load5: Some(0.25),
+                load15: Some(0.7),
+                uptime: Some(3600),
+                memory_available: Some(7_000),
+                swap_total: Some(2_000),
+                swap_used: Some(100),
+                swap_in_rate: Some(1.5),
+                swap_out_rate: Some(f64::NAN),
+            },
+            SampleSummary {
+                id: 2,
+                timestamp: "2024-01-01\t\"12:00:05\"".to_string(),
+                cpu_total: f64::INFINITY,
+                memory_total: 16_000,
+                memory_used: 8_100,
+                net_rx_mbps: 0.0,
+                net_tx_mbps: 0.0,
+                load1: None,
+                load5: None,
+                load15: None,
+                uptime: None,
+                memory_available: None,
+                swap_total: None,
+                swap_used: None,
+                swap_in_rate: None,
+                swap_out_rate: None,
+            },
+        ]
+    }
+
+    fn export(format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        write(&mut out, format, &samples()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_leaves_missing_columns_empty() {
+        let csv = export(ExportFormat::Csv);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,timestamp,cpu_total,"));
+        assert_eq!(
+            lines[1],
+            "1,2024-01-01 12:00:00,12.35,16000,8000,1.2346,0.5000,0.50,0.25,0.70,3600,7000,2000,100,1.50,NaN"
+        );
+        assert_eq!(lines[2], "2,2024-01-01\t\"12:00:05\",inf,16000,8100,0.0000,0.0000,,,,,,,,,");
+    }
+
+    #[test]
+    fn json_is_valid_with_nulls_for_missing_and_non_finite_values() {
+        let json = export(ExportFormat::Json);
+        let rows: Vec<Value> = json.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(rows[0]["cpu_total"], json!(12.35));
+        assert_eq!(rows[0]["net_rx_mbps"], json!(1.2346));
+        assert_eq!(rows[0]["load15"], json!(0.7));
+        assert_eq!(rows[0]["uptime"], json!(3600));
+        assert_eq!(rows[0]["swap_out_rate"], Value::Null);
+
+        assert_eq!(rows[1]["timestamp"], json!("2024-01-01\t\"12:00:05\""));
+        assert_eq!(rows[1]["cpu_total"], Value::Null);
+        for column in ["load1", "uptime", "memory_available", "swap_used", "swap_in_rate"] {
+            assert_eq!(rows[1][column], Value::Null, "{}", column);
+        }
+    }
+}
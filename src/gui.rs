@@ -1,38 +1,463 @@
+use chrono::{DateTime, Local};
 use eframe::egui;
 use eframe::App;
 use egui_plot::{Plot, Line, PlotPoints};
-use sysinfo::{System, Signal};
 
-use std::time::{Duration, Instant};
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::collector::{ProcessSample, Sample};
+use crate::disk;
+use crate::kill::{self, KillRecord, KillRequest, KillSignal, SIGNALS};
+use crate::linux::psi;
+use crate::sensors::TemperatureSample;
+use crate::table::{self, Column, Filter, COLUMNS};
+use crate::tree::{ProcessTree, SortKey};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Processes,
+    Table,
+    Tree,
+    Cgroups,
+}
+
+// Confirmación antes de enviar una señal al proceso elegido con «Matar».
+struct KillDialog {
+    process: ProcessSample,
+    signal: KillSignal,
+    // Solo con TERM
+    escalate: bool,
+    escalate_secs: u64,
+}
 
 pub struct MonitorApp {
-    sys: System,
-    last_update: Instant,
+    samples: Receiver<Arc<Sample>>,
     cpu_history: Vec<f32>,
     ram_history: Vec<f32>,
+    swap_history: Vec<f32>,
+    // Carga de 1, 5 y 15 minutos
+    load_history: [Vec<f64>; 3],
+    // Frecuencia promedio de los núcleos en MHz
+    frequency_history: Vec<f64>,
+    uptime: u64,
+    // `some avg10` de cpu, memory e io; sin PSI no se dibuja
+    pressure_history: [Vec<f64>; 3],
+    pressure_available: bool,
+    temperatures: Vec<TemperatureSample>,
     top_cpu: Vec<(i32, String, f32)>,
     top_ram: Vec<(i32, String, f64)>,
+    // Última muestra recibida, para las pestañas que muestran todos los procesos
+    latest: Option<Arc<Sample>>,
+    tab: Tab,
+    // Nodos abiertos del árbol por (pid, inicio), para que sigan abiertos
+    // entre muestras y no se hereden si el pid se reutiliza
+    expanded: HashSet<(u32, u64)>,
+    tree_sort: SortKey,
+    tree_descending: bool,
+    table_sort: Column,
+    table_descending: bool,
+    // Texto del cuadro de búsqueda y su filtro; se compila solo cuando cambia
+    filter_text: String,
+    filter: Result<Filter, String>,
     dark_mode: bool,
     selected_pid: Option<i32>,
+    confirm: Option<KillDialog>,
+    // Las señales se envían en otro hilo; `audit` lleva el resultado al
+    // hilo de monitoreo para guardarlo en la base
+    audit: Sender<KillRecord>,
+    kill_sender: Sender<KillRecord>,
+    kill_results: Receiver<KillRecord>,
+    kills_pending: usize,
+    // Últimos resultados, del más viejo al más nuevo
+    kill_log: Vec<KillRecord>,
     monitoring: bool, // <-- nuevo campo
 }
 
-impl Default for MonitorApp {
-    fn default() -> Self {
-        let mut sys = System::new_all();
-        sys.refresh_all();
+impl MonitorApp {
+    // La GUI no consulta el sistema: dibuja las muestras que publica el colector.
+    pub fn new(samples: Receiver<Arc<Sample>>, audit: Sender<KillRecord>) -> Self {
+        let (kill_sender, kill_results) = mpsc::channel();
         Self {
-            sys,
-            last_update: Instant::now(),
+            samples,
             cpu_history: vec![0.0; 60],
             ram_history: vec![0.0; 60],
+            swap_history: vec![0.0; 60],
+            load_history: [vec![0.0; 60], vec![0.0; 60], vec![0.0; 60]],
+            frequency_history: vec![0.0; 60],
+            uptime: 0,
+            pressure_history: [vec![0.0; 60], vec![0.0; 60], vec![0.0; 60]],
+            pressure_available: false,
+            temperatures: Vec::new(),
             top_cpu: Vec::new(),
             top_ram: Vec::new(),
+            latest: None,
+            tab: Tab::Processes,
+            expanded: HashSet::new(),
+            tree_sort: SortKey::Cpu,
+            tree_descending: true,
+            table_sort: Column::Cpu,
+            table_descending: true,
+            filter_text: String::new(),
+            filter: Filter::parse("").map_err(|e| e.to_string()),
             dark_mode: true,
             selected_pid: None,
+            confirm: None,
+            audit,
+            kill_sender,
+            kill_results,
+            kills_pending: 0,
+            kill_log: Vec::new(),
             monitoring: true, // <-- inicializa activado
         }
     }
+
+    fn processes_tab(&mut self, ui: &mut egui::Ui) {
+        ui.label("Top 5 Procesos por uso de CPU:");
+        ui.push_id("cpu_scroll", |ui| {
+            egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                egui::Grid::new("cpu_grid").striped(true).show(ui, |ui| {
+                    ui.label("PID");
+                    ui.label("Nombre");
+                    ui.label("CPU %");
+                    ui.end_row();
+                    for (pid, name, cpu) in &self.top_cpu {
+                        ui.label(pid.to_string());
+                        ui.label(name);
+                        ui.label(format!("{:.2}%", cpu));
+                        if ui.button("Matar").clicked() {
+                            self.selected_pid = Some(*pid);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+
+        ui.separator();
+
+        ui.label("Top 5 Procesos por uso de RAM:");
+        ui.push_id("ram_scroll", |ui| {
+            egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                egui::Grid::new("ram_grid").striped(true).show(ui, |ui| {
+                    ui.label("PID");
+                    ui.label("Nombre");
+                    ui.label("RAM (MB)");
+                    ui.end_row();
+                    for (pid, name, ram) in &self.top_ram {
+                        ui.label(pid.to_string());
+                        ui.label(name);
+                        ui.label(format!("{:.2} MB", ram));
+                        if ui.button("Matar").clicked() {
+                            self.selected_pid = Some(*pid);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        });
+    }
+
+    // Muestra el proceso y pide la señal. El pid se vuelve a comprobar con
+    // el inicio del proceso justo antes de enviarla.
+    fn kill_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.confirm else {
+            return;
+        };
+        let mut open = true;
+        let mut send = None;
+        egui::Window::new("Enviar señal")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let process = &dialog.process;
+                ui.label(format!("{} (pid {})", process.name, process.pid));
+                if let Some(user) = &process.user {
+                    ui.label(format!("Usuario: {}", user));
+                }
+                if !process.cmdline.is_empty() {
+                    ui.add(egui::Label::new(egui::RichText::new(&process.cmdline).monospace()).truncate(true))
+                        .on_hover_text(&process.cmdline);
+                }
+                ui.separator();
+
+                for signal in SIGNALS {
+                    ui.radio_value(&mut dialog.signal, signal, signal.label());
+                }
+                ui.add_enabled_ui(dialog.signal == KillSignal::Term, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut dialog.escalate, "Enviar KILL si sigue después de");
+                        ui.add(egui::DragValue::new(&mut dialog.escalate_secs).clamp_range(1..=60).suffix(" s"));
+                    });
+                });
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.button("Enviar").clicked() {
+                        send = Some(true);
+                    }
+                    if ui.button("Cancelar").clicked() {
+                        send = Some(false);
+                    }
+                });
+            });
+
+        match send {
+            Some(true) => {
+                let mut request = KillRequest::new(&dialog.process, dialog.signal);
+                if dialog.signal == KillSignal::Term && dialog.escalate {
+                    request.escalate_after = Some(Duration::from_secs(dialog.escalate_secs));
+                }
+                kill::spawn(request, self.audit.clone(), self.kill_sender.clone());
+                self.kills_pending += 1;
+                self.confirm = None;
+            }
+            Some(false) => self.confirm = None,
+            None if !open => self.confirm = None,
+            None => {}
+        }
+    }
+
+    // Todos los procesos. Solo se dibujan las filas visibles, así la tabla
+    // sigue fluida con miles de procesos.
+    fn table_tab(&mut self, ui: &mut egui::Ui) {
+        let Some(sample) = self.latest.clone() else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Buscar:");
+            let edit = egui::TextEdit::singleline(&mut self.filter_text)
+                .hint_text("nombre, user:usuario o /expresión/")
+                .desired_width(300.0);
+            if ui.add(edit).changed() {
+                self.filter = Filter::parse(&self.filter_text).map_err(|e| e.to_string());
+            }
+            if let Err(error) = &self.filter {
+                ui.colored_label(egui::Color32::RED, format!("Expresión regular inválida: {}", error));
+            }
+        });
+
+        let mut processes: Vec<_> = match &self.filter {
+            Ok(filter) => sample.processes.iter().filter(|p| filter.matches(p)).collect(),
+            Err(_) => sample.processes.iter().collect(),
+        };
+        table::sort(&mut processes, self.table_sort, self.table_descending);
+        ui.label(format!("{} de {} procesos", processes.len(), sample.processes.len()));
+
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::horizontal().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for column in COLUMNS {
+                    let label = match (self.table_sort == column, self.table_descending) {
+                        (true, true) => format!("{} ⏷", column.label()),
+                        (true, false) => format!("{} ⏶", column.label()),
+                        (false, _) => column.label().to_string(),
+                    };
+                    if ui.add_sized([column_width(column), row_height], egui::Button::new(label)).clicked() {
+                        if self.table_sort == column {
+                            self.table_descending = !self.table_descending;
+                        } else {
+                            self.table_sort = column;
+                            self.table_descending = column.descending_by_default();
+                        }
+                    }
+                }
+            });
+            ui.separator();
+
+            egui::ScrollArea::vertical().max_height(330.0).show_rows(ui, row_height, processes.len(), |ui, rows| {
+                for process in &processes[rows] {
+                    ui.horizontal(|ui| {
+                        for column in COLUMNS {
+                            let text = cell_text(column, process);
+                            let size = egui::vec2(column_width(column), row_height);
+                            let layout = egui::Layout::left_to_right(egui::Align::Center);
+                            ui.allocate_ui_with_layout(size, layout, |ui| {
+                                ui.set_min_size(size);
+                                let label = ui.add(egui::Label::new(&text).truncate(true));
+                                if column == Column::Cmdline && !text.is_empty() {
+                                    label.on_hover_text(&text);
+                                }
+                            });
+                        }
+                        if ui.button("Matar").clicked() {
+                            self.selected_pid = Some(process.pid as i32);
+                        }
+                    });
+                }
+            });
+        });
+    }
+
+    // Procesos según su padre. Un nodo cerrado muestra el uso sumado de
+    // todos sus descendientes; abierto, solo el propio.
+    fn tree_tab(&mut self, ui: &mut egui::Ui) {
+        let Some(sample) = self.latest.clone() else {
+            return;
+        };
+        let mut tree = ProcessTree::build(&sample.processes);
+        tree.sort(self.tree_sort, self.tree_descending);
+        let rows = tree.visible(|p| self.expanded.contains(&(p.pid, p.start_time)));
+
+        ui.horizontal(|ui| {
+            if ui.button("Expandir todo").clicked() {
+                self.expanded = tree
+                    .nodes
+                    .iter()
+                    .filter(|n| !n.children.is_empty())
+                    .map(|n| (n.process.pid, n.process.start_time))
+                    .collect();
+            }
+            if ui.button("Contraer todo").clicked() {
+                self.expanded.clear();
+            }
+        });
+
+        egui::ScrollArea::vertical().max_height(330.0).show(ui, |ui| {
+            egui::Grid::new("tree_grid").striped(true).show(ui, |ui| {
+                for key in [SortKey::Name, SortKey::Pid, SortKey::Cpu, SortKey::Memory] {
+                    let label = match (self.tree_sort == key, self.tree_descending) {
+                        (true, true) => format!("{} ⏷", key.label()),
+                        (true, false) => format!("{} ⏶", key.label()),
+                        (false, _) => key.label().to_string(),
+                    };
+                    if ui.button(label).clicked() {
+                        if self.tree_sort == key {
+                            self.tree_descending = !self.tree_descending;
+                        } else {
+                            self.tree_sort = key;
+                            self.tree_descending = key.descending_by_default();
+                        }
+                    }
+                }
+                ui.end_row();
+
+                for (i, depth) in rows {
+                    let node = &tree.nodes[i];
+                    let process = node.process;
+                    let id = (process.pid, process.start_time);
+                    let open = self.expanded.contains(&id);
+                    ui.horizontal(|ui| {
+                        ui.add_space(depth as f32 * 16.0);
+                        if node.children.is_empty() {
+                            ui.add_space(18.0);
+                        } else if ui.small_button(if open { "▼" } else { "▶" }).clicked() {
+                            if open {
+                                self.expanded.remove(&id);
+                            } else {
+                                self.expanded.insert(id);
+                            }
+                        }
+                        ui.label(&process.name);
+                    });
+                    ui.label(process.pid.to_string());
+                    if open || node.children.is_empty() {
+                        ui.label(format!("{:.2}%", process.cpu));
+                        ui.label(format!("{:.2} MB", process.memory as f64 / 1024.0 / 1024.0));
+                    } else {
+                        ui.label(format!("Σ {:.2}%", node.total_cpu));
+                        ui.label(format!("Σ {:.2} MB", node.total_memory as f64 / 1024.0 / 1024.0))
+                            .on_hover_text(format!("{} y {} descendientes", process.name, node.descendants));
+                    }
+                    if ui.button("Matar").clicked() {
+                        self.selected_pid = Some(process.pid as i32);
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+        // Los nodos de procesos que ya terminaron no se guardan para siempre
+        let alive: HashSet<(u32, u64)> = sample.processes.iter().map(|p| (p.pid, p.start_time)).collect();
+        self.expanded.retain(|id| alive.contains(id));
+    }
+
+    // Procesos agrupados por unidad de systemd o contenedor, con los más
+    // demandantes primero.
+    fn cgroups_tab(&mut self, ui: &mut egui::Ui) {
+        let Some(sample) = self.latest.clone() else {
+            return;
+        };
+        if sample.cgroups.is_empty() {
+            ui.label("Sin datos de cgroups (solo Linux con cgroup v2).");
+            return;
+        }
+        egui::ScrollArea::vertical().max_height(330.0).show(ui, |ui| {
+            for group in &sample.cgroups {
+                let header = format!(
+                    "{}  |  CPU {:.2}%  |  RAM {:.2} MB  |  E/S {:.2}/{:.2} MB/s  |  {} procesos",
+                    group.name,
+                    group.cpu,
+                    group.memory as f64 / 1024.0 / 1024.0,
+                    group.read_rate / 1024.0 / 1024.0,
+                    group.write_rate / 1024.0 / 1024.0,
+                    group.processes
+                );
+                egui::CollapsingHeader::new(header).id_source(&group.path).show(ui, |ui| {
+                    ui.label(&group.path);
+                    let mut processes: Vec<_> = sample
+                        .processes
+                        .iter()
+                        .filter(|p| p.cgroup.as_deref() == Some(group.path.as_str()))
+                        .collect();
+                    processes.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(b.memory.cmp(&a.memory)));
+                    egui::Grid::new(("cgroup_grid", &group.path)).striped(true).show(ui, |ui| {
+                        ui.label("PID");
+                        ui.label("Nombre");
+                        ui.label("CPU %");
+                        ui.label("RAM (MB)");
+                        ui.end_row();
+                        for process in processes {
+                            ui.label(process.pid.to_string());
+                            ui.label(&process.name);
+                            ui.label(format!("{:.2}%", process.cpu));
+                            ui.label(format!("{:.2} MB", process.memory as f64 / 1024.0 / 1024.0));
+                            if ui.button("Matar").clicked() {
+                                self.selected_pid = Some(process.pid as i32);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            }
+        });
+    }
+}
+
+fn column_width(column: Column) -> f32 {
+    match column {
+        Column::Pid => 60.0,
+        Column::Name | Column::User => 130.0,
+        Column::StartTime => 120.0,
+        Column::Cmdline => 400.0,
+        _ => 90.0,
+    }
+}
+
+fn cell_text(column: Column, process: &ProcessSample) -> String {
+    let mb = |bytes: u64| format!("{:.2}", bytes as f64 / 1024.0 / 1024.0);
+    let rate = |bytes: f64| format!("{:.2}", bytes / 1024.0 / 1024.0);
+    match column {
+        Column::Pid => process.pid.to_string(),
+        Column::Name => process.name.clone(),
+        Column::User => process.user.clone().unwrap_or_default(),
+        Column::Cpu => format!("{:.2}%", process.cpu),
+        Column::Memory => mb(process.memory),
+        Column::VirtualMemory => mb(process.virtual_memory),
+        Column::Read => rate(process.read_rate),
+        Column::Write => rate(process.write_rate),
+        Column::StartTime => DateTime::from_timestamp(process.start_time as i64, 0)
+            .filter(|_| process.start_time > 0)
+            .map(|t| t.with_timezone(&Local).format("%d/%m %H:%M:%S").to_string())
+            .unwrap_or_default(),
+        Column::Status => process.status.clone(),
+        Column::Cmdline => process.cmdline.clone(),
+    }
 }
 
 impl App for MonitorApp {
@@ -43,56 +468,63 @@ impl App for MonitorApp {
             ctx.set_visuals(egui::Visuals::light());
         }
 
-        if self.monitoring && self.last_update.elapsed() > Duration::from_secs(1) {
-            self.sys.refresh_all();
-
-            let cpus = self.sys.cpus();
-            let cpu_usage = cpus.iter().map(|c| c.cpu_usage()).sum::<f32>() / cpus.len() as f32;
-
-            let total_memory = self.sys.total_memory();
-            let used_memory = self.sys.used_memory();
-            let ram_usage = if total_memory > 0 {
-                (used_memory as f32 / total_memory as f32) * 100.0
-            } else {
-                0.0
-            };
+        // Vaciar el canal siempre; con el monitoreo detenido se descartan
+        while let Ok(sample) = self.samples.try_recv() {
+            if !self.monitoring {
+                continue;
+            }
 
-            self.cpu_history.push(cpu_usage);
+            self.cpu_history.push(sample.cpu_percent());
             self.cpu_history.remove(0);
-            self.ram_history.push(ram_usage);
+            self.ram_history.push(sample.memory_percent());
             self.ram_history.remove(0);
-
-            let num_cores = cpus.len() as f32;
-
-            let mut processes: Vec<_> = self.sys.processes().values().collect();
+            self.swap_history.push(sample.swap_percent().unwrap_or_default());
+            self.swap_history.remove(0);
+            for (history, load) in self.load_history.iter_mut().zip(sample.load_average) {
+                history.push(load);
+                history.remove(0);
+            }
+            let cores = sample.cpu_frequencies.len().max(1) as f64;
+            self.frequency_history
+                .push(sample.cpu_frequencies.iter().sum::<u64>() as f64 / cores);
+            self.frequency_history.remove(0);
+            self.uptime = sample.uptime;
+            self.pressure_available = !sample.pressure.is_empty();
+            for (history, resource) in self.pressure_history.iter_mut().zip(psi::RESOURCES) {
+                history.push(sample.pressure.get(resource).map(|p| p.some.avg10).unwrap_or_default());
+                history.remove(0);
+            }
+            self.temperatures = sample.temperatures.clone();
 
             // Top 5 CPU procesos normalizados por núcleos
-            processes.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap());
-            self.top_cpu = processes.iter()
-                .take(5)
-                .map(|p| {
-                    let cpu_pct = p.cpu_usage() / num_cores;
-                    (p.pid().as_u32() as i32, p.name().to_string_lossy().to_string(), cpu_pct)
-                })
+            self.top_cpu = sample
+                .top_cpu(5)
+                .iter()
+                .map(|p| (p.pid as i32, p.name.clone(), p.cpu))
                 .collect();
 
-            // Top 5 RAM en MB, y limitar valores razonables
-            processes.sort_by(|a, b| b.memory().cmp(&a.memory()));
-            self.top_ram = processes.iter()
-                .take(5)
-                .map(|p| {
-                    let mem_mb = p.memory() as f64 / 1024.0 / 1024.0;
-                    (p.pid().as_u32() as i32, p.name().to_string_lossy().to_string(), mem_mb)
-                })
+            // Top 5 RAM en MB
+            self.top_ram = sample
+                .top_memory(5)
+                .iter()
+                .map(|p| (p.pid as i32, p.name.clone(), p.memory as f64 / 1024.0 / 1024.0))
                 .collect();
 
+            self.latest = Some(sample);
+        }
 
-            self.last_update = Instant::now();
+        while let Ok(record) = self.kill_results.try_recv() {
+            self.kills_pending = self.kills_pending.saturating_sub(1);
+            self.kill_log.push(record);
+            if self.kill_log.len() > 5 {
+                self.kill_log.remove(0);
+            }
         }
 
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("Monitor de Sistema");
+                ui.label(format!("Encendido hace {}", disk::format_eta(Duration::from_secs(self.uptime))));
                 if ui.button(if self.monitoring { "Detener Monitoreo" } else { "Iniciar Monitoreo" }).clicked() {
                     self.monitoring = !self.monitoring;
                 }
@@ -119,7 +551,11 @@ impl App for MonitorApp {
                 });
 
                 ui.vertical(|ui| {
-                    ui.label(format!("RAM: {:.2}%", self.ram_history.last().unwrap()));
+                    ui.label(format!(
+                        "RAM: {:.2}% | Swap: {:.2}%",
+                        self.ram_history.last().unwrap(),
+                        self.swap_history.last().unwrap()
+                    ));
                     Plot::new("ram_plot")
                         .view_aspect(2.0)
                         .height(250.0)
@@ -129,63 +565,173 @@ impl App for MonitorApp {
                             ))
                             .color(egui::Color32::LIGHT_GREEN)
                             .name("RAM %"));
+                            plot_ui.line(Line::new(PlotPoints::from_iter(
+                                self.swap_history.iter().enumerate().map(|(i, v)| [i as f64, *v as f64]),
+                            ))
+                            .color(egui::Color32::KHAKI)
+                            .name("Swap %"));
                         });
                 });
             });
 
-            ui.separator();
-
-            ui.label("Top 5 Procesos por uso de CPU:");
-            ui.push_id("cpu_scroll", |ui| {
-                egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                    egui::Grid::new("cpu_grid").striped(true).show(ui, |ui| {
-                        ui.label("PID");
-                        ui.label("Nombre");
-                        ui.label("CPU %");
-                        ui.end_row();
-                        for (pid, name, cpu) in &self.top_cpu {
-                            ui.label(pid.to_string());
-                            ui.label(name);
-                            ui.label(format!("{:.2}%", cpu));
-                            if ui.button("Matar").clicked() {
-                                self.selected_pid = Some(*pid);
+            ui.horizontal(|ui| {
+                ui.vertical(|ui| {
+                    let [one, five, fifteen] = &self.load_history;
+                    ui.label(format!(
+                        "Carga: {:.2} {:.2} {:.2}",
+                        one.last().unwrap(),
+                        five.last().unwrap(),
+                        fifteen.last().unwrap()
+                    ));
+                    Plot::new("load_plot")
+                        .view_aspect(2.0)
+                        .height(150.0)
+                        .show(ui, |plot_ui| {
+                            let series = [
+                                (one, egui::Color32::GOLD, "1 min"),
+                                (five, egui::Color32::from_rgb(255, 140, 0), "5 min"),
+                                (fifteen, egui::Color32::LIGHT_RED, "15 min"),
+                            ];
+                            for (history, color, name) in series {
+                                plot_ui.line(Line::new(PlotPoints::from_iter(
+                                    history.iter().enumerate().map(|(i, v)| [i as f64, *v]),
+                                ))
+                                .color(color)
+                                .name(name));
                             }
-                            ui.end_row();
-                        }
-                    });
+                        });
+                });
+
+                ui.vertical(|ui| {
+                    ui.label(format!("Frecuencia: {:.0} MHz", self.frequency_history.last().unwrap()));
+                    Plot::new("frequency_plot")
+                        .view_aspect(2.0)
+                        .height(150.0)
+                        .show(ui, |plot_ui| {
+                            plot_ui.line(Line::new(PlotPoints::from_iter(
+                                self.frequency_history.iter().enumerate().map(|(i, v)| [i as f64, *v]),
+                            ))
+                            .color(egui::Color32::LIGHT_YELLOW)
+                            .name("MHz"));
+                        });
                 });
+
+                if self.pressure_available {
+                    ui.vertical(|ui| {
+                        ui.label("Presión (PSI some, 10 s)");
+                        Plot::new("pressure_plot")
+                            .view_aspect(2.0)
+                            .height(150.0)
+                            .show(ui, |plot_ui| {
+                                let colors = [egui::Color32::LIGHT_BLUE, egui::Color32::LIGHT_GREEN, egui::Color32::LIGHT_RED];
+                                for ((history, resource), color) in
+                                    self.pressure_history.iter().zip(psi::RESOURCES).zip(colors)
+                                {
+                                    plot_ui.line(Line::new(PlotPoints::from_iter(
+                                        history.iter().enumerate().map(|(i, v)| [i as f64, *v]),
+                                    ))
+                                    .color(color)
+                                    .name(format!("{} %", resource)));
+                                }
+                            });
+                    });
+                }
             });
 
             ui.separator();
 
-            ui.label("Top 5 Procesos por uso de RAM:");
-            ui.push_id("ram_scroll", |ui| {
-                egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
-                    egui::Grid::new("ram_grid").striped(true).show(ui, |ui| {
-                        ui.label("PID");
-                        ui.label("Nombre");
-                        ui.label("RAM (MB)");
+            ui.collapsing(format!("Temperaturas ({} sensores)", self.temperatures.len()), |ui| {
+                if self.temperatures.is_empty() {
+                    ui.label("No se encontraron sensores de temperatura.");
+                    return;
+                }
+                egui::Grid::new("temperature_grid").striped(true).show(ui, |ui| {
+                    ui.label("Sensor");
+                    ui.label("Actual");
+                    ui.label("Máxima");
+                    ui.label("Crítica");
+                    ui.end_row();
+                    let celsius = |t: Option<f32>| t.map(|t| format!("{:.1} °C", t)).unwrap_or_else(|| "-".to_string());
+                    for sensor in &self.temperatures {
+                        // En rojo a partir del 90% de la crítica, en naranja desde el 75%
+                        let color = match sensor.critical_percent() {
+                            Some(p) if p >= 90.0 => egui::Color32::LIGHT_RED,
+                            Some(p) if p >= 75.0 => egui::Color32::from_rgb(255, 165, 0),
+                            _ => ui.visuals().text_color(),
+                        };
+                        ui.label(&sensor.label);
+                        ui.colored_label(color, celsius(Some(sensor.current)));
+                        ui.label(celsius(sensor.max));
+                        ui.label(celsius(sensor.critical));
                         ui.end_row();
-                        for (pid, name, ram) in &self.top_ram {
-                            ui.label(pid.to_string());
-                            ui.label(name);
-                            ui.label(format!("{:.2} MB", ram));
-                            if ui.button("Matar").clicked() {
-                                self.selected_pid = Some(*pid);
-                            }
-                            ui.end_row();
-                        }
-                    });
+                    }
                 });
             });
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.tab, Tab::Processes, "Procesos");
+                ui.selectable_value(&mut self.tab, Tab::Table, "Todos");
+                ui.selectable_value(&mut self.tab, Tab::Tree, "Árbol");
+                ui.selectable_value(&mut self.tab, Tab::Cgroups, "Cgroups");
+            });
+            ui.separator();
+
+            match self.tab {
+                Tab::Processes => self.processes_tab(ui),
+                Tab::Table => self.table_tab(ui),
+                Tab::Tree => self.tree_tab(ui),
+                Tab::Cgroups => self.cgroups_tab(ui),
+            }
+
+            if self.kills_pending > 0 || !self.kill_log.is_empty() {
+                ui.separator();
+            }
+            for record in &self.kill_log {
+                let text = format!(
+                    "{}  {} a {} ({}): {}",
+                    record.timestamp.format("%H:%M:%S"),
+                    record.signal.as_str(),
+                    record.name,
+                    record.pid,
+                    record.result.label()
+                );
+                let text = if record.audited {
+                    text
+                } else {
+                    format!("{} (no se pudo registrar: el monitoreo está detenido)", text)
+                };
+                let color = if record.result.is_error() || !record.audited {
+                    egui::Color32::RED
+                } else {
+                    egui::Color32::GREEN
+                };
+                ui.colored_label(color, text);
+            }
+            if self.kills_pending > 0 {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Esperando a que el proceso termine...");
+                });
+            }
         });
 
-        if let Some(pid) = self.selected_pid {
-            if let Some(process) = self.sys.process(sysinfo::Pid::from(pid as usize)) {
-                process.kill_with(Signal::Kill);
+        if let Some(pid) = self.selected_pid.take() {
+            let process = self
+                .latest
+                .as_ref()
+                .and_then(|sample| sample.processes.iter().find(|p| p.pid as i32 == pid));
+            if let Some(process) = process {
+                self.confirm = Some(KillDialog {
+                    process: process.clone(),
+                    signal: KillSignal::Term,
+                    escalate: true,
+                    escalate_secs: 5,
+                });
             }
-            self.selected_pid = None;
         }
+        self.kill_dialog(ctx);
 
         ctx.request_repaint_after(Duration::from_millis(100));
     }
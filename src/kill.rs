@@ -0,0 +1,356 @@
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use sysinfo::{Pid, ProcessStatus, ProcessesToUpdate, Signal, System};
+
+use crate::collector::ProcessSample;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillSignal {
+    Term,
+    Int,
+    Hup,
+    Stop,
+    Cont,
+    Kill,
+}
+
+pub const SIGNALS: [KillSignal; 6] = [
+    KillSignal::Term,
+    KillSignal::Int,
+    KillSignal::Hup,
+    KillSignal::Stop,
+    KillSignal::Cont,
+    KillSignal::Kill,
+];
+
+impl KillSignal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KillSignal::Term => "TERM",
+            KillSignal::Int => "INT",
+            KillSignal::Hup => "HUP",
+            KillSignal::Stop => "STOP",
+            KillSignal::Cont => "CONT",
+            KillSignal::Kill => "KILL",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        SIGNALS.into_iter().find(|s| s.as_str() == text)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KillSignal::Term => "TERM (pedir que termine)",
+            KillSignal::Int => "INT (como Ctrl+C)",
+            KillSignal::Hup => "HUP (recargar o colgar)",
+            KillSignal::Stop => "STOP (pausar)",
+            KillSignal::Cont => "CONT (reanudar)",
+            KillSignal::Kill => "KILL (forzar, sin limpieza)",
+        }
+    }
+
+    fn signal(self) -> Signal {
+        match self {
+            KillSignal::Term => Signal::Term,
+            KillSignal::Int => Signal::Interrupt,
+            KillSignal::Hup => Signal::Hangup,
+            KillSignal::Stop => Signal::Stop,
+            KillSignal::Cont => Signal::Continue,
+            KillSignal::Kill => Signal::Kill,
+        }
+    }
+
+    // Las que deberían terminar el proceso; con las demás no se espera
+    fn terminates(self) -> bool {
+        matches!(self, KillSignal::Term | KillSignal::Int | KillSignal::Kill)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillResult {
+    // La señal llegó (STOP, CONT y HUP no se esperan)
+    Sent,
+    Exited,
+    // No terminó con TERM y se envió KILL
+    Escalated,
+    // Sigue en ejecución después de la espera
+    Survived,
+    NotFound,
+    // El pid ya es de otro proceso; no se envió nada
+    PidReused,
+    // Sin permiso, por lo general
+    Failed,
+    // La plataforma no tiene esa señal
+    Unsupported,
+}
+
+impl KillResult {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            KillResult::Sent => "sent",
+            KillResult::Exited => "exited",
+            KillResult::Escalated => "escalated",
+            KillResult::Survived => "survived",
+            KillResult::NotFound => "not_found",
+            KillResult::PidReused => "pid_reused",
+            KillResult::Failed => "failed",
+            KillResult::Unsupported => "unsupported",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        [
+            KillResult::Sent,
+            KillResult::Exited,
+            KillResult::Escalated,
+            KillResult::Survived,
+            KillResult::NotFound,
+            KillResult::PidReused,
+            KillResult::Failed,
+            KillResult::Unsupported,
+        ]
+        .into_iter()
+        .find(|r| r.as_str() == text)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KillResult::Sent => "señal enviada",
+            KillResult::Exited => "terminado",
+            KillResult::Escalated => "terminado con KILL",
+            KillResult::Survived => "sigue en ejecución",
+            KillResult::NotFound => "ya no existe",
+            KillResult::PidReused => "el pid es de otro proceso",
+            KillResult::Failed => "sin permiso",
+            KillResult::Unsupported => "señal no disponible",
+        }
+    }
+
+    pub fn is_error(self) -> bool {
+        !matches!(self, KillResult::Sent | KillResult::Exited | KillResult::Escalated)
+    }
+}
+
+// Proceso a señalar tal como se vio en la muestra: el inicio evita
+// señalar a otro proceso que haya recibido el mismo pid.
+#[derive(Clone, Debug)]
+pub struct KillRequest {
+    pub pid: u32,
+    pub start_time: u64,
+    pub name: String,
+    pub user: Option<String>,
+    pub cmdline: String,
+    pub signal: KillSignal,
+    // Solo con TERM: espera antes de enviar KILL
+    pub escalate_after: Option<Duration>,
+}
+
+impl KillRequest {
+    pub fn new(process: &ProcessSample, signal: KillSignal) -> Self {
+        KillRequest {
+            pid: process.pid,
+            start_time: process.start_time,
+            name: process.name.clone(),
+            user: process.user.clone(),
+            cmdline: process.cmdline.clone(),
+            signal,
+            escalate_after: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct KillRecord {
+    pub timestamp: DateTime<Local>,
+    pub pid: u32,
+    pub start_time: u64,
+    pub name: String,
+    pub user: Option<String>,
+    pub cmdline: String,
+    pub signal: KillSignal,
+    pub result: KillResult,
+    // Desde que se envió la señal hasta el resultado
+    pub elapsed: Duration,
+    // Si llegó al hilo que lo guarda en la base
+    pub audited: bool,
+}
+
+// Cada cuánto se revisa si el proceso terminó
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Espera después de TERM, INT o KILL sin escalado
+const EXIT_WAIT: Duration = Duration::from_secs(2);
+
+// Envía la señal en otro hilo. El resultado va por `audit` al hilo de
+// monitoreo, que lo guarda en la tabla `process_kills`, y por `results` a
+// la ventana. Si el monitoreo ya se detuvo el resultado se marca como no
+// registrado.
+pub fn spawn(request: KillRequest, audit: Sender<KillRecord>, results: Sender<KillRecord>) {
+    thread::spawn(move || {
+        let mut record = run(&request);
+        record.audited = audit.send(record.clone()).is_ok();
+        let _ = results.send(record);
+    });
+}
+
+fn run(request: &KillRequest) -> KillRecord {
+    let timestamp = Local::now();
+    let started = Instant::now();
+    let mut system = System::new();
+    let result = send(&mut system, request);
+    KillRecord {
+        timestamp,
+        pid: request.pid,
+        start_time: request.start_time,
+        name: request.name.clone(),
+        user: request.user.clone(),
+        cmdline: request.cmdline.clone(),
+        signal: request.signal,
+        result,
+        elapsed: started.elapsed(),
+        audited: false,
+    }
+}
+
+fn send(system: &mut System, request: &KillRequest) -> KillResult {
+    let result = signal(system, request, request.signal);
+    if result != KillResult::Sent || !request.signal.terminates() {
+        return result;
+    }
+
+    let escalate = request.escalate_after.filter(|_| request.signal == KillSignal::Term);
+    if wait_exit(system, request, escalate.unwrap_or(EXIT_WAIT)) {
+        return KillResult::Exited;
+    }
+    if escalate.is_none() {
+        return KillResult::Survived;
+    }
+    match signal(system, request, KillSignal::Kill) {
+        KillResult::Sent if wait_exit(system, request, EXIT_WAIT) => KillResult::Escalated,
+        KillResult::Sent => KillResult::Survived,
+        // Terminó justo antes del KILL
+        KillResult::NotFound | KillResult::PidReused => KillResult::Exited,
+        other => other,
+    }
+}
+
+// Vuelve a leer el proceso antes de cada señal: si ya no está o el pid es
+// de un proceso con otro inicio, no se envía nada.
+fn signal(system: &mut System, request: &KillRequest, signal: KillSignal) -> KillResult {
+    let pid = Pid::from_u32(request.pid);
+    system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    let Some(process) = system.process(pid).filter(|p| p.status() != ProcessStatus::Zombie) else {
+        return KillResult::NotFound;
+    };
+    if process.start_time() != request.start_time {
+        return KillResult::PidReused;
+    }
+    match process.kill_with(signal.signal()) {
+        Some(true) => KillResult::Sent,
+        Some(false) => KillResult::Failed,
+        None => KillResult::Unsupported,
+    }
+}
+
+// Un zombi ya terminó aunque su padre todavía no lo haya recogido
+fn wait_exit(system: &mut System, request: &KillRequest, timeout: Duration) -> bool {
+    let pid = Pid::from_u32(request.pid);
+    let deadline = Instant::now() + timeout;
+    loop {
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let alive = system
+            .process(pid)
+            .is_some_and(|p| p.start_time() == request.start_time && p.status() != ProcessStatus::Zombie);
+        if !alive {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::process::{Child, Command};
+
+    fn request(pid: u32, start_time: u64, signal: KillSignal) -> KillRequest {
+        KillRequest {
+            pid,
+            start_time,
+            name: "sleep".to_string(),
+            user: None,
+            cmdline: "sleep 30".to_string(),
+            signal,
+            escalate_after: None,
+        }
+    }
+
+    // Un `sleep` hijo del test y su inicio según sysinfo
+    #[cfg(unix)]
+    fn sleeper() -> (Child, u64) {
+        let child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = Pid::from_u32(child.id());
+        let mut system = System::new();
+        system.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+        let start_time = system.process(pid).unwrap().start_time();
+        (child, start_time)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn term_ends_the_process() {
+        let (mut child, start_time) = sleeper();
+        let record = run(&request(child.id(), start_time, KillSignal::Term));
+        assert_eq!(record.result, KillResult::Exited);
+        assert_eq!(record.pid, child.id());
+        assert!(!record.audited);
+        assert!(!child.wait().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn other_start_time_is_not_signalled() {
+        let (mut child, start_time) = sleeper();
+        let record = run(&request(child.id(), start_time + 1, KillSignal::Kill));
+        assert_eq!(record.result, KillResult::PidReused);
+        assert!(child.try_wait().unwrap().is_none());
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn missing_pid_is_not_found() {
+        // Mayor que el pid_max más alto de Linux (2^22)
+        let record = run(&request(4_000_000_000, 0, KillSignal::Term));
+        assert_eq!(record.result, KillResult::NotFound);
+        assert!(record.result.is_error());
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for result in [
+            KillResult::Sent,
+            KillResult::Exited,
+            KillResult::Escalated,
+            KillResult::Survived,
+            KillResult::NotFound,
+            KillResult::PidReused,
+            KillResult::Failed,
+            KillResult::Unsupported,
+        ] {
+            assert_eq!(KillResult::parse(result.as_str()), Some(result));
+        }
+        for signal in SIGNALS {
+            assert_eq!(KillSignal::parse(signal.as_str()), Some(signal));
+        }
+        assert_eq!(KillResult::parse("Exited"), None);
+        assert_eq!(KillSignal::parse("SIGTERM"), None);
+    }
+}
@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::collector::ProcessSample;
+
+// Contadores acumulados de cpu.stat.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CpuStat {
+    pub usage_usec: u64,
+    // Solo con el controlador `cpu` habilitado
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+}
+
+// Un cgroup v2 con sus límites efectivos: el menor entre el propio y los de
+// sus ancestros, que también se aplican.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cgroup {
+    // Ruta dentro de la jerarquía, ej. `/system.slice/metricas.service`
+    pub path: String,
+    pub memory_current: Option<u64>,
+    // `None` si ningún nivel tiene `memory.max`
+    pub memory_max: Option<u64>,
+    // Núcleos según `cpu.max` (cuota / período)
+    pub cpu_max: Option<f64>,
+    pub cpu: CpuStat,
+}
+
+fn mount(root: &Path) -> PathBuf {
+    root.join("sys/fs/cgroup")
+}
+
+// Ruta del cgroup v2 de este proceso. `None` con cgroup v1 o sin cgroups,
+// como fuera de Linux. En modo híbrido la jerarquía v2 no tiene
+// controladores, así que tampoco cuenta.
+pub fn current(root: &Path) -> Option<String> {
+    if !mount(root).join("cgroup.controllers").is_file() {
+        return None;
+    }
+    let text = fs::read_to_string(root.join("proc/self/cgroup")).ok()?;
+    text.lines().find_map(|line| line.strip_prefix("0::")).map(str::to_string)
+}
+
+pub fn read(root: &Path, path: &str) -> Option<Cgroup> {
+    let base = mount(root);
+    let dir = base.join(path.trim_start_matches('/'));
+    if !dir.is_dir() {
+        return None;
+    }
+
+    // Del cgroup hacia la raíz de la jerarquía
+    let levels: Vec<PathBuf> = dir.ancestors().take_while(|d| d.starts_with(&base)).map(Path::to_path_buf).collect();
+    let memory_max = levels.iter().filter_map(|d| read_number(&d.join("memory.max"))).min();
+    let cpu_max = levels
+        .iter()
+        .filter_map(|d| read_cpu_max(&d.join("cpu.max")))
+        .min_by(f64::total_cmp);
+
+    Some(Cgroup {
+        path: path.to_string(),
+        memory_current: read_number(&dir.join("memory.current")),
+        memory_max,
+        cpu_max,
+        cpu: read_cpu_stat(&dir.join("cpu.stat")),
+    })
+}
+
+// `max` (sin límite) se lee como `None`
+fn read_number(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// "cuota período" en microsegundos, o "max período"
+fn read_cpu_max(path: &Path) -> Option<f64> {
+    let text = fs::read_to_string(path).ok()?;
+    let mut fields = text.split_whitespace();
+    let quota: f64 = fields.next()?.parse().ok()?;
+    let period: f64 = fields.next()?.parse().ok()?;
+    (period > 0.0).then(|| quota / period)
+}
+
+// Líneas "clave valor"; las que faltan quedan en 0
+fn read_cpu_stat(path: &Path) -> CpuStat {
+    let text = fs::read_to_string(path).unwrap_or_default();
+    let value = |name: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once(' '))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.trim().parse().ok())
+            .unwrap_or_default()
+    };
+    CpuStat {
+        usage_usec: value("usage_usec"),
+        nr_periods: value("nr_periods"),
+        nr_throttled: value("nr_throttled"),
+        throttled_usec: value("throttled_usec"),
+    }
+}
+
+// Cgroup v2 de un proceso; `None` si ya terminó o no hay jerarquía v2.
+#[cfg(target_os = "linux")]
+pub fn of_process(root: &Path, pid: u32) -> Option<String> {
+    let text = fs::read_to_string(root.join(format!("proc/{}/cgroup", pid))).ok()?;
+    text.lines().find_map(|line| line.strip_prefix("0::")).map(str::to_string)
+}
+
+// Sin cgroups no se lee nada por cada proceso
+#[cfg(not(target_os = "linux"))]
+pub fn of_process(_root: &Path, _pid: u32) -> Option<String> {
+    None
+}
+
+// Nombre legible de un cgroup: la unidad de systemd más profunda
+// (`nginx.service`) o el contenedor con su id abreviado (`docker 1a2b3c4d5e6f`).
+pub fn unit_name(path: &str) -> String {
+    let short = |id: &str| id.chars().take(12).collect::<String>();
+    let is_id = |id: &str| id.len() >= 12 && id.chars().all(|c| c.is_ascii_hexdigit());
+    for part in path.rsplit('/').filter(|p| !p.is_empty()) {
+        let scope = part.strip_suffix(".scope").unwrap_or(part);
+        for (prefix, runtime) in [("docker-", "docker"), ("libpod-", "podman"), ("cri-containerd-", "containerd"), ("crio-", "cri-o")] {
+            if let Some(id) = scope.strip_prefix(prefix).filter(|id| is_id(id)) {
+                return format!("{} {}", runtime, short(id));
+            }
+        }
+        if is_id(part) && path.contains("/docker/") {
+            return format!("docker {}", short(part));
+        }
+        if part.ends_with(".service") || part.ends_with(".scope") {
+            return part.to_string();
+        }
+    }
+    // Sin unidad: el último componente, como `user.slice`, o la raíz
+    match path.rsplit('/').find(|p| !p.is_empty()) {
+        Some(last) => last.to_string(),
+        None => "/".to_string(),
+    }
+}
+
+// Cgroup v2 en el que corre el monitor (un contenedor o una unidad de
+// systemd) y su uso respecto de los límites que realmente se le aplican.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupSample {
+    pub path: String,
+    pub memory_current: u64,
+    // Solo si es menor que la memoria de la máquina
+    pub memory_limit: Option<u64>,
+    // Núcleos; solo si son menos que los de la máquina
+    pub cpu_limit: Option<f64>,
+    // Núcleos usados desde la muestra anterior
+    pub cpu_usage: Option<f64>,
+    // Porcentaje de los períodos de `cpu.max` en los que se frenó al cgroup
+    pub throttled_percent: Option<f64>,
+}
+
+impl CgroupSample {
+    pub fn memory_percent(&self) -> Option<f64> {
+        let limit = self.memory_limit.filter(|l| *l > 0)?;
+        Some(self.memory_current as f64 / limit as f64 * 100.0)
+    }
+
+    pub fn cpu_percent(&self) -> Option<f64> {
+        let limit = self.cpu_limit.filter(|l| *l > 0.0)?;
+        Some(self.cpu_usage? / limit * 100.0)
+    }
+}
+
+pub struct CgroupTracker {
+    root: PathBuf,
+    // Contadores de la muestra anterior, si el cgroup era el mismo
+    previous: Option<(String, Instant, CpuStat)>,
+}
+
+impl CgroupTracker {
+    // `root` es la raíz desde la que se leen /proc y /sys; en vivo, `linux::ROOT`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        CgroupTracker {
+            root: root.into(),
+            previous: None,
+        }
+    }
+
+    // `None` fuera de un cgroup v2. Los límites mayores que la máquina
+    // (o la memoria y los núcleos del host) no cuentan como límite.
+    pub fn update(&mut self, now: Instant, host_memory: u64, host_cores: usize) -> Option<CgroupSample> {
+        let Some(group) = current(&self.root).and_then(|path| read(&self.root, &path)) else {
+            self.previous = None;
+            return None;
+        };
+
+        let previous = self.previous.replace((group.path.clone(), now, group.cpu));
+        let (mut cpu_usage, mut throttled_percent) = (None, None);
+        if let Some((_, then, old)) = previous.filter(|(path, _, _)| *path == group.path) {
+            let seconds = (now - then).as_secs_f64();
+            if seconds > 0.0 {
+                let used = group.cpu.usage_usec.saturating_sub(old.usage_usec) as f64 / 1_000_000.0;
+                cpu_usage = Some(used / seconds);
+            }
+            let periods = group.cpu.nr_periods.saturating_sub(old.nr_periods);
+            if periods > 0 {
+                let throttled = group.cpu.nr_throttled.saturating_sub(old.nr_throttled);
+                throttled_percent = Some(throttled as f64 / periods as f64 * 100.0);
+            }
+        }
+
+        Some(CgroupSample {
+            memory_current: group.memory_current.unwrap_or_default(),
+            memory_limit: group.memory_max.filter(|l| host_memory == 0 || *l < host_memory),
+            cpu_limit: group.cpu_max.filter(|l| *l < host_cores as f64),
+            cpu_usage,
+            throttled_percent,
+            path: group.path,
+        })
+    }
+}
+
+// Suma de los procesos de un cgroup (una unidad de systemd o un contenedor).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CgroupUsage {
+    pub path: String,
+    // Unidad o contenedor, ej. `nginx.service`
+    pub name: String,
+    pub processes: usize,
+    // Porcentaje del total de la máquina, como el de cada proceso
+    pub cpu: f32,
+    pub memory: u64,
+    // Bytes por segundo
+    pub read_rate: f64,
+    pub write_rate: f64,
+}
+
+// Agrupa los procesos por cgroup; los que no tienen (fuera de Linux o ya
+// terminados) no se cuentan. Ordenado por uso de CPU.
+pub fn group(processes: &[ProcessSample]) -> Vec<CgroupUsage> {
+    let mut groups: BTreeMap<&str, CgroupUsage> = BTreeMap::new();
+    for process in processes {
+        let Some(path) = process.cgroup.as_deref() else {
+            continue;
+        };
+        let usage = groups.entry(path).or_insert_with(|| CgroupUsage {
+            path: path.to_string(),
+            name: unit_name(path),
+            processes: 0,
+            cpu: 0.0,
+            memory: 0,
+            read_rate: 0.0,
+            write_rate: 0.0,
+        });
+        usage.processes += 1;
+        usage.cpu += process.cpu;
+        usage.memory += process.memory;
+        usage.read_rate += process.read_rate;
+        usage.write_rate += process.write_rate;
+    }
+    let mut groups: Vec<CgroupUsage> = groups.into_values().collect();
+    groups.sort_by(|a, b| b.cpu.total_cmp(&a.cpu).then(b.memory.cmp(&a.memory)));
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::collector::test_process;
+    use crate::linux::Fixture;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+    const SERVICE: &str = "sys/fs/cgroup/system.slice/app.service";
+
+    // Raíz sin límites, el slice con 2 GiB y 2 núcleos, y el servicio con
+    // medio núcleo y sin límite de memoria propio
+    fn hierarchy(name: &str) -> Fixture {
+        let fixture = Fixture::new(name);
+        fixture.write("sys/fs/cgroup/cgroup.controllers", "cpu memory io\n");
+        fixture.write("sys/fs/cgroup/memory.max", "max\n");
+        fixture.write("sys/fs/cgroup/cpu.max", "max 100000\n");
+        fixture.write("sys/fs/cgroup/system.slice/memory.max", &format!("{}\n", 2 * GIB));
+        fixture.write("sys/fs/cgroup/system.slice/cpu.max", "200000 100000\n");
+        fixture.write(&format!("{}/memory.max", SERVICE), "max\n");
+        fixture.write(&format!("{}/memory.current", SERVICE), &format!("{}\n", GIB / 2));
+        fixture.write(&format!("{}/cpu.max", SERVICE), "50000 100000\n");
+        fixture.write(&format!("{}/cpu.stat", SERVICE), &cpu_stat(1_000_000, 10, 0));
+        fixture.write("proc/self/cgroup", "0::/system.slice/app.service\n");
+        fixture
+    }
+
+    fn cpu_stat(usage_usec: u64, nr_periods: u64, nr_throttled: u64) -> String {
+        format!(
+            "usage_usec {}\nuser_usec 0\nsystem_usec 0\nnr_periods {}\nnr_throttled {}\nthrottled_usec 0\n",
+            usage_usec, nr_periods, nr_throttled
+        )
+    }
+
+    fn process(cgroup: Option<&str>, cpu: f32, memory: u64) -> ProcessSample {
+        ProcessSample {
+            cgroup: cgroup.map(str::to_string),
+            read_rate: 1.0,
+            write_rate: 2.0,
+            ..test_process(1, "p", cpu, memory)
+        }
+    }
+
+    #[test]
+    fn current_requires_unified_hierarchy() {
+        let fixture = hierarchy("cgroup-current");
+        assert_eq!(current(&fixture.root).as_deref(), Some("/system.slice/app.service"));
+
+        // cgroup v1 o híbrido: sin controladores en la jerarquía v2
+        fs::remove_file(fixture.root.join("sys/fs/cgroup/cgroup.controllers")).unwrap();
+        assert_eq!(current(&fixture.root), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn of_process_reads_unified_line() {
+        let fixture = Fixture::new("cgroup-of-process");
+        fixture.write("proc/42/cgroup", "12:memory:/legacy\n1:name=systemd:/legacy\n0::/system.slice/nginx.service\n");
+        assert_eq!(of_process(&fixture.root, 42).as_deref(), Some("/system.slice/nginx.service"));
+        assert_eq!(of_process(&fixture.root, 43), None);
+    }
+
+    #[test]
+    fn effective_limits_are_minimum_of_ancestors() {
+        let fixture = hierarchy("cgroup-limits");
+        let group = read(&fixture.root, "/system.slice/app.service").unwrap();
+        assert_eq!(group.memory_current, Some(GIB / 2));
+        // `max` en el servicio y en la raíz: manda el slice
+        assert_eq!(group.memory_max, Some(2 * GIB));
+        // 0.5 del servicio contra 2 del slice y `max` en la raíz
+        assert_eq!(group.cpu_max, Some(0.5));
+        assert_eq!(group.cpu.usage_usec, 1_000_000);
+        assert_eq!(group.cpu.nr_periods, 10);
+
+        let slice = read(&fixture.root, "/system.slice").unwrap();
+        assert_eq!(slice.cpu_max, Some(2.0));
+        assert_eq!(slice.memory_current, None);
+
+        assert_eq!(read(&fixture.root, "/missing.slice"), None);
+    }
+
+    #[test]
+    fn unlimited_hierarchy_has_no_limits() {
+        let fixture = hierarchy("cgroup-unlimited");
+        fixture.write("sys/fs/cgroup/system.slice/memory.max", "max\n");
+        fixture.write("sys/fs/cgroup/system.slice/cpu.max", "max 100000\n");
+        fixture.write(&format!("{}/cpu.max", SERVICE), "max 100000\n");
+        let group = read(&fixture.root, "/system.slice/app.service").unwrap();
+        assert_eq!(group.memory_max, None);
+        assert_eq!(group.cpu_max, None);
+    }
+
+    #[test]
+    fn tracker_computes_usage_and_throttling() {
+        let fixture = hierarchy("cgroup-tracker");
+        let mut tracker = CgroupTracker::new(&fixture.root);
+        let start = Instant::now();
+
+        let first = tracker.update(start, 16 * GIB, 8).unwrap();
+        assert_eq!(first.path, "/system.slice/app.service");
+        assert_eq!(first.memory_limit, Some(2 * GIB));
+        assert_eq!(first.cpu_limit, Some(0.5));
+        assert_eq!(first.cpu_usage, None);
+        assert_eq!(first.memory_percent(), Some(25.0));
+
+        // 0.4 s de CPU en 1 s; 5 de 20 períodos frenados
+        fixture.write(&format!("{}/cpu.stat", SERVICE), &cpu_stat(1_400_000, 30, 5));
+        let second = tracker.update(start + Duration::from_secs(1), 16 * GIB, 8).unwrap();
+        assert!((second.cpu_usage.unwrap() - 0.4).abs() < 1e-9);
+        assert_eq!(second.throttled_percent, Some(25.0));
+        assert!((second.cpu_percent().unwrap() - 80.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn limits_above_host_do_not_count() {
+        let fixture = hierarchy("cgroup-host");
+        let mut tracker = CgroupTracker::new(&fixture.root);
+        let sample = tracker.update(Instant::now(), GIB, 0).unwrap();
+        assert_eq!(sample.memory_limit, None);
+        assert_eq!(sample.cpu_limit, None);
+        assert_eq!(sample.memory_percent(), None);
+        assert_eq!(sample.cpu_percent(), None);
+    }
+
+    #[test]
+    fn unit_names() {
+        assert_eq!(unit_name("/system.slice/nginx.service"), "nginx.service");
+        assert_eq!(
+            unit_name("/system.slice/docker-0123456789abcdef0123.scope"),
+            "docker 0123456789ab"
+        );
+        assert_eq!(unit_name("/docker/0123456789abcdef0123"), "docker 0123456789ab");
+        assert_eq!(
+            unit_name("/user.slice/user-1000.slice/user@1000.service/app.slice/libpod-abcdefabcdef12.scope"),
+            "podman abcdefabcdef"
+        );
+        assert_eq!(unit_name("/user.slice"), "user.slice");
+        assert_eq!(unit_name("/"), "/");
+    }
+
+    #[test]
+    fn group_sums_processes_by_cgroup() {
+        let processes = [
+            process(Some("/system.slice/a.service"), 10.0, 100),
+            process(Some("/system.slice/b.service"), 30.0, 10),
+            process(Some("/system.slice/a.service"), 25.0, 50),
+            process(None, 90.0, 1000),
+        ];
+        let groups = group(&processes);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "a.service");
+        assert_eq!(groups[0].processes, 2);
+        assert_eq!(groups[0].cpu, 35.0);
+        assert_eq!(groups[0].memory, 150);
+        assert_eq!(groups[0].read_rate, 2.0);
+        assert_eq!(groups[1].name, "b.service");
+    }
+}
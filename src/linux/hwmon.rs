@@ -0,0 +1,105 @@
+use std::fs;
+use std::path::Path;
+
+use crate::sensors::TemperatureSample;
+
+// Sensores de temperatura de /sys/class/hwmon. Cada `tempN_input` es un
+// sensor; `name` del dispositivo y `tempN_label` forman la etiqueta, como
+// en sysinfo. Vacío si no hay hwmon, como fuera de Linux.
+pub fn read(root: &Path) -> Vec<TemperatureSample> {
+    let Ok(entries) = fs::read_dir(root.join("sys/class/hwmon")) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<_> = entries.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    devices.sort();
+
+    let mut sensors = Vec::new();
+    for device in devices {
+        let name = read_text(&device.join("name")).unwrap_or_default();
+        let Ok(files) = fs::read_dir(&device) else {
+            continue;
+        };
+        let mut ids: Vec<u32> = files
+            .filter_map(|f| f.ok())
+            .filter_map(|f| {
+                let file = f.file_name().into_string().ok()?;
+                file.strip_prefix("temp")?.strip_suffix("_input")?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+
+        for id in ids {
+            let file = |suffix: &str| device.join(format!("temp{}_{}", id, suffix));
+            let Some(current) = read_celsius(&file("input")) else {
+                continue;
+            };
+            let label = match read_text(&file("label")) {
+                Some(label) if name.is_empty() => label,
+                Some(label) => format!("{} {}", name, label),
+                None if name.is_empty() => format!("temp{}", id),
+                None => format!("{} temp{}", name, id),
+            };
+            sensors.push(TemperatureSample {
+                label,
+                current,
+                max: read_celsius(&file("highest")),
+                critical: read_celsius(&file("crit")),
+            });
+        }
+    }
+    sensors
+}
+
+fn read_text(path: &Path) -> Option<String> {
+    let text = fs::read_to_string(path).ok()?;
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+// Los archivos están en milésimas de grado
+fn read_celsius(path: &Path) -> Option<f32> {
+    read_text(path)?.parse::<i64>().ok().map(|m| m as f32 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::Fixture;
+
+    #[test]
+    fn reads_millidegrees_with_labels_and_limits() {
+        let fixture = Fixture::new("hwmon-read");
+        fixture.write("sys/class/hwmon/hwmon0/name", "coretemp\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp1_input", "45000\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp1_label", "Package id 0\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp1_crit", "100000\n");
+        // Sin etiqueta ni crítica
+        fixture.write("sys/class/hwmon/hwmon0/temp2_input", "43500\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp2_highest", "51250\n");
+        // Ilegible: se omite
+        fixture.write("sys/class/hwmon/hwmon0/temp3_input", "N/A\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp10_input", "-5000\n");
+        // Sin `name`
+        fixture.write("sys/class/hwmon/hwmon1/temp1_input", "38000\n");
+        fixture.write("sys/class/hwmon/hwmon1/temp1_label", "Composite\n");
+        fixture.write("sys/class/hwmon/hwmon1/temp1_crit", "84850\n");
+
+        let sensors = read(&fixture.root);
+        let summary: Vec<_> = sensors.iter().map(|s| (s.label.as_str(), s.current, s.max, s.critical)).collect();
+        assert_eq!(
+            summary,
+            [
+                ("coretemp Package id 0", 45.0, None, Some(100.0)),
+                ("coretemp temp2", 43.5, Some(51.25), None),
+                ("coretemp temp10", -5.0, None, None),
+                ("Composite", 38.0, None, Some(84.85)),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_hwmon_is_empty() {
+        let fixture = Fixture::new("hwmon-missing");
+        assert!(read(&fixture.root).is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+// Lectores de /proc y /sys. Reciben la raíz del sistema de archivos para
+// poder leer copias de esos directorios; en vivo se usa `ROOT`.
+pub mod cgroup;
+pub mod diskstats;
+pub mod hwmon;
+pub mod psi;
+pub mod vmstat;
+
+pub const ROOT: &str = "/";
+
+// Directorio temporal con una copia mínima de /proc y /sys para las pruebas;
+// se borra al terminar.
+#[cfg(test)]
+pub struct Fixture {
+    pub root: std::path::PathBuf,
+}
+
+#[cfg(test)]
+impl Fixture {
+    pub fn new(name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!("metricas-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        Fixture { root }
+    }
+
+    pub fn write(&self, path: &str, contents: &str) {
+        let path = self.root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::Path;
+
+// Porcentaje del tiempo con tareas demoradas en las últimas 10 s, 1 min y 5 min.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PressureAvg {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+}
+
+// `some`: al menos una tarea esperando; `full`: todas las tareas esperando.
+// En la CPU `full` no existe en kernels anteriores a 5.13.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pressure {
+    pub some: PressureAvg,
+    pub full: Option<PressureAvg>,
+}
+
+// Recursos de /proc/pressure; cada uno es `None` si el kernel no tiene PSI
+// (anterior a 4.20 o compilado sin CONFIG_PSI) o fuera de Linux.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Psi {
+    pub cpu: Option<Pressure>,
+    pub memory: Option<Pressure>,
+    pub io: Option<Pressure>,
+}
+
+pub const RESOURCES: [&str; 3] = ["cpu", "memory", "io"];
+
+impl Psi {
+    pub fn get(&self, resource: &str) -> Option<&Pressure> {
+        match resource {
+            "cpu" => self.cpu.as_ref(),
+            "memory" => self.memory.as_ref(),
+            "io" => self.io.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, resource: &str) -> Option<&mut Option<Pressure>> {
+        match resource {
+            "cpu" => Some(&mut self.cpu),
+            "memory" => Some(&mut self.memory),
+            "io" => Some(&mut self.io),
+            _ => None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Psi::default()
+    }
+}
+
+pub fn read(root: &Path) -> Psi {
+    let resource = |name: &str| {
+        fs::read_to_string(root.join("proc/pressure").join(name))
+            .ok()
+            .and_then(|text| parse(&text))
+    };
+    Psi {
+        cpu: resource("cpu"),
+        memory: resource("memory"),
+        io: resource("io"),
+    }
+}
+
+// some avg10=0.00 avg60=0.00 avg300=0.00 total=0
+// full avg10=0.00 avg60=0.00 avg300=0.00 total=0
+fn parse(text: &str) -> Option<Pressure> {
+    let line = |kind: &str| {
+        let line = text.lines().find_map(|l| l.strip_prefix(kind)?.strip_prefix(' '))?;
+        let value = |key: &str| {
+            line.split_whitespace()
+                .find_map(|field| field.strip_prefix(key)?.strip_prefix('='))?
+                .parse::<f64>()
+                .ok()
+        };
+        Some(PressureAvg {
+            avg10: value("avg10")?,
+            avg60: value("avg60")?,
+            avg300: value("avg300")?,
+        })
+    };
+    Some(Pressure {
+        some: line("some")?,
+        full: line("full"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::Fixture;
+
+    #[test]
+    fn reads_some_and_full() {
+        let fixture = Fixture::new("psi-full");
+        fixture.write(
+            "proc/pressure/memory",
+            "some avg10=1.50 avg60=0.75 avg300=0.25 total=123456\n\
+             full avg10=0.50 avg60=0.10 avg300=0.00 total=654\n",
+        );
+        let psi = read(&fixture.root);
+        let memory = psi.memory.unwrap();
+        assert_eq!(memory.some, PressureAvg { avg10: 1.5, avg60: 0.75, avg300: 0.25 });
+        assert_eq!(memory.full, Some(PressureAvg { avg10: 0.5, avg60: 0.1, avg300: 0.0 }));
+        assert_eq!(psi.cpu, None);
+        assert_eq!(psi.io, None);
+        assert!(!psi.is_empty());
+    }
+
+    #[test]
+    fn cpu_without_full_line() {
+        let fixture = Fixture::new("psi-cpu");
+        fixture.write("proc/pressure/cpu", "some avg10=3.00 avg60=2.00 avg300=1.00 total=99\n");
+        let cpu = read(&fixture.root).cpu.unwrap();
+        assert_eq!(cpu.some.avg10, 3.0);
+        assert_eq!(cpu.full, None);
+    }
+
+    #[test]
+    fn malformed_some_line_is_none() {
+        let fixture = Fixture::new("psi-bad");
+        fixture.write("proc/pressure/io", "some avg10=x avg60=0.00 avg300=0.00 total=0\n");
+        assert_eq!(read(&fixture.root).io, None);
+    }
+
+    #[test]
+    fn missing_directory_is_none() {
+        let fixture = Fixture::new("psi-missing");
+        let psi = read(&fixture.root);
+        assert_eq!(psi.cpu, None);
+        assert_eq!(psi.memory, None);
+        assert_eq!(psi.io, None);
+        assert!(psi.is_empty());
+    }
+}
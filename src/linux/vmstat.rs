@@ -0,0 +1,118 @@
+use std::fs;
+use std::path::Path;
+use std::time::Instant;
+
+// Bytes llevados desde y hacia la swap desde el arranque.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwapCounters {
+    pub swapped_in: u64,
+    pub swapped_out: u64,
+}
+
+// `None` si no hay /proc/vmstat, como fuera de Linux.
+pub fn read(root: &Path) -> Option<SwapCounters> {
+    let text = fs::read_to_string(root.join("proc/vmstat")).ok()?;
+    parse(&text, page_size())
+}
+
+// Líneas "nombre valor"; pswpin y pswpout cuentan páginas.
+fn parse(text: &str, page_size: u64) -> Option<SwapCounters> {
+    let pages = |name: &str| {
+        text.lines()
+            .filter_map(|line| line.split_once(' '))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+    };
+    Some(SwapCounters {
+        swapped_in: pages("pswpin")? * page_size,
+        swapped_out: pages("pswpout")? * page_size,
+    })
+}
+
+// Convierte los contadores en bytes por segundo; la primera muestra no tiene
+// con qué comparar.
+#[derive(Default)]
+pub struct SwapTracker {
+    last: Option<(Instant, SwapCounters)>,
+}
+
+impl SwapTracker {
+    pub fn update(&mut self, now: Instant, counters: SwapCounters) -> (Option<f64>, Option<f64>) {
+        let Some((then, old)) = self.last.replace((now, counters)) else {
+            return (None, None);
+        };
+        let seconds = (now - then).as_secs_f64();
+        if seconds <= 0.0 {
+            return (None, None);
+        }
+        let rate = |new: u64, old: u64| Some(new.saturating_sub(old) as f64 / seconds);
+        (
+            rate(counters.swapped_in, old.swapped_in),
+            rate(counters.swapped_out, old.swapped_out),
+        )
+    }
+}
+
+#[cfg(unix)]
+fn page_size() -> u64 {
+    // SAFETY: sysconf no tiene precondiciones
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if size > 0 {
+        size as u64
+    } else {
+        4096
+    }
+}
+
+#[cfg(not(unix))]
+fn page_size() -> u64 {
+    4096
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::Fixture;
+    use std::time::Duration;
+
+    #[test]
+    fn parses_swap_pages() {
+        let text = "nr_free_pages 1000\npswpin 10\npswpout 25\npswpin_extra 99\n";
+        let counters = parse(text, 4096).unwrap();
+        assert_eq!(counters, SwapCounters { swapped_in: 40960, swapped_out: 102400 });
+        assert_eq!(parse("nr_free_pages 1000\npswpin 10\n", 4096), None);
+        assert_eq!(parse("pswpin x\npswpout 1\n", 4096), None);
+    }
+
+    #[test]
+    fn reads_the_file() {
+        let fixture = Fixture::new("vmstat");
+        fixture.write("proc/vmstat", "pgfault 123\npswpin 2\npswpout 3\n");
+        let counters = read(&fixture.root).unwrap();
+        assert_eq!(counters.swapped_in, 2 * page_size());
+        assert_eq!(counters.swapped_out, 3 * page_size());
+    }
+
+    #[test]
+    fn missing_file_is_none() {
+        let fixture = Fixture::new("vmstat-missing");
+        assert_eq!(read(&fixture.root), None);
+    }
+
+    #[test]
+    fn rate_over_two_samples() {
+        let mut tracker = SwapTracker::default();
+        let start = Instant::now();
+        let counters = |swapped_in, swapped_out| SwapCounters { swapped_in, swapped_out };
+        assert_eq!(tracker.update(start, counters(1000, 2000)), (None, None));
+        assert_eq!(
+            tracker.update(start + Duration::from_secs(2), counters(5000, 2000)),
+            (Some(2000.0), Some(0.0))
+        );
+        // Los contadores que retroceden no dan tasas negativas
+        assert_eq!(
+            tracker.update(start + Duration::from_secs(4), counters(0, 2400)),
+            (Some(0.0), Some(200.0))
+        );
+    }
+}
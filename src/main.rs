@@ -1,239 +1,796 @@
-use std::{fs::OpenOptions, io::{self, Write}, thread::{sleep, spawn}, time::Duration};
+use std::{fs::{File, OpenOptions}, io::{self, Write}, path::Path, thread::{self, sleep, spawn}, time::Duration};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
 use std::env;
+use clap::Parser;
 use chrono::Local;
-use rusqlite::{Connection, Result, params};
-use sysinfo::{System, Disks, Networks, ProcessesToUpdate, RefreshKind};
-use std::ffi::OsStr;
+use rusqlite::{Connection, Result};
 
-use winreg::enums::*;
-use winreg::RegKey;
-use notify_rust::Notification;
+use signal_hook::consts::{SIGINT, SIGTERM};
 
 mod alert;
-use alert::AlertManager;
+mod autostart;
+use alert::{AlertCondition, AlertEvent, AlertManager, AlertState, Clear};
+
+mod cli;
+use cli::{AlertsCommand, AutostartAction, Cli, Command, ExportFormat};
+
+mod config;
+use config::{Config, Overrides, SharedConfig};
+
+mod collector;
+use collector::{Collector, Sample, SampleBus};
+
+mod db;
+
+mod disk;
+
+mod export;
 
 mod gui;
 
-fn redirect_stdout() -> io::BufWriter<std::fs::File> {
+mod kill;
+use kill::KillRecord;
+
+mod linux;
+use linux::psi::{self, PressureAvg};
+
+mod network;
+
+mod notify;
+
+mod process;
+use process::ProcessMatcher;
+
+mod rule;
+
+mod sensors;
+
+mod table;
+
+mod tree;
+
+mod watchdog;
+
+fn redirect_stdout(path: &Path) -> io::BufWriter<std::fs::File> {
     let log_file = OpenOptions::new()
         .create(true)
         .append(true)
-        .open("metrics.log")
+        .open(path)
         .expect("Failed to open log file");
 
     io::BufWriter::new(log_file)
 }
 
-fn add_to_startup() {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let key = hkcu.open_subkey_with_flags(
-        "Software\\Microsoft\\Windows\\CurrentVersion\\Run", 
-        KEY_WRITE
-    ).expect("Failed to open registry key");
+fn run_autostart_command(action: AutostartAction, backend: Option<&str>) -> io::Result<()> {
+    let backend = autostart::backend(backend)?;
 
-    let exe_path_buf = env::current_exe()
-        .expect("Failed to get current exe path");
+    match action {
+        AutostartAction::Enable => {
+            backend.enable(&env::current_exe()?)?;
+            println!("Inicio automático habilitado ({})", backend.name());
+        }
+        AutostartAction::Disable => {
+            backend.disable()?;
+            println!("Inicio automático deshabilitado ({})", backend.name());
+        }
+        AutostartAction::Status => match backend.status()? {
+            autostart::Status::Enabled(detail) => {
+                println!("Inicio automático habilitado ({}): {}", backend.name(), detail)
+            }
+            autostart::Status::Disabled => {
+                println!("Inicio automático deshabilitado ({})", backend.name())
+            }
+        },
+    }
 
-    let exe_path = exe_path_buf
-        .to_str()
-        .expect("Failed to convert path to str");
+    Ok(())
+}
 
-    key.set_value("Metricas", &exe_path).expect("Failed to add program to startup");
+fn since_timestamp(since: Duration) -> String {
+    let since = chrono::Duration::from_std(since).unwrap_or(chrono::Duration::MAX);
+    Local::now()
+        .checked_sub_signed(since)
+        .map(|t| t.format(db::TIMESTAMP_FORMAT).to_string())
+        .unwrap_or_default()
 }
 
+fn run_query(config: &Config, since: Duration, limit: Option<usize>) -> Result<()> {
+    let conn = db::open(&config.general.db_path)?;
+    let samples = db::samples_since(&conn, Some(&since_timestamp(since)), limit)?;
 
-fn osstr_to_string(os_str: &OsStr) -> String {
-    os_str.to_string_lossy().into_owned()
+    println!(
+        "{:<19}  {:>7}  {:>10}  {:>10}  {:>7}  {:>7}  {:>10}  {:>10}  {:>6}",
+        "Hora", "CPU %", "Mem (MB)", "Total (MB)", "Mem %", "Swap %", "Down Mbps", "Up Mbps", "Carga"
+    );
+    for s in &samples {
+        let load = s.load1.map(|l| format!("{:.2}", l)).unwrap_or_else(|| "-".to_string());
+        let swap = s.swap_percent().map(|p| format!("{:.2}", p)).unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<19}  {:>7.2}  {:>10.2}  {:>10.2}  {:>7.2}  {:>7}  {:>10.2}  {:>10.2}  {:>6}",
+            s.timestamp,
+            s.cpu_total,
+            s.memory_used as f64 / (1024.0 * 1024.0),
+            s.memory_total as f64 / (1024.0 * 1024.0),
+            s.memory_percent(),
+            swap,
+            s.net_rx_mbps,
+            s.net_tx_mbps,
+            load
+        );
+    }
+    println!("{} muestras", samples.len());
+    Ok(())
 }
 
-fn run_monitoring() -> Result<()> {
-    let mut log_writer = redirect_stdout();  // Redirigir la salida a un archivo de log
-    add_to_startup();
-    let mut alert_manager = AlertManager::new();
+fn run_export(
+    config: &Config,
+    format: ExportFormat,
+    since: Option<Duration>,
+    output: Option<&Path>,
+) -> Result<()> {
+    let conn = db::open(&config.general.db_path)?;
+    let since = since.map(since_timestamp);
+    let samples = db::samples_since(&conn, since.as_deref(), None)?;
+
+    let written = match output {
+        Some(path) => File::create(path).and_then(|file| {
+            let mut writer = io::BufWriter::new(file);
+            export::write(&mut writer, format, &samples)?;
+            writer.flush()
+        }),
+        None => export::write(&mut io::stdout().lock(), format, &samples),
+    };
+
+    if let Err(e) = written {
+        eprintln!("Error al exportar: {}", e);
+        std::process::exit(1);
+    }
+    if let Some(path) = output {
+        eprintln!("{} muestras exportadas a {}", samples.len(), path.display());
+    }
+    Ok(())
+}
 
-    let _ = Notification::new()
-        .summary("Monitoreo iniciado")
-        .body("Ya se está recopilando información del sistema.")
-        .show();
+fn list_alerts(config: &Config) {
+    for alert in &config.alerts.conditions {
+        let targets = alert.notify.as_ref().unwrap_or(&config.notifications.default);
+        println!("{:<12}  {} -> {}", alert.id(), alert.describe(), targets.join(", "));
+    }
+    for watch in &config.alerts.watch {
+        let targets = watch.notify.as_ref().unwrap_or(&config.notifications.default);
+        println!("{:<12}  {} -> {}", watch.id, watch.describe(), targets.join(", "));
+    }
+}
 
-    let conn = Connection::open("metrics.db")?;
+fn alert_history(config: &Config, since: Duration, limit: Option<usize>) -> Result<()> {
+    let conn = db::open(&config.general.db_path)?;
+    let records = db::alerts_since(&conn, Some(&since_timestamp(since)), limit)?;
 
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS metrics (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            Hora TEXT NOT NULL,
-            Uso_CPU TEXT,
-            Uso_CPU_Total TEXT,
-            Memoria_total TEXT,
-            Memoria_usada TEXT,
-            Procesos TEXT,
-            Discos TEXT,
-            Internet TEXT,
-            Procesos_CPU TEXT
-        )",
-        [],
-    )?;
+    println!(
+        "{:<19}  {:<19}  {:<12}  {:<12}  {:>9}  {:>9}  Proceso",
+        "Inicio", "Fin", "Regla", "Estado", "Valor", "Umbral"
+    );
+    for r in &records {
+        let resolved_at = r
+            .resolved_at
+            .map(|t| t.format(db::TIMESTAMP_FORMAT).to_string())
+            .unwrap_or_else(|| "-".to_string());
+        println!(
+            "{:<19}  {:<19}  {:<12}  {:<12}  {:>9.2}  {:>9.2}  {}",
+            r.started_at.format(db::TIMESTAMP_FORMAT),
+            resolved_at,
+            r.rule_id,
+            r.state.label(),
+            r.value,
+            r.threshold,
+            r.top_offender.as_deref().unwrap_or("-")
+        );
+    }
+    let firing = records.iter().filter(|r| r.state == AlertState::Firing).count();
+    println!("{} alertas ({} activas)", records.len(), firing);
+    Ok(())
+}
 
-    let mut sys = System::new_with_specifics(RefreshKind::everything());
-    let num_cores = sys.cpus().len() as f32;
+fn process_history(config: &Config, since: Duration, limit: Option<usize>) -> Result<()> {
+    let conn = db::open(&config.general.db_path)?;
+    let events = db::process_events_since(&conn, Some(&since_timestamp(since)), limit)?;
 
-    sys.refresh_cpu_all();
-    sys.refresh_processes(ProcessesToUpdate::All, false);
-    sleep(Duration::from_millis(500));
+    println!("{:<19}  {:<12}  {:<10}  {:>15}  Proceso", "Fecha", "Vigilancia", "Evento", "Pid");
+    for e in &events {
+        let pid = match e.old_pid {
+            Some(old) => format!("{} → {}", old, e.pid),
+            None => e.pid.to_string(),
+        };
+        println!(
+            "{:<19}  {:<12}  {:<10}  {:>15}  {}",
+            e.timestamp.format(db::TIMESTAMP_FORMAT),
+            e.watch_id,
+            e.kind.label(),
+            pid,
+            e.name
+        );
+    }
+    println!("{} eventos", events.len());
+    Ok(())
+}
 
-    loop {
-        sys.refresh_cpu_all();
-        sys.refresh_processes(ProcessesToUpdate::All, false);
-        sys.refresh_memory();
+fn kill_history(config: &Config, since: Duration, limit: Option<usize>) -> Result<()> {
+    let conn = db::open(&config.general.db_path)?;
+    let records = db::process_kills_since(&conn, Some(&since_timestamp(since)), limit)?;
+
+    println!("{:<19}  {:>7}  {:<6}  {:<26}  {:<10}  Proceso", "Fecha", "Pid", "Señal", "Resultado", "Usuario");
+    for r in &records {
+        println!(
+            "{:<19}  {:>7}  {:<6}  {:<26}  {:<10}  {}",
+            r.timestamp.format(db::TIMESTAMP_FORMAT),
+            r.pid,
+            r.signal.as_str(),
+            format!("{} ({:.1} s)", r.result.label(), r.elapsed.as_secs_f64()),
+            r.user.as_deref().unwrap_or("-"),
+            r.name
+        );
+    }
+    println!("{} señales", records.len());
+    Ok(())
+}
 
-        let mut networks = Networks::new_with_refreshed_list();
-        let before_rx = networks.get("Wi-Fi").map(|d| d.total_received());
-        let before_tx = networks.get("Wi-Fi").map(|d| d.total_transmitted());
+fn list_series() {
+    for series in rule::series::SERIES {
+        let name = match series.label {
+            Some(label) => format!("{}{{{}=\"...\"}}", series.name, label),
+            None => series.name.to_string(),
+        };
+        let scope = if series.process { "  (con `process`)" } else { "" };
+        println!("{:<28}  {}{}", name, series.help, scope);
+    }
+    println!("\nFunciones: avg(serie, 5m), min(serie, 5m), max(serie, 5m)");
+}
 
-        sleep(Duration::from_secs(5));
+// Evalúa las condiciones sobre el historial con la misma lógica de
+// sostenimiento, enfriamiento e histéresis que en vivo.
+fn alerts_dry_run(config: &Config, condition: Option<AlertCondition>, id: Option<&str>, since: Duration) -> Result<()> {
+    let mut alerts = config.alerts.clone();
+    if let Some(condition) = condition {
+        let scoped = condition.process.is_some();
+        for (name, text) in [("--expr", condition.expr.as_deref()), ("--clear", condition.clear_expr().as_deref())] {
+            if let Some(Err(e)) = text.map(|text| rule::check(text, scoped)) {
+                eprintln!("Error en {}: {}\n{}", name, e, e.pointer(text.unwrap_or_default()));
+                std::process::exit(2);
+            }
+        }
+        alerts.conditions = vec![condition];
+    } else if let Some(id) = id {
+        alerts.conditions.retain(|c| c.id() == id);
+        if alerts.conditions.is_empty() {
+            eprintln!("No hay una condición con id `{}`", id);
+            std::process::exit(2);
+        }
+    }
 
-        networks.refresh(true);
-        let after_rx = networks.get("Wi-Fi").map(|d| d.total_received());
-        let after_tx = networks.get("Wi-Fi").map(|d| d.total_transmitted());
+    if alerts.conditions.iter().any(|c| c.process.is_some()) {
+        eprintln!(
+            "Aviso: el historial guarda solo los 5 procesos con más CPU y memoria de cada muestra, \
+             sin ejecutable, línea de comandos ni usuario; las reglas con `process` pueden dar resultados parciales."
+        );
+    }
 
-        let (received_mbps, transmitted_mbps) = match (before_rx, before_tx, after_rx, after_tx) {
-            (Some(brx), Some(btx), Some(arx), Some(atx)) => {
-                let down_bytes = arx.saturating_sub(brx);
-                let up_bytes = atx.saturating_sub(btx);
-                let down_mbps = (down_bytes as f64 * 8.0) / (1024.0 * 1024.0 * 5.0);
-                let up_mbps = (up_bytes as f64 * 8.0) / (1024.0 * 1024.0 * 5.0);
-                (down_mbps, up_mbps)
+    let conn = db::open(&config.general.db_path)?;
+    let samples = db::load_samples(&conn, Some(&since_timestamp(since)))?;
+    let mut manager = AlertManager::silent(&alerts);
+
+    let mut fired = 0;
+    for sample in &samples {
+        for event in manager.check_alerts(sample) {
+            match event {
+                AlertEvent::Firing { record, .. } => {
+                    fired += 1;
+                    println!(
+                        "{}  ACTIVA    {:<12}  valor {:.2} (umbral {:.2})  {}",
+                        record.started_at.format(db::TIMESTAMP_FORMAT),
+                        record.rule_id,
+                        record.value,
+                        record.threshold,
+                        record.top_offender.as_deref().unwrap_or("")
+                    );
+                }
+                AlertEvent::Resolved { record, value, .. } => {
+                    let duration = record
+                        .resolved_at
+                        .and_then(|end| (end - record.started_at).to_std().ok())
+                        .unwrap_or_default();
+                    println!(
+                        "{}  RESUELTA  {:<12}  valor {:.2} tras {:?}",
+                        sample.timestamp.format(db::TIMESTAMP_FORMAT),
+                        record.rule_id,
+                        value,
+                        Duration::from_secs(duration.as_secs())
+                    );
+                }
+                // `silent` no evalúa las vigilancias de procesos
+                AlertEvent::Process { .. } => {}
             }
-            _ => (0.0, 0.0),
-        };
+        }
+    }
+
+    let still_firing = manager.interrupt_all().len();
+    println!(
+        "{} muestras evaluadas, {} activaciones ({} siguen activas al final)",
+        samples.len(),
+        fired,
+        still_firing
+    );
+    Ok(())
+}
+
+// Duerme en intervalos cortos para reaccionar rápido a una señal de apagado.
+// Devuelve `true` si se pidió detener el monitoreo.
+fn wait_or_shutdown(shutdown: &AtomicBool, duration: Duration) -> bool {
+    let step = Duration::from_millis(100);
+    let mut remaining = duration;
+    while !remaining.is_zero() {
+        if shutdown.load(Ordering::SeqCst) {
+            return true;
+        }
+        let chunk = remaining.min(step);
+        sleep(chunk);
+        remaining -= chunk;
+    }
+    shutdown.load(Ordering::SeqCst)
+}
+
+// `kills` trae las señales enviadas desde la ventana, que se registran en
+// la misma conexión que las muestras.
+fn run_monitoring(
+    shutdown: &AtomicBool,
+    shared: &SharedConfig,
+    bus: &SampleBus,
+    kills: Receiver<KillRecord>,
+) -> Result<()> {
+    let config = shared.get();
+    let mut log_writer = redirect_stdout(&config.general.log_path);  // Redirigir la salida a un archivo de log
+    let mut conn = db::open(&config.general.db_path)?;
+    db::interrupt_open_alerts(&conn)?;
+    let samples = bus.subscribe();
+
+    let result = thread::scope(|scope| {
+        scope.spawn(|| run_collector(shutdown, shared, bus));
+        let result = monitoring_loop(shutdown, shared, samples, &kills, &mut conn, &mut log_writer);
+        // Si el almacenamiento falla también se detiene la recolección
+        shutdown.store(true, Ordering::SeqCst);
+        result
+    });
+
+    // Cierre ordenado: vaciar el log y cerrar SQLite aunque el ciclo haya fallado
+    if let Err(e) = log_writer.flush() {
+        eprintln!("Error al vaciar el log: {}", e);
+    }
+    conn.close().map_err(|(_, e)| e)?;
+    println!("Monitoreo detenido.");
+    result
+}
 
-        let total_memory = sys.total_memory();
-        let used_memory = sys.used_memory();
+// Único hilo que consulta el sistema; publica cada muestra en el bus.
+fn run_collector(shutdown: &AtomicBool, shared: &SharedConfig, bus: &SampleBus) {
+    let mut config = shared.get();
+    let mut generation = shared.generation();
+    let mut collector = Collector::new(config.network.clone(), config.sensors.clone());
 
-        let total_cpu_usage: f32 = sys.cpus().iter().map(|c| c.cpu_usage()).sum();
-        let avg_system_cpu_usage = total_cpu_usage / num_cores;
+    loop {
+        if shared.generation() != generation {
+            generation = shared.generation();
+            config = shared.get();
+            collector.set_filter(config.network.clone());
+            collector.set_sensors(config.sensors.clone());
+        }
 
-        let mut cpu_usages = Vec::new();
-        for (i, cpu) in sys.cpus().iter().enumerate() {
-            cpu_usages.push(format!("CPU {}: {:.2}%", i + 1, cpu.cpu_usage()));
+        if wait_or_shutdown(shutdown, config.general.interval) {
+            return;
+        }
+        bus.publish(collector.collect());
+        if wait_or_shutdown(shutdown, config.general.pause) {
+            return;
         }
-        let cpu_usage_str = cpu_usages.join(" | ");
+    }
+}
 
-        let mem_percentage = (used_memory as f64 / total_memory as f64) * 100.0;
+// Alertas, almacenamiento y log de cada muestra publicada.
+fn monitoring_loop(
+    shutdown: &AtomicBool,
+    shared: &SharedConfig,
+    samples: Receiver<Arc<Sample>>,
+    kills: &Receiver<KillRecord>,
+    conn: &mut Connection,
+    log_writer: &mut io::BufWriter<std::fs::File>,
+) -> Result<()> {
+    let mut config = shared.get();
+    let mut generation = shared.generation();
+    let mut alert_manager = AlertManager::new(&config.alerts, &config.notifications);
+
+    // Solo en el log: los destinos de notificación son para las alertas
+    let message = "Monitoreo iniciado.\n";
+    log_writer.write_all(message.as_bytes()).expect("Failed to write to log file");
+    print!("{}", message);
+
+    while !shutdown.load(Ordering::SeqCst) {
+        save_kills(kills, conn, log_writer)?;
+        let sample = match samples.recv_timeout(Duration::from_millis(200)) {
+            Ok(sample) => sample,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
 
-        alert_manager.check_alerts(
-            avg_system_cpu_usage,
-            received_mbps as f32,
-            mem_percentage as f32,
-            &sys,
-            num_cores,
-        );
+        if shared.generation() != generation {
+            generation = shared.generation();
+            let new_config = shared.get();
+            let changes = config::diff(&config, &new_config);
 
-        let mut processes_cpu: Vec<_> = sys.processes().values().collect();
-        processes_cpu.sort_by(|a, b| b.cpu_usage().partial_cmp(&a.cpu_usage()).unwrap());
-        let mut process_cpu_info = Vec::new();
-        for p in processes_cpu.iter().take(5) {
-            let cpu_percent = p.cpu_usage() / num_cores;
-            process_cpu_info.push(format!(
-                "[{}] {} (CPU: {:.2}%)",
-                p.pid(),
-                osstr_to_string(p.name()),
-                cpu_percent
-            ));
-        }
-        let process_cpu_info_str = process_cpu_info.join(" | ");
-
-        let mut procesos_mem: Vec<_> = sys.processes().values().collect();
-        procesos_mem.sort_by(|a, b| b.memory().partial_cmp(&a.memory()).unwrap());
-        let mut process_mem_info = Vec::new();
-        for p in procesos_mem.iter().take(5) {
-            process_mem_info.push(format!(
-                "[{}] {} (Mem: {:.2} MB)",
-                p.pid(),
-                osstr_to_string(p.name()),
-                p.memory() as f64 / (1024.0 * 1024.0)
-            ));
-        }
-        let process_mem_info_str = process_mem_info.join(" | ");
-
-        let disks = Disks::new_with_refreshed_list();
-        let mut disk_info = Vec::new();
-        for disk in disks.iter() {
-            let kind = format!("{:?}", disk.kind());
-            let mount = disk.mount_point().to_string_lossy();
-            let read = disk.usage().read_bytes as f64 / (1024.0 * 1024.0);
-            let written = disk.usage().written_bytes as f64 / (1024.0 * 1024.0);
-            disk_info.push(format!(
-                "[{}][Tipo: {}] Lectura: {:.2} MB | Escritura: {:.2} MB",
-                mount, kind, read, written
-            ));
-        }
-        let disk_info_str = disk_info.join(" | ");
-
-        let network_info_str = format!(
-            "Wi-Fi: {:.2} Mbps (down) / {:.2} Mbps (up)",
-            received_mbps, transmitted_mbps
-        );
+            for record in alert_manager.reconfigure(&new_config.alerts, &new_config.notifications) {
+                db::save_alert(conn, &record)?;
+            }
 
-        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
-
-        let total_memory_mb = format!("{:.2} MB", total_memory as f64 / (1024.0 * 1024.0));
-        let used_memory_mb = format!("{:.2} MB", used_memory as f64 / (1024.0 * 1024.0));
-
-        conn.execute(
-            "INSERT INTO metrics (
-                Hora,
-                Uso_CPU,
-                Uso_CPU_Total,
-                Memoria_total,
-                Memoria_usada,
-                Procesos,
-                Discos,
-                Internet,
-                Procesos_CPU
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
-            params![
-                timestamp,
-                cpu_usage_str,
-                format!("{:.2}", avg_system_cpu_usage),
-                total_memory_mb,
-                used_memory_mb,
-                process_mem_info_str,
-                disk_info_str,
-                network_info_str,
-                process_cpu_info_str
-            ],
-        )?;
-
-        let output = format!(
-            "=== Información del sistema ===\n\
-             Fecha y hora: {}\n\
-             CPU Uso (por núcleo): {}\n\
-             CPU Uso (promedio total): {:.2}%\n\
-             Memoria total: {}, Memoria usada: {}\n\
-             Procesos más demandantes de CPU:\n{}\n\
-             Procesos más demandantes de RAM:\n{}\n\
-             Discos:\n{}\n\
-             Redes:\n{}\n\
-             Datos insertados en SQLite exitosamente.\n\
-             =====================================\n\n",
-            timestamp, cpu_usage_str, avg_system_cpu_usage, total_memory_mb, used_memory_mb, process_cpu_info_str, process_mem_info_str, disk_info_str, network_info_str
-        );
+            let mut message = String::from("=== Configuración recargada ===\n");
+            for change in &changes {
+                message.push_str(change);
+                message.push('\n');
+            }
+            if new_config.general.db_path != config.general.db_path
+                || new_config.general.log_path != config.general.log_path
+            {
+                message.push_str("Los cambios de db_path/log_path se aplican al reiniciar.\n");
+            }
+            message.push('\n');
+            log_writer.write_all(message.as_bytes()).expect("Failed to write to log file");
+            println!("{}", message);
+
+            config = new_config;
+        }
+
+        for event in alert_manager.check_alerts(&sample) {
+            let record = match event {
+                AlertEvent::Firing { record, .. } | AlertEvent::Resolved { record, .. } => Some(record),
+                AlertEvent::Process { event, record, .. } => {
+                    db::save_process_event(conn, &event)?;
+                    record
+                }
+            };
+            if let Some(record) = record {
+                db::save_alert(conn, &record)?;
+            }
+        }
+        db::insert_sample(conn, &sample)?;
 
+        let output = format_sample(&sample);
         log_writer.write_all(output.as_bytes()).expect("Failed to write to log file");
         println!("{}", output);
+    }
+
+    for record in alert_manager.interrupt_all() {
+        db::save_alert(conn, &record)?;
+    }
+    save_kills(kills, conn, log_writer)
+}
+
+fn save_kills(kills: &Receiver<KillRecord>, conn: &Connection, log_writer: &mut io::BufWriter<std::fs::File>) -> Result<()> {
+    while let Ok(record) = kills.try_recv() {
+        db::save_process_kill(conn, &record)?;
+        let message = format!(
+            "Señal {} a {} ({}) desde la ventana: {} ({:.1} s)\n",
+            record.signal.as_str(),
+            record.name,
+            record.pid,
+            record.result.label(),
+            record.elapsed.as_secs_f64()
+        );
+        log_writer.write_all(message.as_bytes()).expect("Failed to write to log file");
+        print!("{}", message);
+    }
+    Ok(())
+}
+
+fn format_sample(sample: &Sample) -> String {
+    let cpu_usage_str = sample
+        .cpu_cores
+        .iter()
+        .enumerate()
+        .map(|(i, usage)| format!("CPU {}: {:.2}%", i + 1, usage))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let process_cpu_info_str = sample
+        .top_cpu(5)
+        .iter()
+        .map(|p| format!("[{}] {} (CPU: {:.2}%)", p.pid, p.name, p.cpu))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let process_mem_info_str = sample
+        .top_memory(5)
+        .iter()
+        .map(|p| format!("[{}] {} (Mem: {:.2} MB)", p.pid, p.name, p.memory as f64 / (1024.0 * 1024.0)))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let cgroup_info_str = sample
+        .cgroups
+        .iter()
+        .take(5)
+        .map(|g| {
+            format!(
+                "{} (CPU: {:.2}%, Mem: {:.2} MB, E/S: {:.2}/{:.2} MB/s, {} procesos)",
+                g.name,
+                g.cpu,
+                g.memory as f64 / (1024.0 * 1024.0),
+                g.read_rate / (1024.0 * 1024.0),
+                g.write_rate / (1024.0 * 1024.0),
+                g.processes
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let disk_info_str = sample
+        .disks
+        .iter()
+        .map(|d| {
+            let mut space = format!("{:.2} GB", d.available_space as f64 / (1024.0 * 1024.0 * 1024.0));
+            if let Some(free) = d.free_percent() {
+                space.push_str(&format!(" ({:.2}%)", free));
+            }
+            if let Some(inodes) = d.inodes_free_percent() {
+                space.push_str(&format!(", inodos {:.2}%", inodes));
+            }
+            if let Some(eta) = d.full_in {
+                space.push_str(&format!(", se llena en {}", disk::format_eta(eta)));
+            }
+            // Operaciones y latencia entre paréntesis cuando se conocen
+            let detail = |iops: Option<f64>, latency: Option<f64>| match (iops, latency) {
+                (Some(iops), Some(latency)) => format!(" ({:.0} IOPS, {:.1} ms)", iops, latency),
+                (Some(iops), None) => format!(" ({:.0} IOPS)", iops),
+                _ => String::new(),
+            };
+            let mut text = format!(
+                "[{}][Tipo: {}] Lectura: {:.2} MB/s{} | Escritura: {:.2} MB/s{}",
+                d.mount,
+                d.kind,
+                d.io.read_rate / (1024.0 * 1024.0),
+                detail(d.io.read_iops, d.io.read_latency_ms),
+                d.io.write_rate / (1024.0 * 1024.0),
+                detail(d.io.write_iops, d.io.write_latency_ms),
+            );
+            if let Some(queue) = d.io.queue_depth {
+                text.push_str(&format!(" | Cola: {:.2}", queue));
+            }
+            text.push_str(&format!(" | Libre: {}", space));
+            text
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let temperature_str = sample
+        .temperatures
+        .iter()
+        .map(|t| {
+            let limits: Vec<String> = [("máx", t.max), ("crítica", t.critical)]
+                .into_iter()
+                .filter_map(|(name, value)| Some(format!("{} {:.1} °C", name, value?)))
+                .collect();
+            match limits.is_empty() {
+                true => format!("{}: {:.1} °C", t.label, t.current),
+                false => format!("{}: {:.1} °C ({})", t.label, t.current, limits.join(", ")),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let pressure_str = if sample.pressure.is_empty() {
+        "no disponible".to_string()
+    } else {
+        let avg = |a: &PressureAvg| format!("{:.2}/{:.2}/{:.2}", a.avg10, a.avg60, a.avg300);
+        psi::RESOURCES
+            .iter()
+            .filter_map(|r| {
+                let pressure = sample.pressure.get(r)?;
+                let mut text = format!("{} some {}", r, avg(&pressure.some));
+                if let Some(full) = &pressure.full {
+                    text.push_str(&format!(" full {}", avg(full)));
+                }
+                Some(text)
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+
+    let cgroup_str = match &sample.cgroup {
+        None => String::new(),
+        Some(cgroup) => {
+            let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+            let mut text = format!("Cgroup: {} | Memoria: {:.2} MB", cgroup.path, mb(cgroup.memory_current));
+            if let (Some(limit), Some(percent)) = (cgroup.memory_limit, cgroup.memory_percent()) {
+                text.push_str(&format!(" de {:.2} MB ({:.2}%)", mb(limit), percent));
+            }
+            if let Some(usage) = cgroup.cpu_usage {
+                text.push_str(&format!(" | CPU: {:.2} núcleos", usage));
+            }
+            if let (Some(limit), Some(percent)) = (cgroup.cpu_limit, cgroup.cpu_percent()) {
+                text.push_str(&format!(" de {:.2} ({:.2}%)", limit, percent));
+            }
+            if let Some(throttled) = cgroup.throttled_percent {
+                text.push_str(&format!(" | Frenado: {:.2}% de los períodos", throttled));
+            }
+            text.push('\n');
+            text
+        }
+    };
 
-        sleep(Duration::from_millis(500));
+    let (received_mbps, transmitted_mbps) = sample.network_totals();
+    let mut network_info: Vec<String> = sample
+        .interfaces
+        .iter()
+        .map(|i| format!("{}: {:.2} Mbps (down) / {:.2} Mbps (up)", i.name, i.rx_mbps, i.tx_mbps))
+        .collect();
+    network_info.push(format!(
+        "Total: {:.2} Mbps (down) / {:.2} Mbps (up)",
+        received_mbps, transmitted_mbps
+    ));
+    let network_info_str = network_info.join("\n");
+
+    let frequency_str = sample
+        .cpu_frequencies
+        .iter()
+        .enumerate()
+        .map(|(i, mhz)| format!("CPU {}: {} MHz", i + 1, mhz))
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let [load1, load5, load15] = sample.load_average;
+    let uptime = disk::format_eta(Duration::from_secs(sample.uptime));
+
+    let timestamp = sample.timestamp.format(db::TIMESTAMP_FORMAT);
+    let total_memory_mb = format!("{:.2} MB", sample.memory_total as f64 / (1024.0 * 1024.0));
+    let used_memory_mb = format!("{:.2} MB", sample.memory_used as f64 / (1024.0 * 1024.0));
+    let available_memory_mb = format!("{:.2} MB", sample.memory_available as f64 / (1024.0 * 1024.0));
+    let mut swap_str = format!(
+        "{:.2} MB de {:.2} MB",
+        sample.swap_used as f64 / (1024.0 * 1024.0),
+        sample.swap_total as f64 / (1024.0 * 1024.0)
+    );
+    if let (Some(swap_in), Some(swap_out)) = (sample.swap_in_rate, sample.swap_out_rate) {
+        swap_str.push_str(&format!(
+            " | Entrada: {:.2} MB/s, Salida: {:.2} MB/s",
+            swap_in / (1024.0 * 1024.0),
+            swap_out / (1024.0 * 1024.0)
+        ));
     }
+
+    format!(
+        "=== Información del sistema ===\n\
+         Fecha y hora: {}\n\
+         CPU Uso (por núcleo): {}\n\
+         CPU Uso (promedio total): {:.2}%\n\
+         CPU Frecuencia: {}\n\
+         Carga promedio: {:.2} {:.2} {:.2} | Encendido hace: {}\n\
+         Memoria total: {}, Memoria usada: {}, Memoria disponible: {}\n\
+         {}\
+         Swap: {}\n\
+         Presión (10s/1m/5m %): {}\n\
+         Procesos más demandantes de CPU:\n{}\n\
+         Procesos más demandantes de RAM:\n{}\n\
+         Cgroups más demandantes de CPU:\n{}\n\
+         Discos:\n{}\n\
+         Temperaturas:\n{}\n\
+         Redes:\n{}\n\
+         Datos insertados en SQLite exitosamente.\n\
+         =====================================\n\n",
+        timestamp, cpu_usage_str, sample.cpu_total, frequency_str, load1, load5, load15, uptime, total_memory_mb, used_memory_mb, available_memory_mb, cgroup_str, swap_str, pressure_str, process_cpu_info_str, process_mem_info_str, cgroup_info_str, disk_info_str, temperature_str, network_info_str
+    )
 }
 
 fn main() -> Result<()> {
-    // Lanzar el hilo de monitoreo (no bloqueante)
-    spawn(|| {
-        if let Err(e) = run_monitoring() {
-            eprintln!("Error en monitoreo: {}", e);
+    let cli = Cli::parse();
+    let command = match cli.command {
+        Some(command) => command,
+        None if cli.headless => Command::Run,
+        None => Command::Gui,
+    };
+
+    let overrides = Overrides {
+        db_path: cli.global.db,
+        log_path: cli.global.log,
+        interval: cli.global.interval,
+    };
+    let (config, watch_path) = match Config::load(cli.global.config.as_deref(), &overrides) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error de configuración: {}", e);
+            std::process::exit(2);
         }
-    });
+    };
+
+    match command {
+        Command::Run | Command::Gui => {
+            let shutdown = Arc::new(AtomicBool::new(false));
+            let shared = Arc::new(SharedConfig::new(config));
+            let watcher = watch_path.map(|path| {
+                config::spawn_watcher(path, overrides, Arc::clone(&shared), Arc::clone(&shutdown))
+            });
+
+            let result = if matches!(command, Command::Run) {
+                run_headless(&shared, &shutdown)
+            } else {
+                run_gui(&shared, &shutdown)
+            };
+
+            shutdown.store(true, Ordering::SeqCst);
+            if let Some(watcher) = watcher {
+                let _ = watcher.join();
+            }
+            result
+        }
+        Command::Query { since, limit } => run_query(&config, since, limit),
+        Command::Export { format, since, output } => {
+            run_export(&config, format, since, output.as_deref())
+        }
+        Command::Alerts { command: AlertsCommand::List } => {
+            list_alerts(&config);
+            Ok(())
+        }
+        Command::Alerts { command: AlertsCommand::History { since, limit } } => {
+            alert_history(&config, since, limit)
+        }
+        Command::Alerts {
+            command: AlertsCommand::DryRun { id, expr, clear, for_duration, for_samples, process, since },
+        } => {
+            let condition = expr.map(|expr| AlertCondition {
+                id: Some("expr".to_string()),
+                expr: Some(expr),
+                clear: clear.map(Clear::Expr),
+                process: process.map(|name| ProcessMatcher {
+                    name: Some(name),
+                    ..ProcessMatcher::default()
+                }),
+                for_duration: for_duration.unwrap_or_default(),
+                for_samples: for_samples.unwrap_or(1).max(1),
+                metric: None,
+                threshold: None,
+                notify: None,
+            });
+            alerts_dry_run(&config, condition, id.as_deref(), since)
+        }
+        Command::Alerts { command: AlertsCommand::Processes { since, limit } } => {
+            process_history(&config, since, limit)
+        }
+        Command::Alerts { command: AlertsCommand::Kills { since, limit } } => kill_history(&config, since, limit),
+        Command::Alerts { command: AlertsCommand::Series } => {
+            list_series();
+            Ok(())
+        }
+        Command::Autostart { action, backend } => {
+            if let Err(e) = run_autostart_command(action, backend.as_deref()) {
+                eprintln!("Error de inicio automático: {}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn run_gui(shared: &Arc<SharedConfig>, shutdown: &Arc<AtomicBool>) -> Result<()> {
+    let bus = Arc::new(SampleBus::default());
+    let gui_samples = bus.subscribe_lossy("Ventana");
+    let (audit, kills) = mpsc::channel();
+
+    // Lanzar el hilo de monitoreo (no bloqueante)
+    let monitor = {
+        let shutdown = Arc::clone(shutdown);
+        let shared = Arc::clone(shared);
+        let bus = Arc::clone(&bus);
+        spawn(move || {
+            if let Err(e) = run_monitoring(&shutdown, &shared, &bus, kills) {
+                eprintln!("Error en monitoreo: {}", e);
+            }
+        })
+    };
 
     // Ejecutar la GUI en el hilo principal (sin spawn)
     let options = eframe::NativeOptions {
@@ -243,12 +800,32 @@ fn main() -> Result<()> {
     let result = eframe::run_native(
         "Monitor de Sistema",
         options,
-        Box::new(|_cc| Box::new(gui::MonitorApp::default())),
+        Box::new(|_cc| Box::new(gui::MonitorApp::new(gui_samples, audit))),
     );
 
     if let Err(err) = result {
         eprintln!("Error en la GUI: {}", err);
     }
 
+    // Al cerrar la ventana se detiene también la recolección
+    shutdown.store(true, Ordering::SeqCst);
+    let _ = monitor.join();
+
     Ok(())
 }
+
+// Solo recolección, alertas y almacenamiento; sin ventana.
+fn run_headless(shared: &SharedConfig, shutdown: &Arc<AtomicBool>) -> Result<()> {
+    for signal in [SIGINT, SIGTERM] {
+        // Una segunda señal mientras se cierra termina el proceso de inmediato
+        let registered = signal_hook::flag::register_conditional_shutdown(signal, 1, Arc::clone(shutdown))
+            .and_then(|_| signal_hook::flag::register(signal, Arc::clone(shutdown)));
+        if let Err(e) = registered {
+            eprintln!("No se pudo registrar el manejador de señales: {}", e);
+        }
+    }
+
+    // Sin ventana no se envían señales
+    let (_, kills) = mpsc::channel();
+    run_monitoring(shutdown, shared, &SampleBus::default(), kills)
+}
@@ -0,0 +1,169 @@
+use serde::Deserialize;
+use std::time::Instant;
+use sysinfo::Networks;
+
+// Filtro de interfaces por nombre. Los patrones admiten `*` y `?`.
+// Una lista `include` vacía acepta todas las interfaces.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterfaceFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl Default for InterfaceFilter {
+    fn default() -> Self {
+        InterfaceFilter {
+            include: Vec::new(),
+            exclude: vec![
+                "lo".to_string(),
+                "lo0".to_string(),
+                "Loopback*".to_string(),
+                "veth*".to_string(),
+            ],
+        }
+    }
+}
+
+impl InterfaceFilter {
+    pub fn matches(&self, name: &str) -> bool {
+        let included = self.include.is_empty()
+            || self.include.iter().any(|pattern| glob_match(pattern, name));
+        included && !self.exclude.iter().any(|pattern| glob_match(pattern, name))
+    }
+}
+
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[derive(Clone, Debug)]
+pub struct InterfaceRate {
+    pub name: String,
+    pub rx_mbps: f64,
+    pub tx_mbps: f64,
+}
+
+pub struct NetworkSampler {
+    networks: Networks,
+    filter: InterfaceFilter,
+    last_refresh: Instant,
+}
+
+impl NetworkSampler {
+    pub fn new(filter: InterfaceFilter) -> Self {
+        NetworkSampler {
+            networks: Networks::new_with_refreshed_list(),
+            filter,
+            last_refresh: Instant::now(),
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: InterfaceFilter) {
+        self.filter = filter;
+    }
+
+    // Tasa de cada interfaz aceptada desde la llamada anterior, ordenadas por nombre.
+    pub fn sample(&mut self) -> Vec<InterfaceRate> {
+        self.networks.refresh(true);
+        let now = Instant::now();
+        let secs = now.duration_since(self.last_refresh).as_secs_f64().max(0.001);
+        self.last_refresh = now;
+
+        let mut rates: Vec<InterfaceRate> = self
+            .networks
+            .iter()
+            .filter(|(name, _)| self.filter.matches(name))
+            .map(|(name, data)| InterfaceRate {
+                name: name.clone(),
+                rx_mbps: (data.received() as f64 * 8.0) / (1024.0 * 1024.0 * secs),
+                tx_mbps: (data.transmitted() as f64 * 8.0) / (1024.0 * 1024.0 * secs),
+            })
+            .collect();
+        rates.sort_by(|a, b| a.name.cmp(&b.name));
+        rates
+    }
+}
+
+pub fn totals(rates: &[InterfaceRate]) -> (f64, f64) {
+    rates
+        .iter()
+        .fold((0.0, 0.0), |(rx, tx), r| (rx + r.rx_mbps, tx + r.tx_mbps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(include: &[&str], exclude: &[&str]) -> InterfaceFilter {
+        InterfaceFilter {
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("eth*", "eth0"));
+        assert!(glob_match("eth*", "eth"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*0", "wlan0"));
+        assert!(glob_match("e*h*0", "enp3s0eth0"));
+        assert!(!glob_match("eth*", "veth0"));
+        assert!(glob_match("wlan?", "wlan1"));
+        assert!(!glob_match("wlan?", "wlan"));
+        assert!(!glob_match("wlan?", "wlan10"));
+        assert!(glob_match("?*", "x"));
+        assert!(!glob_match("?*", ""));
+    }
+
+    #[test]
+    fn exact_names() {
+        assert!(glob_match("lo", "lo"));
+        assert!(!glob_match("lo", "lo0"));
+        assert!(!glob_match("lo", "l"));
+        assert!(!glob_match("", "lo"));
+    }
+
+    #[test]
+    fn empty_include_takes_everything_not_excluded() {
+        let default = InterfaceFilter::default();
+        assert!(default.matches("eth0"));
+        assert!(default.matches("wlp2s0"));
+        assert!(!default.matches("lo"));
+        assert!(!default.matches("veth1a2b"));
+        assert!(!default.matches("Loopback Pseudo-Interface 1"));
+        assert!(filter(&[], &[]).matches("lo"));
+    }
+
+    #[test]
+    fn exclude_wins_over_include() {
+        let filter = filter(&["eth*", "wlan*"], &["eth1"]);
+        assert!(filter.matches("eth0"));
+        assert!(filter.matches("wlan0"));
+        assert!(!filter.matches("eth1"));
+        assert!(!filter.matches("docker0"));
+    }
+}
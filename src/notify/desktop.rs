@@ -0,0 +1,34 @@
+use std::io;
+
+use notify_rust::Notification;
+
+use super::{Message, NotificationSink};
+
+pub struct Desktop {
+    appname: String,
+}
+
+impl Desktop {
+    pub fn new(appname: &str) -> Self {
+        Desktop {
+            appname: appname.to_string(),
+        }
+    }
+}
+
+impl NotificationSink for Desktop {
+    fn kind(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        Notification::new()
+            .appname(&self.appname)
+            .icon("warning")
+            .summary(&message.summary)
+            .body(&message.body)
+            .show()
+            .map(|_| ())
+            .map_err(io::Error::other)
+    }
+}
@@ -0,0 +1,166 @@
+use std::io;
+use std::time::Duration;
+
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+
+use super::{fields, Message, NotificationSink, TlsMode};
+
+pub struct Email {
+    transport: SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl Email {
+    pub fn new(
+        server: &str,
+        port: Option<u16>,
+        tls: TlsMode,
+        credentials: Option<(String, String)>,
+        from: &str,
+        to: &[String],
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        let (builder, default_port) = match tls {
+            TlsMode::None => (SmtpTransport::builder_dangerous(server), 25),
+            TlsMode::Starttls => (SmtpTransport::starttls_relay(server).map_err(io::Error::other)?, 587),
+            TlsMode::Tls => (SmtpTransport::relay(server).map_err(io::Error::other)?, 465),
+        };
+        let mut builder = builder.port(port.unwrap_or(default_port)).timeout(Some(timeout));
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let parse = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{}: {}", address, e)))
+        };
+        Ok(Email {
+            transport: builder.build(),
+            from: parse(from)?,
+            to: to.iter().map(|a| parse(a)).collect::<io::Result<_>>()?,
+        })
+    }
+}
+
+impl NotificationSink for Email {
+    fn kind(&self) -> &'static str {
+        "email"
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        let mut body = format!("{}\n\n", message.body);
+        for (key, value) in fields(message) {
+            if !value.is_empty() {
+                body.push_str(&format!("{}: {}\n", key, value));
+            }
+        }
+
+        let mut email = lettre::Message::builder()
+            .from(self.from.clone())
+            .subject(&message.summary)
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            email = email.to(to.clone());
+        }
+        let email = email.body(body).map_err(io::Error::other)?;
+
+        self.transport.send(&email).map(|_| ()).map_err(io::Error::other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+    use crate::notify::test_message;
+
+    // Servidor SMTP mínimo que acepta todo y devuelve los comandos y el
+    // contenido de DATA.
+    fn smtp_stand_in() -> (u16, mpsc::Receiver<(Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let (mut commands, mut data) = (Vec::new(), String::new());
+            stream.write_all(b"220 localhost ESMTP\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let command = line.trim_end().to_string();
+                let verb = command.split([' ', ':']).next().unwrap_or_default().to_uppercase();
+                commands.push(command);
+                match verb.as_str() {
+                    "EHLO" => stream.write_all(b"250-localhost\r\n250 8BITMIME\r\n").unwrap(),
+                    "DATA" => {
+                        stream.write_all(b"354 Fin con <CRLF>.<CRLF>\r\n").unwrap();
+                        loop {
+                            let mut line = String::new();
+                            reader.read_line(&mut line).unwrap();
+                            if line == ".\r\n" {
+                                break;
+                            }
+                            data.push_str(&line);
+                        }
+                        stream.write_all(b"250 Encolado\r\n").unwrap();
+                    }
+                    "QUIT" => {
+                        stream.write_all(b"221 Chau\r\n").unwrap();
+                        break;
+                    }
+                    _ => stream.write_all(b"250 OK\r\n").unwrap(),
+                }
+            }
+            sender.send((commands, data)).unwrap();
+        });
+        (port, receiver)
+    }
+
+    #[test]
+    fn sends_mail_through_smtp_exchange() {
+        let (port, received) = smtp_stand_in();
+        let email = Email::new(
+            "127.0.0.1",
+            Some(port),
+            TlsMode::None,
+            None,
+            "Monitor <monitor@example.com>",
+            &["guardia@example.com".to_string(), "otro@example.com".to_string()],
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        email.send(&test_message()).unwrap();
+
+        let (commands, data) = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        let verbs: Vec<&str> = commands.iter().map(|c| c.split(' ').next().unwrap()).collect();
+        assert_eq!(verbs, ["EHLO", "MAIL", "RCPT", "RCPT", "DATA", "QUIT"], "{:?}", commands);
+        assert!(commands[1].starts_with("MAIL FROM:<monitor@example.com>"), "{:?}", commands);
+        assert_eq!(commands[2], "RCPT TO:<guardia@example.com>");
+        assert_eq!(commands[3], "RCPT TO:<otro@example.com>");
+
+        assert!(data.contains("Subject: CPU alta\r\n"), "{}", data);
+        assert!(data.contains("From: Monitor <monitor@example.com>\r\n"), "{}", data);
+        assert!(data.contains("CPU al 97.50%"), "{}", data);
+        assert!(data.contains("rule_id: cpu"), "{}", data);
+        assert!(data.contains("top_offender: stress (4242)"), "{}", data);
+        // Los campos vacíos no se incluyen
+        assert!(!data.contains("resolved_at"), "{}", data);
+    }
+
+    #[test]
+    fn rejects_invalid_address() {
+        let result = Email::new("127.0.0.1", Some(25), TlsMode::None, None, "sin arroba", &[], Duration::from_secs(1));
+        assert_eq!(result.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidInput));
+    }
+}
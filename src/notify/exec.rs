@@ -0,0 +1,98 @@
+use std::io;
+use std::process::{Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::{fields, Message, NotificationSink};
+
+// Cada campo se pasa como METRICAS_<CAMPO>, además de METRICAS_SUMMARY y
+// METRICAS_BODY.
+pub struct Exec {
+    command: Vec<String>,
+    timeout: Duration,
+}
+
+// Cada cuánto se revisa si el comando terminó
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl Exec {
+    pub fn new(command: Vec<String>, timeout: Duration) -> Self {
+        Exec { command, timeout }
+    }
+}
+
+// Ejecuta el comando y lo mata si no termina en `timeout`: un comando
+// colgado no debe bloquear para siempre el hilo que lo espera.
+pub fn run(command: &mut Command, timeout: Duration) -> io::Result<ExitStatus> {
+    let mut child = command.spawn()?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            let program = command.get_program().to_string_lossy();
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("{} no terminó en {:?}; se lo mató", program, timeout),
+            ));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+impl NotificationSink for Exec {
+    fn kind(&self) -> &'static str {
+        "exec"
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        let mut command = Command::new(&self.command[0]);
+        command.args(&self.command[1..]).stdin(Stdio::null());
+        for (key, value) in fields(message) {
+            command.env(format!("METRICAS_{}", key.to_uppercase()), value);
+        }
+        command
+            .env("METRICAS_SUMMARY", &message.summary)
+            .env("METRICAS_BODY", &message.body);
+
+        let status = run(&mut command, self.timeout)?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("{} terminó con {}", self.command[0], status)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_message;
+
+    fn exec(script: &str, timeout: Duration) -> Exec {
+        Exec::new(vec!["sh".to_string(), "-c".to_string(), script.to_string()], timeout)
+    }
+
+    #[test]
+    fn passes_fields_as_environment() {
+        let script = r#"test "$METRICAS_RULE_ID" = cpu && test "$METRICAS_VALUE" = 97.50 && test "$METRICAS_SUMMARY" = "CPU alta""#;
+        exec(script, Duration::from_secs(5)).send(&test_message()).unwrap();
+    }
+
+    #[test]
+    fn reports_failure_status() {
+        let error = exec("exit 3", Duration::from_secs(5)).send(&test_message()).unwrap_err();
+        assert!(error.to_string().contains("sh terminó con"), "{}", error);
+    }
+
+    #[test]
+    fn kills_command_after_timeout() {
+        let started = Instant::now();
+        let error = exec("exec sleep 10", Duration::from_millis(200)).send(&test_message()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::alert::AlertRecord;
+use crate::config::{deserialize_duration, NotificationsConfig};
+
+mod desktop;
+mod email;
+pub mod exec;
+mod webhook;
+
+// Nombre reservado del aviso de escritorio, que siempre está disponible.
+pub const DESKTOP: &str = "desktop";
+
+// Lo que recibe cada destino al activarse o resolverse una alerta.
+#[derive(Clone, Debug)]
+pub struct Message {
+    // `firing` o `resolved` en las alertas; `started`, `exited` o `restarted`
+    // en los procesos vigilados
+    pub event: &'static str,
+    pub summary: String,
+    pub body: String,
+    pub record: AlertRecord,
+    // Valor de la muestra que provocó el aviso (al resolverse, el que bajó de `clear`)
+    pub current_value: f32,
+}
+
+pub trait NotificationSink: Send + Sync {
+    fn kind(&self) -> &'static str;
+    fn send(&self, message: &Message) -> io::Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum SinkConfig {
+    // POST con el aviso en JSON
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+        #[serde(default = "default_timeout", deserialize_with = "deserialize_duration")]
+        timeout: Duration,
+    },
+    Email {
+        server: String,
+        // Por defecto 25 sin TLS, 587 con STARTTLS y 465 con TLS
+        port: Option<u16>,
+        #[serde(default)]
+        tls: TlsMode,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+        to: Vec<String>,
+        #[serde(default = "default_timeout", deserialize_with = "deserialize_duration")]
+        timeout: Duration,
+    },
+    // Ejecuta el comando (sin shell) con los datos del aviso en variables de entorno
+    Exec {
+        command: Vec<String>,
+        // Pasado este tiempo se mata el comando
+        #[serde(default = "default_timeout", deserialize_with = "deserialize_duration")]
+        timeout: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsMode {
+    None,
+    #[default]
+    Starttls,
+    Tls,
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+impl SinkConfig {
+    // Revisión que no requiere red; los errores de conexión se informan al enviar.
+    pub fn validate(&self) -> Result<(), (&'static str, String)> {
+        match self {
+            SinkConfig::Webhook { url, .. } => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(("url", format!("debe empezar con http:// o https:// (valor: {:?})", url)));
+                }
+            }
+            SinkConfig::Email { server, from, to, username, password, .. } => {
+                if server.trim().is_empty() {
+                    return Err(("server", "no puede estar vacío".into()));
+                }
+                if let Err(e) = from.parse::<lettre::message::Mailbox>() {
+                    return Err(("from", format!("dirección inválida: {}", e)));
+                }
+                if to.is_empty() {
+                    return Err(("to", "debe tener al menos un destinatario".into()));
+                }
+                if let Some(address) = to.iter().find(|a| a.parse::<lettre::message::Mailbox>().is_err()) {
+                    return Err(("to", format!("dirección inválida: {:?}", address)));
+                }
+                if username.is_some() != password.is_some() {
+                    return Err(("password", "username y password van juntos".into()));
+                }
+            }
+            SinkConfig::Exec { command, .. } => {
+                if command.first().is_none_or(|c| c.trim().is_empty()) {
+                    return Err(("command", "no puede estar vacío".into()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn build(config: &SinkConfig) -> io::Result<Arc<dyn NotificationSink>> {
+    Ok(match config {
+        SinkConfig::Webhook { url, headers, timeout } => {
+            Arc::new(webhook::Webhook::new(url, headers.clone(), *timeout))
+        }
+        SinkConfig::Email { server, port, tls, username, password, from, to, timeout } => {
+            let credentials = username.clone().zip(password.clone());
+            Arc::new(email::Email::new(server, *port, *tls, credentials, from, to, *timeout)?)
+        }
+        SinkConfig::Exec { command, timeout } => Arc::new(exec::Exec::new(command.clone(), *timeout)),
+    })
+}
+
+// Reparte cada aviso entre los destinos elegidos por la regla. Los envíos se
+// hacen en otro hilo para no demorar el almacenamiento de las muestras.
+pub struct Notifier {
+    enabled: bool,
+    default: Vec<String>,
+    sinks: HashMap<String, Arc<dyn NotificationSink>>,
+}
+
+impl Notifier {
+    pub fn new(config: &NotificationsConfig) -> Self {
+        let mut sinks: HashMap<String, Arc<dyn NotificationSink>> = HashMap::new();
+        for (name, sink) in &config.sinks {
+            match build(sink) {
+                Ok(sink) => {
+                    sinks.insert(name.clone(), sink);
+                }
+                Err(e) => eprintln!("Destino de notificación `{}` deshabilitado: {}", name, e),
+            }
+        }
+        sinks.insert(DESKTOP.to_string(), Arc::new(desktop::Desktop::new(&config.appname)));
+
+        Notifier {
+            enabled: config.enabled,
+            default: config.default.clone(),
+            sinks,
+        }
+    }
+
+    // Sin `targets` se usan los destinos por defecto de `[notifications]`.
+    pub fn send(&self, targets: Option<&[String]>, message: Message) {
+        if !self.enabled {
+            return;
+        }
+        let targets: Vec<(String, Arc<dyn NotificationSink>)> = targets
+            .unwrap_or(&self.default)
+            .iter()
+            .filter_map(|name| Some((name.clone(), Arc::clone(self.sinks.get(name)?))))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        thread::spawn(move || {
+            for (name, sink) in targets {
+                if let Err(e) = sink.send(&message) {
+                    eprintln!("No se pudo notificar por `{}` ({}): {}", name, sink.kind(), e);
+                }
+            }
+        });
+    }
+}
+
+// Variables comunes a todos los destinos, en el orden en que se muestran.
+fn fields(message: &Message) -> Vec<(&'static str, String)> {
+    let record = &message.record;
+    let time = |t: &chrono::DateTime<chrono::Local>| t.to_rfc3339();
+    vec![
+        ("event", message.event.to_string()),
+        ("state", record.state.as_str().to_string()),
+        ("rule_id", record.rule_id.clone()),
+        ("metric", record.metric.clone()),
+        ("value", format!("{:.2}", record.value)),
+        ("threshold", format!("{:.2}", record.threshold)),
+        ("current_value", format!("{:.2}", message.current_value)),
+        ("started_at", time(&record.started_at)),
+        ("resolved_at", record.resolved_at.as_ref().map(time).unwrap_or_default()),
+        ("top_offender", record.top_offender.clone().unwrap_or_default()),
+        ("host", sysinfo::System::host_name().unwrap_or_default()),
+    ]
+}
+
+// Aviso de una alerta de CPU activa, para las pruebas de los destinos.
+#[cfg(test)]
+pub fn test_message() -> Message {
+    use crate::alert::AlertState;
+
+    Message {
+        event: "firing",
+        summary: "CPU alta".to_string(),
+        body: "CPU al 97.50%".to_string(),
+        record: AlertRecord {
+            rule_id: "cpu".to_string(),
+            metric: "cpu".to_string(),
+            value: 97.5,
+            threshold: 90.0,
+            started_at: chrono::Local::now(),
+            resolved_at: None,
+            top_offender: Some("stress (4242)".to_string()),
+            state: AlertState::Firing,
+        },
+        current_value: 97.5,
+    }
+}
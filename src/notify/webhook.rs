@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::time::Duration;
+
+use serde_json::{Map, Value};
+
+use super::{fields, Message, NotificationSink};
+
+pub struct Webhook {
+    url: String,
+    headers: BTreeMap<String, String>,
+    agent: ureq::Agent,
+}
+
+impl Webhook {
+    pub fn new(url: &str, headers: BTreeMap<String, String>, timeout: Duration) -> Self {
+        Webhook {
+            url: url.to_string(),
+            headers,
+            agent: ureq::AgentBuilder::new().timeout(timeout).build(),
+        }
+    }
+}
+
+// {"state": "firing", "rule_id": "cpu", ..., "summary": "...", "body": "..."}.
+// Los valores numéricos van como números y los vacíos como null.
+fn payload(message: &Message) -> Value {
+    let mut object = Map::new();
+    for (key, value) in fields(message) {
+        let value = match key {
+            "value" | "threshold" | "current_value" => value
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            _ if value.is_empty() => Value::Null,
+            _ => Value::String(value),
+        };
+        object.insert(key.to_string(), value);
+    }
+    object.insert("summary".to_string(), Value::String(message.summary.clone()));
+    object.insert("body".to_string(), Value::String(message.body.clone()));
+    Value::Object(object)
+}
+
+impl NotificationSink for Webhook {
+    fn kind(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn send(&self, message: &Message) -> io::Result<()> {
+        let mut request = self.agent.post(&self.url);
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        match request.send_json(payload(message)) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(code, response)) => Err(io::Error::other(format!(
+                "{} respondió {} {}",
+                self.url,
+                code,
+                response.status_text()
+            ))),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use super::*;
+    use crate::notify::test_message;
+
+    // Servidor HTTP de una sola petición: responde `status` y devuelve
+    // las cabeceras (en minúsculas) y el cuerpo recibidos.
+    fn serve_once(status: &'static str) -> (String, mpsc::Receiver<(String, Vec<String>, String)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks/abc", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                headers.push(line.to_lowercase());
+            }
+            let length: usize = headers
+                .iter()
+                .find_map(|h| h.strip_prefix("content-length: "))
+                .and_then(|l| l.parse().ok())
+                .unwrap_or_default();
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = stream;
+            write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).unwrap();
+            sender.send((request_line.trim_end().to_string(), headers, String::from_utf8(body).unwrap())).unwrap();
+        });
+        (url, receiver)
+    }
+
+    #[test]
+    fn posts_json_with_headers() {
+        let (url, received) = serve_once("200 OK");
+        let headers = BTreeMap::from([("Authorization".to_string(), "Bearer token".to_string())]);
+        Webhook::new(&url, headers, Duration::from_secs(5)).send(&test_message()).unwrap();
+
+        let (request_line, headers, body) = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request_line, "POST /hooks/abc HTTP/1.1");
+        assert!(headers.contains(&"content-type: application/json".to_string()), "{:?}", headers);
+        assert!(headers.contains(&"authorization: bearer token".to_string()), "{:?}", headers);
+
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body["event"], "firing");
+        assert_eq!(body["state"], "firing");
+        assert_eq!(body["rule_id"], "cpu");
+        assert_eq!(body["value"], 97.5);
+        assert_eq!(body["threshold"], 90.0);
+        assert_eq!(body["resolved_at"], Value::Null);
+        assert_eq!(body["top_offender"], "stress (4242)");
+        assert_eq!(body["summary"], "CPU alta");
+        assert_eq!(body["body"], "CPU al 97.50%");
+    }
+
+    #[test]
+    fn reports_error_status() {
+        let (url, _received) = serve_once("503 Service Unavailable");
+        let error = Webhook::new(&url, BTreeMap::new(), Duration::from_secs(5))
+            .send(&test_message())
+            .unwrap_err();
+        assert!(error.to_string().contains("503"), "{}", error);
+    }
+}
@@ -0,0 +1,137 @@
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::collector::ProcessSample;
+use crate::network::glob_match;
+
+// Selección de procesos. Todos los criterios indicados deben cumplirse.
+// `name` y `exe` admiten `*` y `?`; `cmdline` es una expresión regular.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessMatcher {
+    pub name: Option<String>,
+    pub pid: Option<u32>,
+    pub exe: Option<String>,
+    pub cmdline: Option<String>,
+    pub user: Option<String>,
+}
+
+impl ProcessMatcher {
+    pub fn is_empty(&self) -> bool {
+        *self == ProcessMatcher::default()
+    }
+
+    pub fn validate(&self) -> Result<(), (&'static str, String)> {
+        if let Some(pattern) = &self.cmdline {
+            Regex::new(pattern).map_err(|e| ("cmdline", format!("expresión regular inválida: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub fn compile(&self) -> Result<Matcher, regex::Error> {
+        Ok(Matcher {
+            config: self.clone(),
+            cmdline: self.cmdline.as_deref().map(Regex::new).transpose()?,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(name) = &self.name {
+            parts.push(format!("name={:?}", name));
+        }
+        if let Some(pid) = self.pid {
+            parts.push(format!("pid={}", pid));
+        }
+        if let Some(exe) = &self.exe {
+            parts.push(format!("exe={:?}", exe));
+        }
+        if let Some(cmdline) = &self.cmdline {
+            parts.push(format!("cmdline=/{}/", cmdline));
+        }
+        if let Some(user) = &self.user {
+            parts.push(format!("user={:?}", user));
+        }
+        parts.join(" ")
+    }
+}
+
+pub struct Matcher {
+    config: ProcessMatcher,
+    cmdline: Option<Regex>,
+}
+
+impl Matcher {
+    pub fn matches(&self, process: &ProcessSample) -> bool {
+        let config = &self.config;
+        config.name.as_ref().is_none_or(|p| glob_match(p, &process.name))
+            && config.pid.is_none_or(|pid| pid == process.pid)
+            && config.exe.as_ref().is_none_or(|p| glob_match(p, &process.exe))
+            && self.cmdline.as_ref().is_none_or(|re| re.is_match(&process.cmdline))
+            && config.user.as_ref().is_none_or(|u| process.user.as_ref() == Some(u))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::test_process;
+
+    fn postgres() -> ProcessSample {
+        let mut process = test_process(4321, "postgres", 1.0, 100);
+        process.exe = "/usr/lib/postgresql/16/bin/postgres".to_string();
+        process.cmdline = "/usr/lib/postgresql/16/bin/postgres -D /var/lib/postgresql/16/main".to_string();
+        process.user = Some("postgres".to_string());
+        process
+    }
+
+    fn matches(config: ProcessMatcher) -> bool {
+        config.compile().unwrap().matches(&postgres())
+    }
+
+    #[test]
+    fn each_criterion() {
+        assert!(matches(ProcessMatcher { name: Some("post*".into()), ..Default::default() }));
+        assert!(!matches(ProcessMatcher { name: Some("post".into()), ..Default::default() }));
+        assert!(matches(ProcessMatcher { exe: Some("*/bin/postgres".into()), ..Default::default() }));
+        assert!(!matches(ProcessMatcher { exe: Some("/usr/bin/*".into()), ..Default::default() }));
+        assert!(matches(ProcessMatcher { cmdline: Some(r"-D \S+/main$".into()), ..Default::default() }));
+        assert!(!matches(ProcessMatcher { cmdline: Some("^postgres".into()), ..Default::default() }));
+        assert!(matches(ProcessMatcher { user: Some("postgres".into()), ..Default::default() }));
+        assert!(!matches(ProcessMatcher { user: Some("root".into()), ..Default::default() }));
+        assert!(matches(ProcessMatcher { pid: Some(4321), ..Default::default() }));
+        assert!(!matches(ProcessMatcher { pid: Some(1), ..Default::default() }));
+    }
+
+    #[test]
+    fn all_criteria_must_hold() {
+        let all = ProcessMatcher {
+            name: Some("postgres".into()),
+            pid: Some(4321),
+            exe: Some("*postgres".into()),
+            cmdline: Some("main".into()),
+            user: Some("postgres".into()),
+        };
+        assert!(matches(all.clone()));
+        assert!(!matches(ProcessMatcher { pid: Some(1), ..all.clone() }));
+        assert!(!matches(ProcessMatcher { user: Some("root".into()), ..all.clone() }));
+        assert!(!matches(ProcessMatcher { cmdline: Some("replica".into()), ..all }));
+    }
+
+    #[test]
+    fn unknown_user_does_not_match() {
+        let mut process = postgres();
+        process.user = None;
+        let matcher = ProcessMatcher { user: Some("postgres".into()), ..Default::default() };
+        assert!(!matcher.compile().unwrap().matches(&process));
+    }
+
+    #[test]
+    fn empty_and_invalid() {
+        assert!(ProcessMatcher::default().is_empty());
+        assert!(!ProcessMatcher { pid: Some(0), ..Default::default() }.is_empty());
+        let bad = ProcessMatcher { cmdline: Some("(".into()), ..Default::default() };
+        assert_eq!(bad.validate().unwrap_err().0, "cmdline");
+        assert!(bad.compile().is_err());
+    }
+}
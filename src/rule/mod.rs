@@ -0,0 +1,403 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+
+use crate::collector::{ProcessSample, Sample};
+use crate::network::glob_match;
+use crate::process::Matcher;
+
+mod parser;
+pub mod series;
+
+pub use parser::ParseError;
+use series::{Combine, Series};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Avg,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+}
+
+// Serie con un patrón opcional (`*` y `?`) sobre su etiqueta.
+#[derive(Debug)]
+pub struct Selector {
+    series: &'static Series,
+    pattern: Option<String>,
+}
+
+impl Selector {
+    // Sin valores que coincidan (ej. un punto de montaje ausente) no hay dato.
+    fn value(&self, input: &Input) -> Option<f64> {
+        let values = (self.series.read)(input.sample, &input.processes)
+            .into_iter()
+            .filter(|(label, _)| self.pattern.as_ref().is_none_or(|p| glob_match(p, label)))
+            .map(|(_, value)| value);
+        match self.series.combine {
+            Combine::Sum => values.reduce(|a, b| a + b),
+            Combine::Max => values.reduce(f64::max),
+            Combine::Min => values.reduce(f64::min),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Number(f64),
+    Series(Selector),
+    // `slot` es el índice del historial de la ventana dentro de la regla
+    Window {
+        aggregate: Aggregate,
+        selector: Selector,
+        window: Duration,
+        slot: usize,
+    },
+    Neg(Box<Expr>),
+    Arith(Box<Expr>, ArithOp, Box<Expr>),
+    Compare(Box<Expr>, CmpOp, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+// Comprueba la sintaxis, las series y las etiquetas sin construir una regla.
+// `process` indica si la condición elige procesos.
+pub fn check(text: &str, process: bool) -> Result<(), ParseError> {
+    parser::parse(text, 0, process).map(|_| ())
+}
+
+// Resultado de evaluar una expresión sobre una muestra. `value` y `threshold`
+// son los dos lados de la primera comparación, para informar el aviso.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Outcome {
+    pub active: bool,
+    pub value: Option<f64>,
+    pub threshold: Option<f64>,
+}
+
+// Condición de disparo y de resolución con el historial de sus ventanas.
+pub struct Rule {
+    trigger: Expr,
+    clear: Option<Expr>,
+    windows: Vec<VecDeque<(DateTime<Local>, f64)>>,
+    process: Option<Matcher>,
+}
+
+// La muestra junto con los procesos elegidos por la regla, filtrados una vez.
+struct Input<'a> {
+    sample: &'a Sample,
+    processes: Vec<&'a ProcessSample>,
+}
+
+impl Rule {
+    // Sin `clear` la regla se resuelve cuando `trigger` deja de cumplirse.
+    pub fn new(trigger: &str, clear: Option<&str>, process: Option<Matcher>) -> Result<Rule, ParseError> {
+        let scoped = process.is_some();
+        let (trigger, slots) = parser::parse(trigger, 0, scoped)?;
+        let (clear, slots) = match clear {
+            Some(text) => {
+                let (expr, slots) = parser::parse(text, slots, scoped)?;
+                (Some(expr), slots)
+            }
+            None => (None, slots),
+        };
+        Ok(Rule {
+            trigger,
+            clear,
+            windows: vec![VecDeque::new(); slots],
+            process,
+        })
+    }
+
+    // Patrones de la etiqueta `label` que usa la regla, ej. los `mount` de
+    // `disk_free_pct{mount="/data"}`, para informar el disco afectado.
+    pub fn patterns(&self, label: &str) -> Vec<&str> {
+        let mut patterns = Vec::new();
+        for expr in std::iter::once(&self.trigger).chain(&self.clear) {
+            collect_patterns(expr, label, &mut patterns);
+        }
+        patterns
+    }
+
+    // Series que usa la regla en el orden en que aparecen, primero las de
+    // disparo y después las de resolución.
+    pub fn series(&self) -> Vec<&'static Series> {
+        let mut series = Vec::new();
+        for expr in std::iter::once(&self.trigger).chain(&self.clear) {
+            collect_series(expr, &mut series);
+        }
+        series
+    }
+
+    // Sin `process` todos los procesos cuentan.
+    pub fn matches(&self, process: &ProcessSample) -> bool {
+        self.process.as_ref().is_none_or(|m| m.matches(process))
+    }
+
+    // Evalúa las dos expresiones para que las ventanas se llenen siempre,
+    // también mientras la alerta no está activa. Devuelve (disparo, resolución).
+    pub fn evaluate(&mut self, sample: &Sample) -> (Outcome, Outcome) {
+        let processes = match &self.process {
+            Some(matcher) => sample.processes.iter().filter(|p| matcher.matches(p)).collect(),
+            None => Vec::new(),
+        };
+        let input = Input { sample, processes };
+        let trigger = evaluate(&self.trigger, &input, &mut self.windows);
+        let clear = match &self.clear {
+            Some(expr) => evaluate(expr, &input, &mut self.windows),
+            None => Outcome { active: !trigger.active, ..trigger },
+        };
+        (trigger, clear)
+    }
+}
+
+fn collect_patterns<'a>(expr: &'a Expr, label: &str, patterns: &mut Vec<&'a str>) {
+    match expr {
+        Expr::Number(_) => {}
+        Expr::Series(selector) | Expr::Window { selector, .. } => {
+            if let (Some(pattern), true) = (&selector.pattern, selector.series.label == Some(label)) {
+                patterns.push(pattern);
+            }
+        }
+        Expr::Neg(inner) | Expr::Not(inner) => collect_patterns(inner, label, patterns),
+        Expr::Arith(left, _, right) | Expr::Compare(left, _, right) | Expr::And(left, right) | Expr::Or(left, right) => {
+            collect_patterns(left, label, patterns);
+            collect_patterns(right, label, patterns);
+        }
+    }
+}
+
+fn collect_series(expr: &Expr, series: &mut Vec<&'static Series>) {
+    match expr {
+        Expr::Number(_) => {}
+        Expr::Series(selector) | Expr::Window { selector, .. } => series.push(selector.series),
+        Expr::Neg(inner) | Expr::Not(inner) => collect_series(inner, series),
+        Expr::Arith(left, _, right) | Expr::Compare(left, _, right) | Expr::And(left, right) | Expr::Or(left, right) => {
+            collect_series(left, series);
+            collect_series(right, series);
+        }
+    }
+}
+
+type Windows = [VecDeque<(DateTime<Local>, f64)>];
+
+fn evaluate(expr: &Expr, input: &Input, windows: &mut Windows) -> Outcome {
+    let mut first = None;
+    let active = truth(expr, input, windows, &mut first);
+    let (value, threshold) = first.unwrap_or_default();
+    Outcome { active, value, threshold }
+}
+
+// Se evalúan siempre ambos lados de `and`/`or`: cortar antes dejaría
+// ventanas sin la muestra actual.
+fn truth(expr: &Expr, input: &Input, windows: &mut Windows, first: &mut Option<(Option<f64>, Option<f64>)>) -> bool {
+    match expr {
+        Expr::Compare(left, op, right) => {
+            let l = number(left, input, windows);
+            let r = number(right, input, windows);
+            first.get_or_insert((l, r));
+            match (l, r) {
+                (Some(l), Some(r)) => match op {
+                    CmpOp::Gt => l > r,
+                    CmpOp::Ge => l >= r,
+                    CmpOp::Lt => l < r,
+                    CmpOp::Le => l <= r,
+                    CmpOp::Eq => l == r,
+                    CmpOp::Ne => l != r,
+                },
+                // Sin dato la comparación no se cumple
+                _ => false,
+            }
+        }
+        Expr::And(left, right) => {
+            let l = truth(left, input, windows, first);
+            let r = truth(right, input, windows, first);
+            l && r
+        }
+        Expr::Or(left, right) => {
+            let l = truth(left, input, windows, first);
+            let r = truth(right, input, windows, first);
+            l || r
+        }
+        Expr::Not(inner) => !truth(inner, input, windows, first),
+        // El parser solo deja números dentro de comparaciones
+        _ => false,
+    }
+}
+
+fn number(expr: &Expr, input: &Input, windows: &mut Windows) -> Option<f64> {
+    match expr {
+        Expr::Number(value) => Some(*value),
+        Expr::Series(selector) => selector.value(input),
+        Expr::Window { aggregate, selector, window, slot } => {
+            let now = input.sample.timestamp;
+            let history = &mut windows[*slot];
+            if let Some(value) = selector.value(input) {
+                history.push_back((now, value));
+            }
+            let window = chrono::Duration::from_std(*window).unwrap_or(chrono::Duration::MAX);
+            let start = now.checked_sub_signed(window);
+            while history.front().is_some_and(|(t, _)| start.is_some_and(|start| *t <= start)) {
+                history.pop_front();
+            }
+
+            let values = history.iter().map(|(_, v)| *v);
+            match aggregate {
+                Aggregate::Avg if !history.is_empty() => Some(values.sum::<f64>() / history.len() as f64),
+                Aggregate::Avg => None,
+                Aggregate::Min => values.reduce(f64::min),
+                Aggregate::Max => values.reduce(f64::max),
+            }
+        }
+        Expr::Neg(inner) => number(inner, input, windows).map(|v| -v),
+        Expr::Arith(left, op, right) => {
+            let l = number(left, input, windows);
+            let r = number(right, input, windows);
+            let (l, r) = (l?, r?);
+            match op {
+                ArithOp::Add => Some(l + r),
+                ArithOp::Sub => Some(l - r),
+                ArithOp::Mul => Some(l * r),
+                ArithOp::Div if r != 0.0 => Some(l / r),
+                ArithOp::Div => None,
+            }
+        }
+        // El parser no deja condiciones donde se espera un número
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration as TimeDelta;
+
+    use super::*;
+    use crate::collector::test_sample;
+
+    // Una muestra por segundo con el uso de CPU y la carga del último minuto
+    fn sample(second: i64, cpu: f32, load: f64) -> Sample {
+        let mut sample = test_sample();
+        sample.timestamp += TimeDelta::seconds(second);
+        sample.cpu_total = cpu;
+        sample.load_average = [load, 0.0, 0.0];
+        sample
+    }
+
+    fn rule(trigger: &str, clear: Option<&str>) -> Rule {
+        Rule::new(trigger, clear, None).unwrap()
+    }
+
+    #[test]
+    fn windows_drop_old_values() {
+        let mut avg = rule("avg(cpu_total, 10s) > 50", None);
+        let mut min = rule("min(cpu_total, 10s) < 10", None);
+        let mut max = rule("max(cpu_total, 10s) > 90", None);
+        let steps = [
+            // segundo, CPU, promedio, mínimo, máximo
+            (0, 100.0, 100.0, 100.0, 100.0),
+            (5, 0.0, 50.0, 0.0, 100.0),
+            (9, 30.0, 130.0 / 3.0, 0.0, 100.0),
+            // El valor del segundo 0 ya quedó fuera de la ventana
+            (10, 60.0, 30.0, 0.0, 60.0),
+            (16, 60.0, 50.0, 30.0, 60.0),
+        ];
+        for (second, cpu, expected_avg, expected_min, expected_max) in steps {
+            let sample = sample(second, cpu, 0.0);
+            let (trigger, clear) = avg.evaluate(&sample);
+            assert!((trigger.value.unwrap() - expected_avg).abs() < 1e-9, "{}: {:?}", second, trigger);
+            assert_eq!(trigger.threshold, Some(50.0));
+            assert_eq!(trigger.active, expected_avg > 50.0, "{}", second);
+            assert_eq!(clear.active, !trigger.active);
+            assert_eq!(min.evaluate(&sample).0.value, Some(expected_min), "{}", second);
+            assert_eq!(max.evaluate(&sample).0.value, Some(expected_max), "{}", second);
+        }
+    }
+
+    #[test]
+    fn and_or_over_samples() {
+        let mut both = rule("cpu_total > 50 and load1 > 2", None);
+        let mut either = rule("cpu_total > 50 or load1 > 2", None);
+        let mut neither = rule("not (cpu_total > 50 or load1 > 2)", None);
+        let steps = [
+            (10.0, 1.0, false, false),
+            (80.0, 1.0, false, true),
+            (10.0, 3.0, false, true),
+            (80.0, 3.0, true, true),
+        ];
+        for (second, (cpu, load, and, or)) in steps.into_iter().enumerate() {
+            let sample = sample(second as i64, cpu, load);
+            let (trigger, _) = both.evaluate(&sample);
+            assert_eq!(trigger.active, and, "{} {}", cpu, load);
+            // El valor informado es el de la primera comparación
+            assert_eq!((trigger.value, trigger.threshold), (Some(cpu as f64), Some(50.0)));
+            assert_eq!(either.evaluate(&sample).0.active, or, "{} {}", cpu, load);
+            assert_eq!(neither.evaluate(&sample).0.active, !or, "{} {}", cpu, load);
+        }
+    }
+
+    #[test]
+    fn both_sides_fill_their_windows() {
+        // Aunque la izquierda no se cumpla, la ventana de la derecha recibe la muestra
+        let mut rule = rule("cpu_total > 50 and avg(load1, 1m) > 1", None);
+        assert!(!rule.evaluate(&sample(0, 0.0, 3.0)).0.active);
+        assert!(rule.evaluate(&sample(1, 80.0, 0.0)).0.active);
+    }
+
+    #[test]
+    fn clear_expression_is_separate() {
+        let mut rule = rule("avg(cpu_total, 1m) > 80", Some("max(cpu_total, 1m) < 50"));
+        let (trigger, clear) = rule.evaluate(&sample(0, 90.0, 0.0));
+        assert!(trigger.active && !clear.active);
+        let (trigger, clear) = rule.evaluate(&sample(1, 40.0, 0.0));
+        assert!(!trigger.active && !clear.active);
+        assert_eq!((clear.value, clear.threshold), (Some(90.0), Some(50.0)));
+    }
+
+    #[test]
+    fn missing_data_never_matches() {
+        // Sin swap no hay dato: ni `>` ni `<` se cumplen
+        let mut high = rule("swap_pct > 50", None);
+        let mut low = rule("swap_pct < 50", None);
+        let sample = sample(0, 0.0, 0.0);
+        assert!(!high.evaluate(&sample).0.active);
+        assert_eq!(low.evaluate(&sample).0.value, None);
+        assert!(!low.evaluate(&sample).0.active);
+        assert!(!rule("load1 / 0 < 1", None).evaluate(&sample).0.active);
+    }
+
+    #[test]
+    fn label_patterns() {
+        let mut sample = sample(0, 0.0, 0.0);
+        sample.cpu_cores = vec![10.0, 95.0, 20.0, 30.0];
+
+        let value = |text: &str| rule(text, None).evaluate(&sample).0.value;
+        assert_eq!(value("cpu_core > 0"), Some(95.0));
+        assert_eq!(value(r#"cpu_core{core="2"} > 0"#), Some(20.0));
+        assert_eq!(value(r#"cpu_core{core="[23]"} > 0"#), None);
+        assert_eq!(value(r#"cpu_core{core="?"} > 0"#), Some(95.0));
+
+        let rule = rule(r#"disk_free_pct{mount="/data*"} < 10"#, Some(r#"disk_free_pct{mount="/home"} > 20"#));
+        assert_eq!(rule.patterns("mount"), vec!["/data*", "/home"]);
+        assert!(rule.patterns("sensor").is_empty());
+        assert_eq!(rule.series().iter().map(|s| s.name).collect::<Vec<_>>(), vec!["disk_free_pct", "disk_free_pct"]);
+    }
+}
@@ -0,0 +1,611 @@
+use std::fmt;
+use std::time::Duration;
+
+use super::{series, Aggregate, ArithOp, CmpOp, Expr, Selector};
+use crate::cli::parse_duration;
+
+// `column` cuenta caracteres desde 1, para señalar el lugar en la expresión.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "columna {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    // La expresión con una marca debajo de la columna del error.
+    pub fn pointer(&self, text: &str) -> String {
+        format!("  {}\n  {}^", text, " ".repeat(self.column.saturating_sub(1)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    // Número con unidad pegada, ej. `5m` o `1h30m`
+    Duration(Duration),
+    Ident(String),
+    Str(String),
+    Op(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "`{}`", n),
+            Token::Duration(d) => write!(f, "la duración `{:?}`", d),
+            Token::Ident(name) => write!(f, "`{}`", name),
+            Token::Str(text) => write!(f, "{:?}", text),
+            Token::Op(op) => write!(f, "`{}`", op),
+            Token::End => write!(f, "el final de la expresión"),
+        }
+    }
+}
+
+// Los operadores de dos caracteres van primero para que `>=` no se lea como `>`.
+const OPERATORS: &[&str] = &[
+    ">=", "<=", "==", "!=", ">", "<", "(", ")", "{", "}", ",", "=", "+", "-", "*", "/",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            if i < chars.len() && chars[i].is_ascii_alphabetic() {
+                while i < chars.len() && chars[i].is_ascii_alphanumeric() {
+                    i += 1;
+                }
+                let literal: String = chars[start..i].iter().collect();
+                let duration = parse_duration(&literal).map_err(|message| ParseError { column, message })?;
+                tokens.push((column, Token::Duration(duration)));
+            } else {
+                let value = number.parse().map_err(|_| ParseError {
+                    column,
+                    message: format!("número inválido `{}`", number),
+                })?;
+                tokens.push((column, Token::Number(value)));
+            }
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((column, Token::Ident(chars[start..i].iter().collect())));
+        } else if c == '"' {
+            let start = i + 1;
+            i = start;
+            while i < chars.len() && chars[i] != '"' {
+                i += 1;
+            }
+            if i == chars.len() {
+                return Err(ParseError { column, message: "falta cerrar las comillas".into() });
+            }
+            tokens.push((column, Token::Str(chars[start..i].iter().collect())));
+            i += 1;
+        } else if let Some(op) = OPERATORS.iter().find(|op| {
+            op.chars().enumerate().all(|(k, o)| chars.get(i + k) == Some(&o))
+        }) {
+            tokens.push((column, Token::Op(op)));
+            i += op.chars().count();
+        } else {
+            return Err(ParseError { column, message: format!("carácter inesperado `{}`", c) });
+        }
+    }
+
+    tokens.push((chars.len() + 1, Token::End));
+    Ok(tokens)
+}
+
+// Paréntesis, `not` y `-` anidados; más profundidad desbordaría la pila
+const MAX_DEPTH: usize = 64;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Number,
+    Bool,
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // Próximo índice libre para el historial de cada función con ventana
+    windows: usize,
+    // Si la condición elige procesos, lo que habilita las series `proc_*`
+    process: bool,
+    depth: usize,
+}
+
+// or  := and ("or" and)*
+// and := not ("and" not)*
+// not := "not" not | cmp
+// cmp := sum (op sum)?
+// sum := term (("+" | "-") term)*
+// term := unary (("*" | "/") unary)*
+// unary := "-" unary | número | "(" or ")" | función "(" serie "," duración ")" | serie
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].1
+    }
+
+    fn column(&self) -> usize {
+        self.tokens[self.pos].0
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].1.clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        Err(ParseError { column: self.column(), message })
+    }
+
+    fn expect(&mut self, op: &'static str) -> Result<(), ParseError> {
+        if *self.peek() == Token::Op(op) {
+            self.next();
+            Ok(())
+        } else {
+            self.error(format!("se esperaba `{}` y se encontró {}", op, self.peek()))
+        }
+    }
+
+    fn keyword(&mut self, word: &str) -> bool {
+        if matches!(self.peek(), Token::Ident(name) if name == word) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Para cada nivel anidado; el llamador resta al volver
+    fn descend(&mut self, column: usize) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError {
+                column,
+                message: format!("la expresión anida más de {} niveles", MAX_DEPTH),
+            });
+        }
+        Ok(())
+    }
+
+    fn require(&self, column: usize, expr: (Expr, Kind), kind: Kind) -> Result<Expr, ParseError> {
+        if expr.1 == kind {
+            return Ok(expr.0);
+        }
+        let message = match kind {
+            Kind::Bool => "se esperaba una condición (comparación, and, or, not) y no un número",
+            Kind::Number => "se esperaba un número y no una condición",
+        };
+        Err(ParseError { column, message: message.into() })
+    }
+
+    fn or(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        let mut left = self.and()?;
+        while self.keyword("or") {
+            let right_column = self.column();
+            let right = self.and()?;
+            let l = self.require(column, left, Kind::Bool)?;
+            let r = self.require(right_column, right, Kind::Bool)?;
+            left = (Expr::Or(Box::new(l), Box::new(r)), Kind::Bool);
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        let mut left = self.not()?;
+        while self.keyword("and") {
+            let right_column = self.column();
+            let right = self.not()?;
+            let l = self.require(column, left, Kind::Bool)?;
+            let r = self.require(right_column, right, Kind::Bool)?;
+            left = (Expr::And(Box::new(l), Box::new(r)), Kind::Bool);
+        }
+        Ok(left)
+    }
+
+    fn not(&mut self) -> Result<(Expr, Kind), ParseError> {
+        if self.keyword("not") {
+            let column = self.column();
+            self.descend(column)?;
+            let inner = self.not()?;
+            self.depth -= 1;
+            let inner = self.require(column, inner, Kind::Bool)?;
+            return Ok((Expr::Not(Box::new(inner)), Kind::Bool));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        let left = self.sum()?;
+        let op = match self.peek() {
+            Token::Op(">") => CmpOp::Gt,
+            Token::Op(">=") => CmpOp::Ge,
+            Token::Op("<") => CmpOp::Lt,
+            Token::Op("<=") => CmpOp::Le,
+            Token::Op("==") => CmpOp::Eq,
+            Token::Op("!=") => CmpOp::Ne,
+            _ => return Ok(left),
+        };
+        self.next();
+        let right_column = self.column();
+        let right = self.sum()?;
+        let l = self.require(column, left, Kind::Number)?;
+        let r = self.require(right_column, right, Kind::Number)?;
+        Ok((Expr::Compare(Box::new(l), op, Box::new(r)), Kind::Bool))
+    }
+
+    fn sum(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        let mut left = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op("+") => ArithOp::Add,
+                Token::Op("-") => ArithOp::Sub,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right_column = self.column();
+            let right = self.term()?;
+            let l = self.require(column, left, Kind::Number)?;
+            let r = self.require(right_column, right, Kind::Number)?;
+            left = (Expr::Arith(Box::new(l), op, Box::new(r)), Kind::Number);
+        }
+    }
+
+    fn term(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        let mut left = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Token::Op("*") => ArithOp::Mul,
+                Token::Op("/") => ArithOp::Div,
+                _ => return Ok(left),
+            };
+            self.next();
+            let right_column = self.column();
+            let right = self.unary()?;
+            let l = self.require(column, left, Kind::Number)?;
+            let r = self.require(right_column, right, Kind::Number)?;
+            left = (Expr::Arith(Box::new(l), op, Box::new(r)), Kind::Number);
+        }
+    }
+
+    fn unary(&mut self) -> Result<(Expr, Kind), ParseError> {
+        let column = self.column();
+        match self.next() {
+            Token::Op("-") => {
+                self.descend(column)?;
+                let inner = self.unary()?;
+                self.depth -= 1;
+                let inner = self.require(column + 1, inner, Kind::Number)?;
+                Ok((Expr::Neg(Box::new(inner)), Kind::Number))
+            }
+            Token::Number(value) => Ok((Expr::Number(value), Kind::Number)),
+            Token::Op("(") => {
+                self.descend(column)?;
+                let inner = self.or()?;
+                self.expect(")")?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            Token::Ident(name) if *self.peek() == Token::Op("(") => self.window(column, &name),
+            Token::Ident(name) => Ok((Expr::Series(self.selector(column, &name)?), Kind::Number)),
+            Token::Duration(_) => Err(ParseError {
+                column,
+                message: "una duración solo puede ser la ventana de avg, min o max".into(),
+            }),
+            other => Err(ParseError {
+                column,
+                message: format!("se esperaba un número, una serie o `(` y se encontró {}", other),
+            }),
+        }
+    }
+
+    // avg(serie, 5m)
+    fn window(&mut self, column: usize, name: &str) -> Result<(Expr, Kind), ParseError> {
+        let aggregate = match name {
+            "avg" => Aggregate::Avg,
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            _ => {
+                return Err(ParseError {
+                    column,
+                    message: format!("función desconocida `{}` (disponibles: avg, min, max)", name),
+                })
+            }
+        };
+        self.expect("(")?;
+        let series_column = self.column();
+        let selector = match self.next() {
+            Token::Ident(series) => self.selector(series_column, &series)?,
+            other => {
+                return Err(ParseError {
+                    column: series_column,
+                    message: format!("se esperaba el nombre de una serie y se encontró {}", other),
+                })
+            }
+        };
+        self.expect(",")?;
+        let window = match self.next() {
+            Token::Duration(window) if !window.is_zero() => window,
+            _ => {
+                return Err(ParseError {
+                    column: self.tokens[self.pos - 1].0,
+                    message: "se esperaba una ventana como 30s, 5m o 1h".into(),
+                })
+            }
+        };
+        self.expect(")")?;
+
+        let slot = self.windows;
+        self.windows += 1;
+        Ok((Expr::Window { aggregate, selector, window, slot }, Kind::Number))
+    }
+
+    // nombre o nombre{etiqueta="patrón"}
+    fn selector(&mut self, column: usize, name: &str) -> Result<Selector, ParseError> {
+        let Some(series) = series::find(name) else {
+            return Err(ParseError {
+                column,
+                message: format!("serie desconocida `{}` (disponibles: {})", name, series::names().join(", ")),
+            });
+        };
+        if series.process && !self.process {
+            return Err(ParseError {
+                column,
+                message: format!("`{}` solo se puede usar en condiciones con `process`", name),
+            });
+        }
+
+        let mut pattern = None;
+        if *self.peek() == Token::Op("{") {
+            self.next();
+            let label_column = self.column();
+            let label = match self.next() {
+                Token::Ident(label) => label,
+                other => {
+                    return Err(ParseError {
+                        column: label_column,
+                        message: format!("se esperaba una etiqueta y se encontró {}", other),
+                    })
+                }
+            };
+            if series.label != Some(label.as_str()) {
+                let message = match series.label {
+                    Some(expected) => format!("`{}` solo admite la etiqueta `{}`", name, expected),
+                    None => format!("`{}` no tiene etiquetas", name),
+                };
+                return Err(ParseError { column: label_column, message });
+            }
+            self.expect("=")?;
+            let value_column = self.column();
+            match self.next() {
+                Token::Str(value) => pattern = Some(value),
+                other => {
+                    return Err(ParseError {
+                        column: value_column,
+                        message: format!("se esperaba un texto entre comillas y se encontró {}", other),
+                    })
+                }
+            }
+            self.expect("}")?;
+        }
+
+        Ok(Selector { series, pattern })
+    }
+}
+
+// Devuelve la expresión y cuántos historiales de ventana necesita. `first_slot`
+// permite que dos expresiones de la misma regla no compartan historiales.
+pub fn parse(text: &str, first_slot: usize, process: bool) -> Result<(Expr, usize), ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(text)?,
+        pos: 0,
+        windows: first_slot,
+        process,
+        depth: 0,
+    };
+    if *parser.peek() == Token::End {
+        return parser.error("la expresión está vacía".into());
+    }
+
+    let column = parser.column();
+    let expr = parser.or()?;
+    if *parser.peek() != Token::End {
+        return parser.error(format!("sobra {} al final de la expresión", parser.peek()));
+    }
+    let expr = parser.require(column, expr, Kind::Bool)?;
+    Ok((expr, parser.windows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // La expresión con todos los paréntesis, para comprobar la precedencia
+    fn show(expr: &Expr) -> String {
+        let selector = |s: &Selector| match &s.pattern {
+            Some(pattern) => format!("{}{{{}={:?}}}", s.series.name, s.series.label.unwrap_or_default(), pattern),
+            None => s.series.name.to_string(),
+        };
+        match expr {
+            Expr::Number(value) => value.to_string(),
+            Expr::Series(s) => selector(s),
+            Expr::Window { aggregate, selector: s, window, slot } => {
+                format!("{:?}#{}({}, {:?})", aggregate, slot, selector(s), window)
+            }
+            Expr::Neg(inner) => format!("-{}", show(inner)),
+            Expr::Not(inner) => format!("(not {})", show(inner)),
+            Expr::Arith(l, op, r) => format!("({} {:?} {})", show(l), op, show(r)),
+            Expr::Compare(l, op, r) => format!("({} {:?} {})", show(l), op, show(r)),
+            Expr::And(l, r) => format!("({} and {})", show(l), show(r)),
+            Expr::Or(l, r) => format!("({} or {})", show(l), show(r)),
+        }
+    }
+
+    fn parsed(text: &str) -> String {
+        show(&parse(text, 0, false).unwrap().0)
+    }
+
+    fn error(text: &str) -> ParseError {
+        parse(text, 0, false).unwrap_err()
+    }
+
+    #[test]
+    fn tokenizes_with_columns() {
+        let tokens = tokenize(r#"avg(cpu_total, 1h30m)>=.5 and disk_free_pct{mount="/"} != 0"#).unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                (1, Token::Ident("avg".into())),
+                (4, Token::Op("(")),
+                (5, Token::Ident("cpu_total".into())),
+                (14, Token::Op(",")),
+                (16, Token::Duration(Duration::from_secs(5400))),
+                (21, Token::Op(")")),
+                (22, Token::Op(">=")),
+                (24, Token::Number(0.5)),
+                (27, Token::Ident("and".into())),
+                (31, Token::Ident("disk_free_pct".into())),
+                (44, Token::Op("{")),
+                (45, Token::Ident("mount".into())),
+                (50, Token::Op("=")),
+                (51, Token::Str("/".into())),
+                (54, Token::Op("}")),
+                (56, Token::Op("!=")),
+                (59, Token::Number(0.0)),
+                (60, Token::End),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizer_errors_point_at_the_character() {
+        let e = tokenize("cpu_total > 5 & load1 > 1").unwrap_err();
+        assert_eq!((e.column, e.message.as_str()), (15, "carácter inesperado `&`"));
+        let e = tokenize(r#"cpu_core{core="1} > 5"#).unwrap_err();
+        assert_eq!((e.column, e.message.as_str()), (15, "falta cerrar las comillas"));
+        let e = tokenize("cpu_total > 1.2.3").unwrap_err();
+        assert_eq!((e.column, e.message.as_str()), (13, "número inválido `1.2.3`"));
+        let e = tokenize("avg(cpu_total, 5x) > 1").unwrap_err();
+        assert_eq!(e.column, 16);
+        assert!(e.message.starts_with("unidad desconocida"), "{}", e.message);
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(
+            parsed("cpu_total > 1 + 2 * 3 or load1 > 2 and not load5 < 1"),
+            "((cpu_total Gt (1 Add (2 Mul 3))) or ((load1 Gt 2) and (not (load5 Lt 1))))"
+        );
+        assert_eq!(parsed("(1 + 2) * 3 > cpu_total"), "(((1 Add 2) Mul 3) Gt cpu_total)");
+        assert_eq!(parsed("10 - 2 - 3 / 4 / 5 <= -load1"), "(((10 Sub 2) Sub ((3 Div 4) Div 5)) Le -load1)");
+        assert_eq!(
+            parsed("(load1 > 1 or load5 > 1) and load15 > 1"),
+            "(((load1 Gt 1) or (load5 Gt 1)) and (load15 Gt 1))"
+        );
+        assert_eq!(parsed("not not cpu_total == 0"), "(not (not (cpu_total Eq 0)))");
+    }
+
+    #[test]
+    fn selectors_and_windows() {
+        assert_eq!(
+            parsed(r#"max(cpu_core{core="1"}, 5m) > 90 and avg(load1, 30s) > cores"#),
+            r#"((Max#0(cpu_core{core="1"}, 300s) Gt 90) and (Avg#1(load1, 30s) Gt cores))"#
+        );
+
+        // Las ventanas de `clear` siguen después de las de `trigger`
+        let (_, slots) = parse("min(load1, 1m) < 1 or avg(load5, 1m) < 1", 3, false).unwrap();
+        assert_eq!(slots, 5);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let e = error("cpu_total > ");
+        assert_eq!(e.column, 13);
+        assert_eq!(e.message, "se esperaba un número, una serie o `(` y se encontró el final de la expresión");
+        assert_eq!(e.pointer("cpu_total > "), format!("  cpu_total > \n  {}^", " ".repeat(12)));
+        assert_eq!(e.to_string(), format!("columna 13: {}", e.message));
+
+        let cases = [
+            ("", 1, "la expresión está vacía"),
+            ("cpu_total", 1, "se esperaba una condición (comparación, and, or, not) y no un número"),
+            ("cpu_total > 5 5", 15, "sobra `5` al final de la expresión"),
+            ("load1 > 1 and 2", 15, "se esperaba una condición (comparación, and, or, not) y no un número"),
+            ("(load1 > 1) + 2 > 1", 1, "se esperaba un número y no una condición"),
+            ("(load1 > 1", 11, "se esperaba `)` y se encontró el final de la expresión"),
+            ("5m > 1", 1, "una duración solo puede ser la ventana de avg, min o max"),
+            ("sum(load1, 5m) > 1", 1, "función desconocida `sum` (disponibles: avg, min, max)"),
+            ("avg(load1, 0s) > 1", 12, "se esperaba una ventana como 30s, 5m o 1h"),
+            ("avg(load1, 5) > 1", 12, "se esperaba una ventana como 30s, 5m o 1h"),
+            ("avg(1, 5m) > 1", 5, "se esperaba el nombre de una serie y se encontró `1`"),
+            ("proc_cpu > 5", 1, "`proc_cpu` solo se puede usar en condiciones con `process`"),
+        ];
+        for (text, column, message) in cases {
+            let e = error(text);
+            assert_eq!((e.column, e.message.as_str()), (column, message), "{}", text);
+        }
+        assert!(parse("proc_cpu > 5", 0, true).is_ok());
+    }
+
+    #[test]
+    fn unknown_series_and_bad_labels() {
+        let e = error("cpu_tota > 5");
+        assert_eq!(e.column, 1);
+        assert!(e.message.starts_with("serie desconocida `cpu_tota` (disponibles: cpu_total, "), "{}", e.message);
+
+        let cases = [
+            (r#"cpu_total{core="0"} > 1"#, 11, "`cpu_total` no tiene etiquetas"),
+            (r#"cpu_core{mount="/"} > 1"#, 10, "`cpu_core` solo admite la etiqueta `core`"),
+            ("cpu_core{core=0} > 1", 15, "se esperaba un texto entre comillas y se encontró `0`"),
+            (r#"cpu_core{"0"} > 1"#, 10, r#"se esperaba una etiqueta y se encontró "0""#),
+            (r#"cpu_core{core "0"} > 1"#, 15, r#"se esperaba `=` y se encontró "0""#),
+            (r#"cpu_core{core="0" > 1"#, 19, "se esperaba `}` y se encontró `>`"),
+        ];
+        for (text, column, message) in cases {
+            let e = error(text);
+            assert_eq!((e.column, e.message.as_str()), (column, message), "{}", text);
+        }
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}load1 > 1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH), 0, false).is_ok());
+
+        let e = error(&nested(100_000));
+        assert_eq!((e.column, e.message.as_str()), (MAX_DEPTH + 1, "la expresión anida más de 64 niveles"));
+        let e = error(&format!("{}load1 > 1", "not ".repeat(100_000)));
+        assert_eq!(e.message, "la expresión anida más de 64 niveles");
+        let e = error(&format!("load1 > {}1", "-".repeat(100_000)));
+        assert_eq!(e.message, "la expresión anida más de 64 niveles");
+    }
+}
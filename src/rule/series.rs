@@ -0,0 +1,501 @@
+use crate::collector::{ProcessSample, Sample};
+use crate::linux::cgroup::CgroupSample;
+use crate::linux::psi::{self, Pressure};
+
+// Cómo se combinan los valores de una serie con etiqueta cuando el selector
+// no la reduce a uno solo (sin etiqueta o con un patrón que abarca varios).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combine {
+    Sum,
+    Max,
+    Min,
+}
+
+// Valores con el valor de su etiqueta (vacío si no tiene)
+pub type Values = Vec<(String, f64)>;
+
+#[derive(Debug)]
+pub struct Series {
+    pub name: &'static str,
+    // Nombre de la única etiqueta que admite, ej. `mount`
+    pub label: Option<&'static str>,
+    pub combine: Combine,
+    pub help: &'static str,
+    // Solo en condiciones con `process`; se calcula sobre los procesos elegidos
+    pub process: bool,
+    // Valores de la muestra; el segundo argumento son los procesos elegidos
+    pub read: fn(&Sample, &[&ProcessSample]) -> Values,
+}
+
+const MB: f64 = 1024.0 * 1024.0;
+
+fn one(value: f64) -> Values {
+    vec![(String::new(), value)]
+}
+
+fn cgroup(sample: &Sample, value: fn(&CgroupSample) -> Option<f64>) -> Values {
+    sample.cgroup.as_ref().and_then(value).map(one).unwrap_or_default()
+}
+
+fn pressure(sample: &Sample, value: fn(&Pressure) -> Option<f64>) -> Values {
+    psi::RESOURCES
+        .iter()
+        .filter_map(|r| Some((r.to_string(), value(sample.pressure.get(r)?)?)))
+        .collect()
+}
+
+fn per_process(processes: &[&ProcessSample], value: fn(&ProcessSample) -> f64) -> Values {
+    processes.iter().map(|p| (p.pid.to_string(), value(p))).collect()
+}
+
+pub const SERIES: &[Series] = &[
+    Series {
+        name: "cpu_total",
+        label: None,
+        combine: Combine::Max,
+        help: "uso de CPU promedio (%); en un cgroup con `cpu.max`, respecto de ese límite",
+        process: false,
+        read: |s, _| one(s.cpu_percent() as f64),
+    },
+    Series {
+        name: "cpu_core",
+        label: Some("core"),
+        combine: Combine::Max,
+        help: "uso de cada núcleo (%), el mayor sin etiqueta",
+        process: false,
+        read: |s, _| {
+            s.cpu_cores
+                .iter()
+                .enumerate()
+                .map(|(i, usage)| (i.to_string(), *usage as f64))
+                .collect()
+        },
+    },
+    Series {
+        name: "cpu_freq_mhz",
+        label: Some("core"),
+        combine: Combine::Min,
+        help: "frecuencia de cada núcleo (MHz), la menor sin etiqueta",
+        process: false,
+        read: |s, _| {
+            s.cpu_frequencies
+                .iter()
+                .enumerate()
+                .map(|(i, mhz)| (i.to_string(), *mhz as f64))
+                .collect()
+        },
+    },
+    Series {
+        name: "cores",
+        label: None,
+        combine: Combine::Max,
+        help: "cantidad de núcleos, ej. `load1 > cores * 2`",
+        process: false,
+        read: |s, _| one(s.cpu_cores.len() as f64),
+    },
+    Series {
+        name: "load1",
+        label: None,
+        combine: Combine::Max,
+        help: "carga promedio del último minuto; 0 en Windows",
+        process: false,
+        read: |s, _| one(s.load_average[0]),
+    },
+    Series {
+        name: "load5",
+        label: None,
+        combine: Combine::Max,
+        help: "carga promedio de los últimos 5 minutos; 0 en Windows",
+        process: false,
+        read: |s, _| one(s.load_average[1]),
+    },
+    Series {
+        name: "load15",
+        label: None,
+        combine: Combine::Max,
+        help: "carga promedio de los últimos 15 minutos; 0 en Windows",
+        process: false,
+        read: |s, _| one(s.load_average[2]),
+    },
+    Series {
+        name: "uptime_hours",
+        label: None,
+        combine: Combine::Max,
+        help: "horas desde el arranque del sistema",
+        process: false,
+        read: |s, _| one(s.uptime as f64 / 3600.0),
+    },
+    Series {
+        name: "mem_pct",
+        label: None,
+        combine: Combine::Max,
+        help: "memoria usada (%), sin contar la caché liberable; en un cgroup con `memory.max`, respecto de ese límite",
+        process: false,
+        read: |s, _| one(s.memory_percent() as f64),
+    },
+    Series {
+        name: "mem_used_mb",
+        label: None,
+        combine: Combine::Max,
+        help: "memoria usada (MB)",
+        process: false,
+        read: |s, _| one(s.memory_used as f64 / MB),
+    },
+    Series {
+        name: "mem_available_mb",
+        label: None,
+        combine: Combine::Min,
+        help: "memoria disponible sin usar la swap, incluida la caché liberable (MB)",
+        process: false,
+        read: |s, _| one(s.memory_available as f64 / MB),
+    },
+    Series {
+        name: "swap_pct",
+        label: None,
+        combine: Combine::Max,
+        help: "swap usada (%); sin dato si no hay swap",
+        process: false,
+        read: |s, _| s.swap_percent().map(|p| one(p as f64)).unwrap_or_default(),
+    },
+    Series {
+        name: "swap_used_mb",
+        label: None,
+        combine: Combine::Max,
+        help: "swap usada (MB)",
+        process: false,
+        read: |s, _| one(s.swap_used as f64 / MB),
+    },
+    Series {
+        name: "swap_in_mbs",
+        label: None,
+        combine: Combine::Max,
+        help: "páginas traídas desde la swap (MB/s); solo Linux",
+        process: false,
+        read: |s, _| s.swap_in_rate.map(|r| one(r / MB)).unwrap_or_default(),
+    },
+    Series {
+        name: "swap_out_mbs",
+        label: None,
+        combine: Combine::Max,
+        help: "páginas llevadas a la swap (MB/s); solo Linux",
+        process: false,
+        read: |s, _| s.swap_out_rate.map(|r| one(r / MB)).unwrap_or_default(),
+    },
+    Series {
+        name: "cgroup_mem_pct",
+        label: None,
+        combine: Combine::Max,
+        help: "memoria del cgroup del monitor respecto de su límite (%); solo Linux con cgroup v2",
+        process: false,
+        read: |s, _| cgroup(s, |c| c.memory_percent()),
+    },
+    Series {
+        name: "cgroup_cpu_pct",
+        label: None,
+        combine: Combine::Max,
+        help: "CPU del cgroup del monitor respecto de su límite (%); solo Linux con cgroup v2",
+        process: false,
+        read: |s, _| cgroup(s, |c| c.cpu_percent()),
+    },
+    Series {
+        name: "cgroup_cpu_cores",
+        label: None,
+        combine: Combine::Max,
+        help: "núcleos usados por el cgroup del monitor; solo Linux con cgroup v2",
+        process: false,
+        read: |s, _| cgroup(s, |c| c.cpu_usage),
+    },
+    Series {
+        name: "cgroup_throttled_pct",
+        label: None,
+        combine: Combine::Max,
+        help: "períodos de `cpu.max` en los que se frenó al cgroup del monitor (%); solo Linux con cgroup v2",
+        process: false,
+        read: |s, _| cgroup(s, |c| c.throttled_percent),
+    },
+    Series {
+        name: "cgroup_unit_cpu",
+        label: Some("unit"),
+        combine: Combine::Max,
+        help: "uso de CPU de los procesos de cada unidad o contenedor (%), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.cgroups.iter().map(|g| (g.name.clone(), g.cpu as f64)).collect(),
+    },
+    Series {
+        name: "cgroup_unit_mem_mb",
+        label: Some("unit"),
+        combine: Combine::Max,
+        help: "memoria de los procesos de cada unidad o contenedor (MB), la mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.cgroups.iter().map(|g| (g.name.clone(), g.memory as f64 / MB)).collect(),
+    },
+    Series {
+        name: "cgroup_unit_read_mbs",
+        label: Some("unit"),
+        combine: Combine::Max,
+        help: "lectura de disco de cada unidad o contenedor (MB/s), la mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.cgroups.iter().map(|g| (g.name.clone(), g.read_rate / MB)).collect(),
+    },
+    Series {
+        name: "cgroup_unit_write_mbs",
+        label: Some("unit"),
+        combine: Combine::Max,
+        help: "escritura de disco de cada unidad o contenedor (MB/s), la mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.cgroups.iter().map(|g| (g.name.clone(), g.write_rate / MB)).collect(),
+    },
+    Series {
+        name: "net_rx_mbps",
+        label: Some("interface"),
+        combine: Combine::Sum,
+        help: "bajada (Mbps), suma de las interfaces sin etiqueta",
+        process: false,
+        read: |s, _| s.interfaces.iter().map(|i| (i.name.clone(), i.rx_mbps)).collect(),
+    },
+    Series {
+        name: "net_tx_mbps",
+        label: Some("interface"),
+        combine: Combine::Sum,
+        help: "subida (Mbps), suma de las interfaces sin etiqueta",
+        process: false,
+        read: |s, _| s.interfaces.iter().map(|i| (i.name.clone(), i.tx_mbps)).collect(),
+    },
+    Series {
+        name: "disk_free_pct",
+        label: Some("mount"),
+        combine: Combine::Min,
+        help: "espacio libre (%), el menor sin etiqueta",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.free_percent()?))).collect(),
+    },
+    Series {
+        name: "disk_used_pct",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "espacio usado (%), el mayor sin etiqueta",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), 100.0 - d.free_percent()?))).collect(),
+    },
+    Series {
+        name: "disk_free_mb",
+        label: Some("mount"),
+        combine: Combine::Min,
+        help: "espacio libre (MB), el menor sin etiqueta",
+        process: false,
+        read: |s, _| {
+            s.disks
+                .iter()
+                .filter(|d| d.total_space > 0)
+                .map(|d| (d.mount.clone(), d.available_space as f64 / MB))
+                .collect()
+        },
+    },
+    Series {
+        name: "disk_inodes_free_pct",
+        label: Some("mount"),
+        combine: Combine::Min,
+        help: "inodos libres (%), el menor sin etiqueta; solo Unix",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.inodes_free_percent()?))).collect(),
+    },
+    Series {
+        name: "disk_hours_to_full",
+        label: Some("mount"),
+        combine: Combine::Min,
+        help: "horas hasta llenarse al ritmo de la última media hora; sin dato si no se llena",
+        process: false,
+        read: |s, _| {
+            s.disks
+                .iter()
+                .filter_map(|d| Some((d.mount.clone(), d.full_in?.as_secs_f64() / 3600.0)))
+                .collect()
+        },
+    },
+    Series {
+        name: "disk_read_mbs",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "lectura de cada disco (MB/s), la mayor sin etiqueta",
+        process: false,
+        read: |s, _| s.disks.iter().map(|d| (d.mount.clone(), d.io.read_rate / MB)).collect(),
+    },
+    Series {
+        name: "disk_write_mbs",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "escritura de cada disco (MB/s), la mayor sin etiqueta",
+        process: false,
+        read: |s, _| s.disks.iter().map(|d| (d.mount.clone(), d.io.write_rate / MB)).collect(),
+    },
+    Series {
+        name: "disk_read_iops",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "lecturas por segundo, las más sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.io.read_iops?))).collect(),
+    },
+    Series {
+        name: "disk_write_iops",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "escrituras por segundo, las más sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.io.write_iops?))).collect(),
+    },
+    Series {
+        name: "disk_read_latency_ms",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "tiempo medio de cada lectura (ms), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.io.read_latency_ms?))).collect(),
+    },
+    Series {
+        name: "disk_write_latency_ms",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "tiempo medio de cada escritura (ms), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.io.write_latency_ms?))).collect(),
+    },
+    Series {
+        name: "disk_queue",
+        label: Some("mount"),
+        combine: Combine::Max,
+        help: "operaciones en cola en promedio, la mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| s.disks.iter().filter_map(|d| Some((d.mount.clone(), d.io.queue_depth?))).collect(),
+    },
+    Series {
+        name: "temp_c",
+        label: Some("sensor"),
+        combine: Combine::Max,
+        help: "temperatura de cada sensor (°C), la mayor sin etiqueta",
+        process: false,
+        read: |s, _| s.temperatures.iter().map(|t| (t.label.clone(), t.current as f64)).collect(),
+    },
+    Series {
+        name: "temp_crit_pct",
+        label: Some("sensor"),
+        combine: Combine::Max,
+        help: "temperatura como porcentaje de la crítica de cada sensor, la mayor sin etiqueta",
+        process: false,
+        read: |s, _| {
+            s.temperatures
+                .iter()
+                .filter_map(|t| Some((t.label.clone(), t.critical_percent()?)))
+                .collect()
+        },
+    },
+    Series {
+        name: "temp_crit_margin_c",
+        label: Some("sensor"),
+        combine: Combine::Min,
+        help: "grados que faltan para la crítica de cada sensor, el menor sin etiqueta",
+        process: false,
+        read: |s, _| {
+            s.temperatures
+                .iter()
+                .filter_map(|t| Some((t.label.clone(), (t.critical? - t.current) as f64)))
+                .collect()
+        },
+    },
+    Series {
+        name: "psi_some_avg10",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con alguna tarea esperando cpu, memory o io (10 s), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| Some(p.some).map(|a| a.avg10)),
+    },
+    Series {
+        name: "psi_some_avg60",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con alguna tarea esperando cpu, memory o io (1 min), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| Some(p.some).map(|a| a.avg60)),
+    },
+    Series {
+        name: "psi_some_avg300",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con alguna tarea esperando cpu, memory o io (5 min), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| Some(p.some).map(|a| a.avg300)),
+    },
+    Series {
+        name: "psi_full_avg10",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con todas las tareas esperando cpu, memory o io (10 s), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| p.full.map(|a| a.avg10)),
+    },
+    Series {
+        name: "psi_full_avg60",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con todas las tareas esperando cpu, memory o io (1 min), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| p.full.map(|a| a.avg60)),
+    },
+    Series {
+        name: "psi_full_avg300",
+        label: Some("resource"),
+        combine: Combine::Max,
+        help: "% del tiempo con todas las tareas esperando cpu, memory o io (5 min), el mayor sin etiqueta; solo Linux",
+        process: false,
+        read: |s, _| pressure(s, |p| p.full.map(|a| a.avg300)),
+    },
+    Series {
+        name: "proc_count",
+        label: None,
+        combine: Combine::Sum,
+        help: "cantidad de procesos que coinciden con `process` (0 si no hay)",
+        process: true,
+        read: |_, p| one(p.len() as f64),
+    },
+    Series {
+        name: "proc_cpu",
+        label: Some("pid"),
+        combine: Combine::Max,
+        help: "uso de CPU de cada proceso (%), el mayor sin etiqueta",
+        process: true,
+        read: |_, p| per_process(p, |p| p.cpu as f64),
+    },
+    Series {
+        name: "proc_mem_mb",
+        label: Some("pid"),
+        combine: Combine::Max,
+        help: "memoria residente de cada proceso (MB), la mayor sin etiqueta",
+        process: true,
+        read: |_, p| per_process(p, |p| p.memory as f64 / MB),
+    },
+    Series {
+        name: "proc_cpu_total",
+        label: Some("pid"),
+        combine: Combine::Sum,
+        help: "uso de CPU sumado de los procesos (%)",
+        process: true,
+        read: |_, p| per_process(p, |p| p.cpu as f64),
+    },
+    Series {
+        name: "proc_mem_total_mb",
+        label: Some("pid"),
+        combine: Combine::Sum,
+        help: "memoria residente sumada de los procesos (MB)",
+        process: true,
+        read: |_, p| per_process(p, |p| p.memory as f64 / MB),
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Series> {
+    SERIES.iter().find(|s| s.name == name)
+}
+
+pub fn names() -> Vec<&'static str> {
+    SERIES.iter().map(|s| s.name).collect()
+}
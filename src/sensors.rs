@@ -0,0 +1,178 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use sysinfo::Components;
+
+use crate::linux::{self, hwmon};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TemperatureSample {
+    pub label: String,
+    // Grados Celsius
+    pub current: f32,
+    // La más alta vista desde que se empezó a medir
+    pub max: Option<f32>,
+    // Temperatura a partir de la cual el hardware se protege
+    pub critical: Option<f32>,
+}
+
+impl TemperatureSample {
+    // Temperatura actual como porcentaje de la crítica
+    pub fn critical_percent(&self) -> Option<f64> {
+        let critical = self.critical.filter(|c| *c > 0.0)?;
+        Some(self.current as f64 / critical as f64 * 100.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SensorSource {
+    // Componentes de sysinfo (hwmon en Linux, SMC en macOS, WMI en Windows)
+    #[default]
+    Sysinfo,
+    // Lectura directa de /sys/class/hwmon bajo `root`, ej. un directorio de prueba
+    Hwmon,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SensorsConfig {
+    pub source: SensorSource,
+    pub root: PathBuf,
+}
+
+impl Default for SensorsConfig {
+    fn default() -> Self {
+        SensorsConfig {
+            source: SensorSource::Sysinfo,
+            root: PathBuf::from(linux::ROOT),
+        }
+    }
+}
+
+enum Source {
+    Sysinfo(Components),
+    Hwmon(PathBuf),
+}
+
+pub struct Sensors {
+    config: SensorsConfig,
+    source: Source,
+    // Máxima vista de cada sensor, para las fuentes que no la informan
+    max: HashMap<String, f32>,
+}
+
+impl Sensors {
+    pub fn new(config: SensorsConfig) -> Self {
+        let source = match config.source {
+            SensorSource::Sysinfo => Source::Sysinfo(Components::new_with_refreshed_list()),
+            SensorSource::Hwmon => Source::Hwmon(config.root.clone()),
+        };
+        Sensors {
+            config,
+            source,
+            max: HashMap::new(),
+        }
+    }
+
+    // Sin cambios se conservan la lista de componentes y las máximas.
+    pub fn reconfigure(&mut self, config: SensorsConfig) {
+        if config != self.config {
+            *self = Sensors::new(config);
+        }
+    }
+
+    pub fn read(&mut self) -> Vec<TemperatureSample> {
+        let mut sensors = match &mut self.source {
+            Source::Sysinfo(components) => {
+                // Los sensores que aparecen después del arranque (ej. un disco USB) se agregan
+                components.refresh(true);
+                components
+                    .iter()
+                    .filter_map(|c| {
+                        Some(TemperatureSample {
+                            label: c.label().to_string(),
+                            current: c.temperature().filter(|t| t.is_finite())?,
+                            max: c.max().filter(|t| t.is_finite()),
+                            critical: c.critical().filter(|t| t.is_finite()),
+                        })
+                    })
+                    .collect()
+            }
+            Source::Hwmon(root) => hwmon::read(Path::new(root)),
+        };
+
+        self.max.retain(|label, _| sensors.iter().any(|s| s.label == *label));
+        for sensor in &mut sensors {
+            let seen = self.max.entry(sensor.label.clone()).or_insert(sensor.current);
+            *seen = seen.max(sensor.current).max(sensor.max.unwrap_or(f32::MIN));
+            sensor.max = Some(*seen);
+        }
+        sensors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::linux::Fixture;
+
+    const INPUT: &str = "sys/class/hwmon/hwmon0/temp1_input";
+
+    fn sensors(fixture: &Fixture) -> Sensors {
+        fixture.write("sys/class/hwmon/hwmon0/name", "acpitz\n");
+        fixture.write("sys/class/hwmon/hwmon0/temp1_crit", "90000\n");
+        Sensors::new(SensorsConfig {
+            source: SensorSource::Hwmon,
+            root: fixture.root.clone(),
+        })
+    }
+
+    #[test]
+    fn tracks_highest_reading() {
+        let fixture = Fixture::new("sensors-max");
+        let mut sensors = sensors(&fixture);
+
+        fixture.write(INPUT, "50000\n");
+        assert_eq!(sensors.read()[0].max, Some(50.0));
+        fixture.write(INPUT, "70000\n");
+        assert_eq!(sensors.read()[0].max, Some(70.0));
+        fixture.write(INPUT, "60000\n");
+        let reading = sensors.read();
+        assert_eq!(reading[0].current, 60.0);
+        assert_eq!(reading[0].max, Some(70.0));
+
+        // La máxima que informa el hardware también cuenta
+        fixture.write("sys/class/hwmon/hwmon0/temp1_highest", "80000\n");
+        assert_eq!(sensors.read()[0].max, Some(80.0));
+    }
+
+    #[test]
+    fn forgets_removed_sensors() {
+        let fixture = Fixture::new("sensors-removed");
+        let mut sensors = sensors(&fixture);
+        fixture.write(INPUT, "70000\n");
+        sensors.read();
+
+        fs::remove_file(fixture.root.join(INPUT)).unwrap();
+        assert!(sensors.read().is_empty());
+        fixture.write(INPUT, "40000\n");
+        assert_eq!(sensors.read()[0].max, Some(40.0));
+    }
+
+    #[test]
+    fn critical_percent() {
+        let sensor = |current, critical| TemperatureSample {
+            label: "cpu".to_string(),
+            current,
+            max: None,
+            critical,
+        };
+        assert_eq!(sensor(45.0, Some(90.0)).critical_percent(), Some(50.0));
+        assert_eq!(sensor(45.0, None).critical_percent(), None);
+        assert_eq!(sensor(45.0, Some(0.0)).critical_percent(), None);
+    }
+}
@@ -0,0 +1,220 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+
+use crate::collector::ProcessSample;
+use crate::network::glob_match;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    Memory,
+    VirtualMemory,
+    Read,
+    Write,
+    StartTime,
+    Status,
+    Cmdline,
+}
+
+pub const COLUMNS: [Column; 11] = [
+    Column::Pid,
+    Column::Name,
+    Column::User,
+    Column::Cpu,
+    Column::Memory,
+    Column::VirtualMemory,
+    Column::Read,
+    Column::Write,
+    Column::StartTime,
+    Column::Status,
+    Column::Cmdline,
+];
+
+impl Column {
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Name => "Nombre",
+            Column::User => "Usuario",
+            Column::Cpu => "CPU %",
+            Column::Memory => "RAM (MB)",
+            Column::VirtualMemory => "Virtual (MB)",
+            Column::Read => "Lectura (MB/s)",
+            Column::Write => "Escritura (MB/s)",
+            Column::StartTime => "Inicio",
+            Column::Status => "Estado",
+            Column::Cmdline => "Comando",
+        }
+    }
+
+    // Los números empiezan de mayor a menor y los textos en orden alfabético
+    pub fn descending_by_default(self) -> bool {
+        matches!(
+            self,
+            Column::Cpu | Column::Memory | Column::VirtualMemory | Column::Read | Column::Write | Column::StartTime
+        )
+    }
+
+    pub fn compare(self, a: &ProcessSample, b: &ProcessSample) -> Ordering {
+        let text = |a: &str, b: &str| a.to_lowercase().cmp(&b.to_lowercase());
+        match self {
+            Column::Pid => a.pid.cmp(&b.pid),
+            Column::Name => text(&a.name, &b.name),
+            Column::User => text(a.user.as_deref().unwrap_or_default(), b.user.as_deref().unwrap_or_default()),
+            Column::Cpu => a.cpu.total_cmp(&b.cpu),
+            Column::Memory => a.memory.cmp(&b.memory),
+            Column::VirtualMemory => a.virtual_memory.cmp(&b.virtual_memory),
+            Column::Read => a.read_rate.total_cmp(&b.read_rate),
+            Column::Write => a.write_rate.total_cmp(&b.write_rate),
+            Column::StartTime => a.start_time.cmp(&b.start_time),
+            Column::Status => a.status.cmp(&b.status),
+            Column::Cmdline => a.cmdline.cmp(&b.cmdline),
+        }
+    }
+}
+
+// Ordena los procesos por una columna; a igual valor, por pid.
+pub fn sort(processes: &mut [&ProcessSample], column: Column, descending: bool) {
+    processes.sort_by(|a, b| {
+        let order = column.compare(a, b).then(a.pid.cmp(&b.pid));
+        if descending {
+            order.reverse()
+        } else {
+            order
+        }
+    });
+}
+
+enum Term {
+    // Parte del nombre sin distinguir mayúsculas, o un patrón con `*` y `?`
+    Name(String),
+    User(String),
+    // Sobre el nombre y la línea de comandos
+    Regex(Regex),
+}
+
+// Filtro del cuadro de búsqueda: términos separados por espacios que deben
+// cumplirse todos. `user:nombre` compara el usuario, `/expresión/` busca
+// en el nombre y la línea de comandos, y el resto busca en el nombre.
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Filter, regex::Error> {
+        let terms = text
+            .split_whitespace()
+            .map(|term| {
+                if let Some(user) = term.strip_prefix("user:") {
+                    Ok(Term::User(user.to_string()))
+                } else if let Some(pattern) = term.strip_prefix('/').and_then(|t| t.strip_suffix('/')) {
+                    Ok(Term::Regex(Regex::new(pattern)?))
+                } else {
+                    Ok(Term::Name(term.to_lowercase()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Filter { terms })
+    }
+
+    pub fn matches(&self, process: &ProcessSample) -> bool {
+        self.terms.iter().all(|term| match term {
+            Term::Name(name) if name.contains(['*', '?']) => glob_match(name, &process.name.to_lowercase()),
+            Term::Name(name) => process.name.to_lowercase().contains(name.as_str()),
+            Term::User(user) => process.user.as_deref() == Some(user.as_str()),
+            Term::Regex(re) => re.is_match(&process.name) || re.is_match(&process.cmdline),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::test_process;
+
+    fn processes() -> Vec<ProcessSample> {
+        let mut firefox = test_process(30, "firefox", 12.5, 800);
+        firefox.user = Some("ana".to_string());
+        firefox.cmdline = "/usr/lib/firefox/firefox -contentproc".to_string();
+        let mut python = test_process(10, "python3", 50.0, 200);
+        python.user = Some("root".to_string());
+        python.cmdline = "python3 servidor.py --puerto 8080".to_string();
+        let mut bash = test_process(20, "Bash", 0.0, 200);
+        bash.user = Some("ana".to_string());
+        vec![firefox, python, bash]
+    }
+
+    fn matching(filter: &str) -> Vec<u32> {
+        let filter = Filter::parse(filter).unwrap();
+        processes().iter().filter(|p| filter.matches(p)).map(|p| p.pid).collect()
+    }
+
+    fn sorted(column: Column, descending: bool) -> Vec<u32> {
+        let processes = processes();
+        let mut refs: Vec<&ProcessSample> = processes.iter().collect();
+        sort(&mut refs, column, descending);
+        refs.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        assert_eq!(matching(""), vec![30, 10, 20]);
+        assert_eq!(matching("   "), vec![30, 10, 20]);
+    }
+
+    #[test]
+    fn name_substring_ignores_case() {
+        assert_eq!(matching("fox"), vec![30]);
+        assert_eq!(matching("BASH"), vec![20]);
+        // Sin comodines no mira la línea de comandos
+        assert_eq!(matching("servidor"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn name_globs_cover_the_whole_name() {
+        assert_eq!(matching("py*"), vec![10]);
+        assert_eq!(matching("b?sh"), vec![20]);
+        assert_eq!(matching("fire"), vec![30]);
+        assert_eq!(matching("fire?"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn user_is_exact() {
+        assert_eq!(matching("user:ana"), vec![30, 20]);
+        assert_eq!(matching("user:an"), Vec::<u32>::new());
+        assert_eq!(matching("user:"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn regex_searches_name_and_cmdline() {
+        assert_eq!(matching("/--puerto.\\d+/"), vec![10]);
+        assert_eq!(matching("/^(firefox|Bash)$/"), vec![30, 20]);
+        assert!(Filter::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn all_terms_must_match() {
+        assert_eq!(matching("user:ana b*"), vec![20]);
+        assert_eq!(matching("user:ana /contentproc/"), vec![30]);
+        assert_eq!(matching("user:root fox"), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn sorts_by_column() {
+        assert_eq!(sorted(Column::Pid, false), vec![10, 20, 30]);
+        assert_eq!(sorted(Column::Cpu, true), vec![10, 30, 20]);
+        // Los nombres no distinguen mayúsculas
+        assert_eq!(sorted(Column::Name, false), vec![20, 30, 10]);
+        assert_eq!(sorted(Column::User, false), vec![20, 30, 10]);
+    }
+
+    #[test]
+    fn ties_break_by_pid() {
+        assert_eq!(sorted(Column::Memory, false), vec![10, 20, 30]);
+        assert_eq!(sorted(Column::Memory, true), vec![30, 20, 10]);
+    }
+}
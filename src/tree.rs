@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+
+use crate::collector::ProcessSample;
+
+// Proceso del árbol con el uso sumado de sus descendientes.
+pub struct Node<'a> {
+    pub process: &'a ProcessSample,
+    pub children: Vec<usize>,
+    // Incluyen al propio proceso
+    pub total_cpu: f32,
+    pub total_memory: u64,
+    pub descendants: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortKey {
+    Cpu,
+    Memory,
+    Name,
+    Pid,
+}
+
+pub struct ProcessTree<'a> {
+    pub nodes: Vec<Node<'a>>,
+    pub roots: Vec<usize>,
+}
+
+impl<'a> ProcessTree<'a> {
+    // Un proceso cuelga de su padre si el padre está en la muestra y no es
+    // más nuevo que él; si no, el pid del padre ya es de otro proceso.
+    pub fn build(processes: &'a [ProcessSample]) -> Self {
+        let index: HashMap<u32, usize> = processes.iter().enumerate().map(|(i, p)| (p.pid, i)).collect();
+        let mut nodes: Vec<Node> = processes
+            .iter()
+            .map(|p| Node {
+                process: p,
+                children: Vec::new(),
+                total_cpu: p.cpu,
+                total_memory: p.memory,
+                descendants: 0,
+            })
+            .collect();
+
+        let mut roots = Vec::new();
+        for (i, process) in processes.iter().enumerate() {
+            let parent = process
+                .parent
+                .filter(|parent| *parent != process.pid)
+                .and_then(|parent| index.get(&parent).copied())
+                .filter(|&parent| processes[parent].start_time <= process.start_time);
+            match parent {
+                Some(parent) => nodes[parent].children.push(i),
+                None => roots.push(i),
+            }
+        }
+
+        // Los hijos se suman antes que los padres. Con la condición de
+        // arriba solo puede haber ciclos entre procesos del mismo segundo:
+        // lo que no se alcanza desde las raíces está en uno, y el primero de
+        // cada ciclo pasa a ser una raíz
+        let mut order = Vec::with_capacity(nodes.len());
+        let mut visited = vec![false; nodes.len()];
+        for &root in &roots {
+            walk(&nodes, root, &mut visited, &mut order);
+        }
+        for (start, process) in processes.iter().enumerate() {
+            if visited[start] {
+                continue;
+            }
+            if let Some(&parent) = process.parent.as_ref().and_then(|p| index.get(p)) {
+                nodes[parent].children.retain(|&c| c != start);
+            }
+            roots.push(start);
+            walk(&nodes, start, &mut visited, &mut order);
+        }
+        for &i in order.iter().rev() {
+            let (cpu, memory, descendants) = nodes[i].children.iter().fold((0.0, 0, 0), |(cpu, memory, count), &c| {
+                let child = &nodes[c];
+                (cpu + child.total_cpu, memory + child.total_memory, count + 1 + child.descendants)
+            });
+            let node = &mut nodes[i];
+            node.total_cpu += cpu;
+            node.total_memory += memory;
+            node.descendants = descendants;
+        }
+
+        ProcessTree { nodes, roots }
+    }
+
+    // Ordena las raíces y los hijos de cada nodo por el uso sumado.
+    pub fn sort(&mut self, key: SortKey, descending: bool) {
+        let nodes = &self.nodes;
+        let compare = |a: &usize, b: &usize| {
+            let (a, b) = (&nodes[*a], &nodes[*b]);
+            let order = match key {
+                SortKey::Cpu => a.total_cpu.total_cmp(&b.total_cpu),
+                SortKey::Memory => a.total_memory.cmp(&b.total_memory),
+                SortKey::Name => a.process.name.to_lowercase().cmp(&b.process.name.to_lowercase()),
+                SortKey::Pid => a.process.pid.cmp(&b.process.pid),
+            };
+            let order = order.then(a.process.pid.cmp(&b.process.pid));
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        };
+
+        let mut children: Vec<Vec<usize>> = self.nodes.iter().map(|n| n.children.clone()).collect();
+        for list in &mut children {
+            list.sort_by(compare);
+        }
+        self.roots.sort_by(compare);
+        for (node, list) in self.nodes.iter_mut().zip(children) {
+            node.children = list;
+        }
+    }
+
+    // Filas visibles en orden: (nodo, profundidad). Solo se recorren los
+    // hijos de los nodos para los que `expanded` devuelve true.
+    pub fn visible(&self, expanded: impl Fn(&ProcessSample) -> bool) -> Vec<(usize, usize)> {
+        let mut rows = Vec::new();
+        let mut stack: Vec<(usize, usize)> = self.roots.iter().rev().map(|&r| (r, 0)).collect();
+        let mut visited = vec![false; self.nodes.len()];
+        while let Some((i, depth)) = stack.pop() {
+            if std::mem::replace(&mut visited[i], true) {
+                continue;
+            }
+            rows.push((i, depth));
+            let node = &self.nodes[i];
+            if expanded(node.process) {
+                stack.extend(node.children.iter().rev().map(|&c| (c, depth + 1)));
+            }
+        }
+        rows
+    }
+}
+
+// Agrega a `order` los nodos alcanzables desde `start`, padres antes que hijos.
+fn walk(nodes: &[Node], start: usize, visited: &mut [bool], order: &mut Vec<usize>) {
+    let mut pending = vec![start];
+    while let Some(i) = pending.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            continue;
+        }
+        order.push(i);
+        pending.extend(nodes[i].children.iter().copied());
+    }
+}
+
+impl SortKey {
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Cpu => "CPU %",
+            SortKey::Memory => "RAM (MB)",
+            SortKey::Name => "Nombre",
+            SortKey::Pid => "PID",
+        }
+    }
+
+    // Los números empiezan de mayor a menor y los textos en orden alfabético
+    pub fn descending_by_default(self) -> bool {
+        matches!(self, SortKey::Cpu | SortKey::Memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collector::test_process;
+
+    fn process(pid: u32, parent: Option<u32>, start_time: u64, cpu: f32, memory: u64) -> ProcessSample {
+        let mut process = test_process(pid, &format!("p{pid}"), cpu, memory);
+        process.parent = parent;
+        process.start_time = start_time;
+        process
+    }
+
+    fn pids(tree: &ProcessTree, indexes: &[usize]) -> Vec<u32> {
+        indexes.iter().map(|&i| tree.nodes[i].process.pid).collect()
+    }
+
+    fn node<'t, 'a>(tree: &'t ProcessTree<'a>, pid: u32) -> &'t Node<'a> {
+        tree.nodes.iter().find(|n| n.process.pid == pid).unwrap()
+    }
+
+    #[test]
+    fn sums_descendants() {
+        let processes = vec![
+            process(1, None, 10, 1.0, 100),
+            process(2, Some(1), 20, 2.0, 200),
+            process(3, Some(2), 30, 4.0, 400),
+            process(4, Some(2), 30, 8.0, 800),
+            process(5, Some(1), 40, 16.0, 1600),
+        ];
+        let tree = ProcessTree::build(&processes);
+        assert_eq!(pids(&tree, &tree.roots), vec![1]);
+
+        let root = node(&tree, 1);
+        assert_eq!(root.total_cpu, 31.0);
+        assert_eq!(root.total_memory, 3100);
+        assert_eq!(root.descendants, 4);
+        assert_eq!(pids(&tree, &root.children), vec![2, 5]);
+
+        let middle = node(&tree, 2);
+        assert_eq!(middle.total_cpu, 14.0);
+        assert_eq!(middle.total_memory, 1400);
+        assert_eq!(middle.descendants, 2);
+
+        let leaf = node(&tree, 3);
+        assert_eq!((leaf.total_cpu, leaf.total_memory, leaf.descendants), (4.0, 400, 0));
+    }
+
+    #[test]
+    fn missing_or_own_parent_is_a_root() {
+        let processes = vec![process(0, Some(0), 0, 1.0, 1), process(7, Some(99), 5, 1.0, 1)];
+        let tree = ProcessTree::build(&processes);
+        assert_eq!(pids(&tree, &tree.roots), vec![0, 7]);
+        assert!(tree.nodes.iter().all(|n| n.descendants == 0));
+    }
+
+    #[test]
+    fn parent_newer_than_child_is_a_reused_pid() {
+        // El padre original de 5 terminó y su pid lo tomó un proceso posterior
+        let processes = vec![process(4, None, 200, 1.0, 10), process(5, Some(4), 100, 2.0, 20)];
+        let tree = ProcessTree::build(&processes);
+        assert_eq!(pids(&tree, &tree.roots), vec![4, 5]);
+        assert_eq!(node(&tree, 4).descendants, 0);
+        assert_eq!(node(&tree, 4).total_cpu, 1.0);
+    }
+
+    #[test]
+    fn same_second_cycle_gets_a_root() {
+        let processes = vec![
+            process(1, Some(2), 100, 1.0, 10),
+            process(2, Some(1), 100, 2.0, 20),
+            process(3, Some(2), 100, 4.0, 40),
+        ];
+        let tree = ProcessTree::build(&processes);
+        assert_eq!(pids(&tree, &tree.roots), vec![1]);
+        let root = node(&tree, 1);
+        assert_eq!(root.descendants, 2);
+        assert_eq!(root.total_cpu, 7.0);
+        assert_eq!(root.total_memory, 70);
+        assert_eq!(pids(&tree, &node(&tree, 2).children), vec![3]);
+
+        let rows = tree.visible(|_| true);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows.iter().map(|&(_, depth)| depth).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn sort_orders_roots_and_children() {
+        let processes = vec![
+            process(1, None, 10, 1.0, 100),
+            process(2, Some(1), 20, 5.0, 100),
+            process(3, Some(1), 20, 9.0, 100),
+            process(4, None, 10, 3.0, 100),
+        ];
+        let mut tree = ProcessTree::build(&processes);
+        tree.sort(SortKey::Cpu, true);
+        assert_eq!(pids(&tree, &tree.roots), vec![1, 4]);
+        assert_eq!(pids(&tree, &node(&tree, 1).children), vec![3, 2]);
+
+        let rows = tree.visible(|p| p.pid != 1);
+        assert_eq!(pids(&tree, &rows.iter().map(|&(i, _)| i).collect::<Vec<_>>()), vec![1, 4]);
+    }
+}
@@ -0,0 +1,334 @@
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+
+use crate::collector::{ProcessSample, Sample};
+use crate::config::deserialize_duration;
+use crate::notify::exec;
+use crate::process::{Matcher, ProcessMatcher};
+
+// Proceso vigilado entre muestras. Si hay varios que coinciden se sigue el
+// más antiguo, así los hijos que van y vienen no cuentan como reinicios.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchConfig {
+    pub id: String,
+    pub process: ProcessMatcher,
+    // Comando (sin shell) que se ejecuta cuando el proceso termina
+    #[serde(default)]
+    pub restart: Option<Vec<String>>,
+    // Si el comando de reinicio no termina en este tiempo se lo mata
+    #[serde(default = "default_restart_timeout", deserialize_with = "deserialize_duration")]
+    pub restart_timeout: Duration,
+    #[serde(default)]
+    pub notify: Option<Vec<String>>,
+}
+
+fn default_restart_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+impl WatchConfig {
+    pub fn describe(&self) -> String {
+        let mut text = format!("vigila procesos {}", self.process.describe());
+        if let Some(restart) = &self.restart {
+            text.push_str(&format!(" (reinicio: {})", restart.join(" ")));
+        }
+        text
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessEventKind {
+    Started,
+    Exited,
+    Restarted,
+}
+
+impl ProcessEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProcessEventKind::Started => "started",
+            ProcessEventKind::Exited => "exited",
+            ProcessEventKind::Restarted => "restarted",
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text {
+            "started" => Some(ProcessEventKind::Started),
+            "exited" => Some(ProcessEventKind::Exited),
+            "restarted" => Some(ProcessEventKind::Restarted),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessEventKind::Started => "iniciado",
+            ProcessEventKind::Exited => "terminado",
+            ProcessEventKind::Restarted => "reiniciado",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ProcessEvent {
+    pub watch_id: String,
+    pub kind: ProcessEventKind,
+    // El que empezó o, si terminó, el que terminó
+    pub pid: u32,
+    // Solo en los reinicios
+    pub old_pid: Option<u32>,
+    pub name: String,
+    pub timestamp: DateTime<Local>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Seen {
+    pid: u32,
+    start_time: u64,
+    name: String,
+}
+
+impl Seen {
+    fn new(process: &ProcessSample) -> Self {
+        Seen {
+            pid: process.pid,
+            start_time: process.start_time,
+            name: process.name.clone(),
+        }
+    }
+
+    fn is(&self, process: &ProcessSample) -> bool {
+        self.pid == process.pid && self.start_time == process.start_time
+    }
+}
+
+struct Watch {
+    config: WatchConfig,
+    matcher: Option<Matcher>,
+    // `None` hasta la primera muestra, que solo fija el estado inicial
+    seen: Option<Option<Seen>>,
+}
+
+impl Watch {
+    fn new(config: &WatchConfig) -> Self {
+        let matcher = config.process.compile();
+        if let Err(e) = &matcher {
+            eprintln!("Vigilancia `{}` ignorada: process.cmdline: {}", config.id, e);
+        }
+        Watch {
+            config: config.clone(),
+            matcher: matcher.ok(),
+            seen: None,
+        }
+    }
+
+    fn observe(&mut self, sample: &Sample) -> Option<ProcessEvent> {
+        let matcher = self.matcher.as_ref()?;
+        let matching: Vec<&ProcessSample> = sample.processes.iter().filter(|p| matcher.matches(p)).collect();
+        let oldest = matching.iter().min_by_key(|p| (p.start_time, p.pid)).map(|p| Seen::new(p));
+
+        let event = |kind, seen: &Seen, old_pid| ProcessEvent {
+            watch_id: self.config.id.clone(),
+            kind,
+            pid: seen.pid,
+            old_pid,
+            name: seen.name.clone(),
+            timestamp: sample.timestamp,
+        };
+
+        let (event, seen) = match (self.seen.take(), oldest) {
+            (None, oldest) => (None, oldest),
+            (Some(None), None) => (None, None),
+            (Some(None), Some(new)) => (Some(event(ProcessEventKind::Started, &new, None)), Some(new)),
+            (Some(Some(old)), None) => (Some(event(ProcessEventKind::Exited, &old, None)), None),
+            (Some(Some(old)), Some(_)) if matching.iter().any(|p| old.is(p)) => (None, Some(old)),
+            // Terminó el que se seguía pero sigue uno anterior (que antes no
+            // coincidía): no es un reinicio ni una terminación, se sigue al otro
+            (Some(Some(old)), Some(new)) if new.start_time < old.start_time => (None, Some(new)),
+            (Some(Some(old)), Some(new)) => {
+                (Some(event(ProcessEventKind::Restarted, &new, Some(old.pid))), Some(new))
+            }
+        };
+        self.seen = Some(seen);
+        event
+    }
+}
+
+pub struct Watchdog {
+    watches: Vec<Watch>,
+}
+
+impl Watchdog {
+    pub fn new(configs: &[WatchConfig]) -> Self {
+        Watchdog {
+            watches: configs.iter().map(Watch::new).collect(),
+        }
+    }
+
+    // Las vigilancias sin cambios conservan el proceso que seguían.
+    pub fn reconfigure(&mut self, configs: &[WatchConfig]) {
+        let mut previous = std::mem::take(&mut self.watches);
+        for config in configs {
+            match previous.iter().position(|w| w.config == *config) {
+                Some(i) => self.watches.push(previous.swap_remove(i)),
+                None => self.watches.push(Watch::new(config)),
+            }
+        }
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.watches.iter().map(|w| w.config.id.as_str())
+    }
+
+    pub fn observe(&mut self, sample: &Sample) -> Vec<(WatchConfig, ProcessEvent)> {
+        self.watches
+            .iter_mut()
+            .filter_map(|w| Some((w.config.clone(), w.observe(sample)?)))
+            .collect()
+    }
+}
+
+// Corre el comando de reinicio en otro hilo, con `restart_timeout` como
+// límite; el resultado solo se informa.
+pub fn restart(watch: &WatchConfig, event: &ProcessEvent) {
+    let Some((program, args)) = watch.restart.as_ref().and_then(|c| c.split_first()) else {
+        return;
+    };
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .env("METRICAS_WATCH_ID", &event.watch_id)
+        .env("METRICAS_PID", event.pid.to_string())
+        .env("METRICAS_NAME", &event.name);
+    let (id, timeout) = (watch.id.clone(), watch.restart_timeout);
+
+    thread::spawn(move || match exec::run(&mut command, timeout) {
+        Ok(status) if status.success() => println!("Vigilancia `{}`: comando de reinicio ejecutado", id),
+        Ok(status) => eprintln!("Vigilancia `{}`: el comando de reinicio terminó con {}", id, status),
+        Err(e) => eprintln!("Vigilancia `{}`: no se pudo ejecutar el comando de reinicio: {}", id, e),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration as TimeDelta;
+
+    use super::*;
+    use crate::collector::{test_process, test_sample};
+
+    fn watchdog() -> Watchdog {
+        Watchdog::new(&[WatchConfig {
+            id: "db".to_string(),
+            process: ProcessMatcher { name: Some("postgres".to_string()), ..Default::default() },
+            restart: None,
+            restart_timeout: default_restart_timeout(),
+            notify: None,
+        }])
+    }
+
+    // (pid, inicio) de los procesos que coinciden; `bash` no coincide nunca
+    fn sample(second: i64, processes: &[(u32, u64)]) -> Sample {
+        let mut sample = test_sample();
+        sample.timestamp += TimeDelta::seconds(second);
+        sample.processes = processes
+            .iter()
+            .map(|&(pid, start_time)| ProcessSample { start_time, ..test_process(pid, "postgres", 0.0, 0) })
+            .chain(std::iter::once(test_process(1, "bash", 0.0, 0)))
+            .collect();
+        sample
+    }
+
+    // Los eventos como (tipo, pid, pid anterior)
+    type Event = (ProcessEventKind, u32, Option<u32>);
+
+    fn events(watchdog: &mut Watchdog, second: i64, processes: &[(u32, u64)]) -> Vec<Event> {
+        watchdog
+            .observe(&sample(second, processes))
+            .into_iter()
+            .map(|(watch, e)| {
+                assert_eq!((watch.id.as_str(), e.watch_id.as_str(), e.name.as_str()), ("db", "db", "postgres"));
+                assert_eq!(e.timestamp, sample(second, &[]).timestamp);
+                (e.kind, e.pid, e.old_pid)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_sample_sets_the_state_silently() {
+        let mut running = watchdog();
+        assert!(events(&mut running, 0, &[(10, 100)]).is_empty());
+        assert!(events(&mut running, 1, &[(10, 100)]).is_empty());
+
+        let mut absent = watchdog();
+        assert!(events(&mut absent, 0, &[]).is_empty());
+        assert!(events(&mut absent, 1, &[]).is_empty());
+    }
+
+    #[test]
+    fn start_exit_and_restart() {
+        use ProcessEventKind::*;
+
+        let mut watchdog = watchdog();
+        events(&mut watchdog, 0, &[]);
+        assert_eq!(events(&mut watchdog, 1, &[(10, 100)]), vec![(Started, 10, None)]);
+        assert_eq!(events(&mut watchdog, 2, &[]), vec![(Exited, 10, None)]);
+        assert_eq!(events(&mut watchdog, 3, &[(11, 103)]), vec![(Started, 11, None)]);
+        // Otro pid entre dos muestras
+        assert_eq!(events(&mut watchdog, 4, &[(12, 104)]), vec![(Restarted, 12, Some(11))]);
+        // El mismo pid con otra hora de inicio
+        assert_eq!(events(&mut watchdog, 5, &[(12, 105)]), vec![(Restarted, 12, Some(12))]);
+        assert!(events(&mut watchdog, 6, &[(12, 105)]).is_empty());
+    }
+
+    #[test]
+    fn children_do_not_count_as_restarts() {
+        use ProcessEventKind::*;
+
+        let mut watchdog = watchdog();
+        events(&mut watchdog, 0, &[(10, 100)]);
+        // Hijos más nuevos que van y vienen
+        assert!(events(&mut watchdog, 1, &[(10, 100), (20, 101), (21, 101)]).is_empty());
+        assert!(events(&mut watchdog, 2, &[(10, 100), (21, 101)]).is_empty());
+        assert!(events(&mut watchdog, 3, &[(10, 100)]).is_empty());
+
+        // Si termina el que se seguía y queda uno más nuevo, es un reinicio
+        assert!(events(&mut watchdog, 4, &[(10, 100), (22, 104)]).is_empty());
+        assert_eq!(events(&mut watchdog, 5, &[(22, 104)]), vec![(Restarted, 22, Some(10))]);
+    }
+
+    #[test]
+    fn older_survivor_is_followed_without_events() {
+        use ProcessEventKind::*;
+
+        let mut watchdog = watchdog();
+        events(&mut watchdog, 0, &[(10, 100)]);
+        // Terminó el seguido, pero sigue uno anterior que antes no se veía
+        assert!(events(&mut watchdog, 1, &[(5, 50)]).is_empty());
+        assert!(events(&mut watchdog, 2, &[(5, 50), (30, 102)]).is_empty());
+        assert_eq!(events(&mut watchdog, 3, &[]), vec![(Exited, 5, None)]);
+    }
+
+    #[test]
+    fn reconfigure_keeps_unchanged_watches() {
+        use ProcessEventKind::*;
+
+        let mut watchdog = watchdog();
+        events(&mut watchdog, 0, &[(10, 100)]);
+        let configs: Vec<WatchConfig> = watchdog.watches.iter().map(|w| w.config.clone()).collect();
+        watchdog.reconfigure(&configs);
+        assert_eq!(events(&mut watchdog, 1, &[]), vec![(Exited, 10, None)]);
+
+        // Una vigilancia cambiada empieza de nuevo y toma el estado sin avisar
+        let changed = WatchConfig { notify: Some(vec!["desktop".to_string()]), ..configs[0].clone() };
+        watchdog.reconfigure(&[changed]);
+        assert!(events(&mut watchdog, 2, &[(11, 102)]).is_empty());
+        assert_eq!(watchdog.ids().collect::<Vec<_>>(), vec!["db"]);
+    }
+}